@@ -1,9 +1,10 @@
+use crate::sip::audit::Decision;
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "sip")]
 #[command(about = "safe install proxy - a trusted registry wrapper for package managers")]
-#[command(long_about = "sip is a drop-in cli wrapper for native package managers (pip, cargo, go) that enforces trusted registry checks before installation")]
+#[command(long_about = "sip is a drop-in cli wrapper for native package managers (pip, cargo, go, npm, gem, composer) that enforces trusted registry checks before installation")]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
@@ -29,6 +30,10 @@ pub enum Commands {
         #[arg(short, long)]
         yes: bool,
 
+        /// require a valid provenance attestation built from the trusted source repository
+        #[arg(long)]
+        provenance: bool,
+
         /// pass additional arguments to the underlying package manager
         #[arg(last = true)]
         extra_args: Vec<String>,
@@ -47,6 +52,56 @@ pub enum Commands {
         /// explicitly specify the language/ecosystem
         #[arg(short, long, value_enum)]
         lang: Option<Language>,
+
+        /// also check the provenance attestation upstream publishes for the artifact
+        #[arg(long)]
+        provenance: bool,
+    },
+
+    /// download a package and scan the code it runs at install or build time for risky patterns
+    Scan {
+        /// name of the package to scan
+        #[arg(value_name = "PACKAGE")]
+        package: String,
+
+        /// specific version to scan (optional, defaults to latest)
+        #[arg(short, long)]
+        version: Option<String>,
+
+        /// explicitly specify the language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// diff the published artifacts of two versions of a package
+    Diff {
+        /// name of the package
+        #[arg(value_name = "PACKAGE")]
+        package: String,
+
+        /// version currently trusted
+        #[arg(value_name = "OLD")]
+        old: String,
+
+        /// version to compare it with
+        #[arg(value_name = "NEW")]
+        new: String,
+
+        /// explicitly specify the language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// only list changed files, without the unified diff
+        #[arg(long)]
+        stat: bool,
+
+        /// output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
     },
 
     /// add a package to the trusted registry
@@ -63,13 +118,17 @@ pub enum Commands {
         #[arg(short, long, value_enum)]
         lang: Option<Language>,
 
-        /// fetch metadata from package registry (pypi, crates.io, etc)
+        /// fetch metadata from package registry (pypi, crates.io, npm, rubygems, packagist, etc)
         #[arg(short, long)]
         fetch: bool,
 
         /// trust score to assign (0.0 - 10.0)
         #[arg(short, long, default_value = "5.0")]
         score: f64,
+
+        /// registry layer to record the package in (personal, project, community or a configured layer)
+        #[arg(long, default_value = "personal")]
+        layer: String,
     },
 
     /// remove a package from the trusted registry
@@ -81,6 +140,52 @@ pub enum Commands {
         /// explicitly specify the language/ecosystem
         #[arg(short, long, value_enum)]
         lang: Option<Language>,
+
+        /// registry layer to remove the package from
+        #[arg(long, default_value = "personal")]
+        layer: String,
+    },
+
+    /// record a known-malicious or banned package so it can never be installed
+    Block {
+        /// name of the package to block
+        #[arg(value_name = "PACKAGE")]
+        package: String,
+
+        /// why the package is blocked
+        #[arg(short, long)]
+        reason: String,
+
+        /// affected version, may be repeated (defaults to every version)
+        #[arg(short, long = "version")]
+        versions: Vec<String>,
+
+        /// advisory or report describing the issue
+        #[arg(long)]
+        reference: Option<String>,
+
+        /// explicitly specify the language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// registry layer to record the block in
+        #[arg(long, default_value = "personal")]
+        layer: String,
+    },
+
+    /// remove a package from the blocklist
+    Unblock {
+        /// name of the package to unblock
+        #[arg(value_name = "PACKAGE")]
+        package: String,
+
+        /// explicitly specify the language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// registry layer to remove the block from
+        #[arg(long, default_value = "personal")]
+        layer: String,
     },
 
     /// list trusted packages in the registry
@@ -88,6 +193,119 @@ pub enum Commands {
         /// filter by language/ecosystem
         #[arg(short, long, value_enum)]
         lang: Option<Language>,
+
+        /// only show packages recorded in this registry layer instead of the merged view
+        #[arg(long)]
+        layer: Option<String>,
+
+        /// list blocked packages instead of trusted ones
+        #[arg(long)]
+        blocked: bool,
+    },
+
+    /// check every dependency declared in the project's manifests against the trusted registry
+    Vet {
+        /// project directory to scan
+        #[arg(short, long, default_value = ".")]
+        path: String,
+
+        /// only vet dependencies of this language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// vet the components of a cyclonedx or spdx json sbom instead of a project
+        #[arg(long, conflicts_with_all = ["path", "locked"])]
+        sbom: Option<String>,
+
+        /// vet the resolved dependencies from the project's lockfiles, not just the declared ones
+        #[arg(long)]
+        locked: bool,
+    },
+
+    /// report the licenses of every dependency declared in the project's manifests
+    Licenses {
+        /// project directory to scan
+        #[arg(short, long, default_value = ".")]
+        path: String,
+
+        /// only report dependencies of this language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// look up licenses upstream for dependencies the registry has none for
+        #[arg(long)]
+        fetch: bool,
+
+        /// output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// write a cyclonedx or spdx sbom of the project's resolved dependencies
+    Sbom {
+        /// project directory to scan
+        #[arg(short, long, default_value = ".")]
+        path: String,
+
+        /// only include dependencies of this language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// sbom format
+        #[arg(long, value_enum, default_value = "cyclonedx")]
+        format: SbomFormat,
+
+        /// write the sbom to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// show when trusted packages were last reviewed, oldest first
+    Review {
+        /// only list packages past their configured review age
+        #[arg(long)]
+        stale: bool,
+
+        /// filter by language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+    },
+
+    /// show recorded sip decisions from the audit log, newest last
+    Log {
+        /// only events for this package
+        #[arg(short, long)]
+        package: Option<String>,
+
+        /// only events for this language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// only events from this sip command (install, verify, trust, ...)
+        #[arg(short, long)]
+        command: Option<String>,
+
+        /// only events with this decision
+        #[arg(short, long, value_enum)]
+        decision: Option<Decision>,
+
+        /// only events on or after this date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// number of most recent matching events to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// print matching events as json lines
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// inspect and maintain registry data files
+    Registry {
+        #[command(subcommand)]
+        command: RegistryCommands,
     },
 
     /// bulk fetch package metadata and add to registry
@@ -103,14 +321,111 @@ pub enum Commands {
         /// default trust score to assign (0.0 - 10.0)
         #[arg(short, long, default_value = "5.0")]
         score: f64,
+
+        /// registry layer to record the packages in
+        #[arg(long, default_value = "personal")]
+        layer: String,
     },
 }
 
-#[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
+#[derive(Subcommand)]
+pub enum RegistryCommands {
+    /// report semantic changes between two registry snapshots (data files or registry/data directories)
+    Diff {
+        /// the snapshot before the change
+        #[arg(value_name = "OLD")]
+        old: String,
+
+        /// the snapshot after the change
+        #[arg(value_name = "NEW")]
+        new: String,
+
+        /// only compare this language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// re-check recorded hashes and owners against the upstream registries (pypi, crates.io, goproxy, npm)
+    Audit {
+        /// registry data directory
+        #[arg(value_name = "PATH", default_value = "registry/data")]
+        path: String,
+
+        /// only audit this language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// replace mismatched hashes with upstream's, record yanks and replace non-repository sources, leaving scores and reviews untouched
+        #[arg(long)]
+        fix: bool,
+
+        /// report packages this many releases behind upstream
+        #[arg(long, default_value = "5")]
+        behind: usize,
+
+        /// also download each artifact and check that its metadata declares the recorded source repository
+        #[arg(long)]
+        sources: bool,
+
+        /// output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// check registry data files for schema errors, duplicates, placeholders and policy violations
+    Lint {
+        /// registry data directory or a single data file
+        #[arg(value_name = "PATH", default_value = "registry/data")]
+        path: String,
+
+        /// only lint this language/ecosystem
+        #[arg(short, long, value_enum)]
+        lang: Option<Language>,
+
+        /// output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SbomFormat {
+    #[value(name = "cyclonedx")]
+    CycloneDx,
+    Spdx,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Language {
     Python,
     Rust,
     Go,
+    Node,
+    Java,
+    Ruby,
+    Php,
+}
+
+impl Language {
+    pub const ALL: [Language; 7] = [
+        Language::Python,
+        Language::Rust,
+        Language::Go,
+        Language::Node,
+        Language::Java,
+        Language::Ruby,
+        Language::Php,
+    ];
 }
 
 impl std::fmt::Display for Language {
@@ -119,6 +434,10 @@ impl std::fmt::Display for Language {
             Language::Python => write!(f, "python"),
             Language::Rust => write!(f, "rust"),
             Language::Go => write!(f, "go"),
+            Language::Node => write!(f, "node"),
+            Language::Java => write!(f, "java"),
+            Language::Ruby => write!(f, "ruby"),
+            Language::Php => write!(f, "php"),
         }
     }
 }
@@ -182,6 +501,108 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_provenance_flags() {
+        let cli = Cli::try_parse_from(vec!["sip", "verify", "sigstore", "--provenance"]).unwrap();
+        assert!(matches!(cli.command, Commands::Verify { provenance: true, .. }));
+
+        let cli = Cli::try_parse_from(vec!["sip", "install", "sigstore", "--provenance", "--yes"]).unwrap();
+        assert!(matches!(cli.command, Commands::Install { provenance: true, yes: true, .. }));
+    }
+
+    #[test]
+    fn test_install_scoped_node_package() {
+        let cli = Cli::try_parse_from(vec![
+            "sip", "install", "@types/node", "--lang", "node",
+        ]).unwrap();
+        match cli.command {
+            Commands::Install { package, lang, .. } => {
+                assert_eq!(package, "@types/node");
+                assert!(matches!(lang, Some(Language::Node)));
+            }
+            _ => panic!("expected install command"),
+        }
+    }
+
+    #[test]
+    fn test_vet_command() {
+        let cli = Cli::try_parse_from(vec![
+            "sip", "vet", "--path", "environments/java-project", "--lang", "java",
+        ]).unwrap();
+        match cli.command {
+            Commands::Vet { path, lang, sbom, locked } => {
+                assert_eq!(path, "environments/java-project");
+                assert!(matches!(lang, Some(Language::Java)));
+                assert!(sbom.is_none());
+                assert!(!locked);
+            }
+            _ => panic!("expected vet command"),
+        }
+
+        let cli = Cli::try_parse_from(vec!["sip", "vet", "--sbom", "vendor.cdx.json"]).unwrap();
+        assert!(matches!(cli.command, Commands::Vet { sbom: Some(ref file), .. } if file == "vendor.cdx.json"));
+        assert!(Cli::try_parse_from(vec!["sip", "vet", "--sbom", "vendor.cdx.json", "--locked"]).is_err());
+    }
+
+    #[test]
+    fn test_licenses_command() {
+        let cli = Cli::try_parse_from(vec!["sip", "licenses", "--lang", "rust", "--fetch", "--format", "json"]).unwrap();
+        match cli.command {
+            Commands::Licenses { path, lang, fetch, format } => {
+                assert_eq!(path, ".");
+                assert!(matches!(lang, Some(Language::Rust)));
+                assert!(fetch);
+                assert!(matches!(format, OutputFormat::Json));
+            }
+            _ => panic!("expected licenses command"),
+        }
+    }
+
+    #[test]
+    fn test_sbom_command() {
+        let cli = Cli::try_parse_from(vec!["sip", "sbom", "--format", "spdx", "-o", "app.spdx.json"]).unwrap();
+        match cli.command {
+            Commands::Sbom { path, lang, format, output } => {
+                assert_eq!(path, ".");
+                assert!(lang.is_none());
+                assert_eq!(format, SbomFormat::Spdx);
+                assert_eq!(output.as_deref(), Some("app.spdx.json"));
+            }
+            _ => panic!("expected sbom command"),
+        }
+        let cli = Cli::try_parse_from(vec!["sip", "sbom"]).unwrap();
+        assert!(matches!(cli.command, Commands::Sbom { format: SbomFormat::CycloneDx, .. }));
+    }
+
+    #[test]
+    fn test_scan_command() {
+        let cli = Cli::try_parse_from(vec!["sip", "scan", "left-pad", "-v", "1.3.0", "--format", "json"]).unwrap();
+        match cli.command {
+            Commands::Scan { package, version, lang, format } => {
+                assert_eq!(package, "left-pad");
+                assert_eq!(version.as_deref(), Some("1.3.0"));
+                assert!(lang.is_none());
+                assert_eq!(format, OutputFormat::Json);
+            }
+            _ => panic!("expected scan command"),
+        }
+    }
+
+    #[test]
+    fn test_diff_command() {
+        let cli = Cli::try_parse_from(vec!["sip", "diff", "serde", "1.0.188", "1.0.190", "--lang", "rust", "--stat"]).unwrap();
+        match cli.command {
+            Commands::Diff { package, old, new, lang, stat, format } => {
+                assert_eq!(package, "serde");
+                assert_eq!((old.as_str(), new.as_str()), ("1.0.188", "1.0.190"));
+                assert!(matches!(lang, Some(Language::Rust)));
+                assert!(stat);
+                assert_eq!(format, OutputFormat::Text);
+            }
+            _ => panic!("expected diff command"),
+        }
+    }
+
     #[test]
     fn test_install_with_extra_args() {
         let cli = Cli::try_parse_from(vec![
@@ -195,4 +616,60 @@ mod tests {
             _ => panic!("expected install command"),
         }
     }
+
+    #[test]
+    fn test_trust_defaults_to_personal_layer() {
+        let cli = Cli::try_parse_from(vec!["sip", "trust", "flask"]).unwrap();
+        match cli.command {
+            Commands::Trust { layer, .. } => assert_eq!(layer, "personal"),
+            _ => panic!("expected trust command"),
+        }
+    }
+
+    #[test]
+    fn test_block_command() {
+        let cli = Cli::try_parse_from(vec![
+            "sip", "block", "event-stream", "--reason", "malicious flatmap-stream dependency",
+            "--version", "3.3.6", "--lang", "node",
+        ]).unwrap();
+        match cli.command {
+            Commands::Block { package, reason, versions, layer, .. } => {
+                assert_eq!(package, "event-stream");
+                assert_eq!(reason, "malicious flatmap-stream dependency");
+                assert_eq!(versions, vec!["3.3.6"]);
+                assert_eq!(layer, "personal");
+            }
+            _ => panic!("expected block command"),
+        }
+    }
+
+    #[test]
+    fn test_log_command() {
+        let cli = Cli::try_parse_from(vec![
+            "sip", "log", "--decision", "cancelled", "--since", "2026-01-01", "-n", "5",
+        ]).unwrap();
+        match cli.command {
+            Commands::Log { decision, since, limit, .. } => {
+                assert_eq!(decision, Some(Decision::Cancelled));
+                assert_eq!(since, Some("2026-01-01".to_string()));
+                assert_eq!(limit, 5);
+            }
+            _ => panic!("expected log command"),
+        }
+    }
+
+    #[test]
+    fn test_registry_diff_command() {
+        let cli = Cli::try_parse_from(vec![
+            "sip", "registry", "diff", "old/registry/data", "registry/data", "--format", "json",
+        ]).unwrap();
+        match cli.command {
+            Commands::Registry { command: RegistryCommands::Diff { old, new, format, .. } } => {
+                assert_eq!(old, "old/registry/data");
+                assert_eq!(new, "registry/data");
+                assert_eq!(format, OutputFormat::Json);
+            }
+            _ => panic!("expected registry diff command"),
+        }
+    }
 }
@@ -0,0 +1,273 @@
+// audit log: every sip decision is appended as one json line to ~/.sip/logs/audit.jsonl,
+// which is rotated to audit.jsonl.1, .2, ... once it reaches the configured size
+
+use crate::sip::config::{Config, LogConfig};
+use crate::sip::package::PackageRecord;
+use crate::sip::paths::log_dir;
+use crate::sip::prompt::ReviewChoice;
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const LOG_FILE: &str = "audit.jsonl";
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Decision {
+    // install went ahead
+    Allowed,
+    // the user declined an untrusted install
+    Cancelled,
+    // install refused because the package is blocked
+    Refused,
+    // verify outcomes
+    Trusted,
+    Untrusted,
+    Blocked,
+    // registry edits
+    Added,
+    Removed,
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Decision::Allowed => "allowed",
+            Decision::Cancelled => "cancelled",
+            Decision::Refused => "refused",
+            Decision::Trusted => "trusted",
+            Decision::Untrusted => "untrusted",
+            Decision::Blocked => "blocked",
+            Decision::Added => "added",
+            Decision::Removed => "removed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEvent {
+    pub timestamp: String,
+    pub user: String,
+    pub cwd: String,
+    pub command: String,
+    pub package: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    // the registry record the decision was based on, as it was at the time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<PackageRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    pub decision: Decision,
+    // the user went ahead despite a warning (prompt answered yes, or --yes)
+    #[serde(default)]
+    pub overridden: bool,
+    // what the user chose when the install was reviewed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<ReviewChoice>,
+    // exit code of the wrapped package manager, when one ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_status: Option<i32>,
+}
+
+impl AuditEvent {
+    pub fn new(command: &str, package: &str, decision: Decision) -> Self {
+        AuditEvent {
+            timestamp: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            user: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "unknown".to_string()),
+            cwd: std::env::current_dir()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            command: command.to_string(),
+            package: package.to_string(),
+            version: None,
+            language: None,
+            record: None,
+            layer: None,
+            decision,
+            overridden: false,
+            review: None,
+            exit_status: None,
+        }
+    }
+}
+
+// append to the user's audit log; a log that cannot be written never fails the command itself
+pub fn record(event: AuditEvent) {
+    let limits = Config::load()
+        .ok()
+        .and_then(|config| config.log)
+        .unwrap_or_default();
+    if let Err(e) = append(&log_dir(), &event, &limits) {
+        println!("⚠ could not write audit log: {}", e);
+    }
+}
+
+fn rotated_path(dir: &Path, n: usize) -> PathBuf {
+    dir.join(format!("{}.{}", LOG_FILE, n))
+}
+
+pub fn append(dir: &Path, event: &AuditEvent, limits: &LogConfig) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)?;
+    let line = serde_json::to_string(event)? + "\n";
+
+    let path = dir.join(LOG_FILE);
+    let size = fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    if size > 0 && size + line.len() as u64 > limits.max_bytes {
+        rotate(dir, limits.keep)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+// shift audit.jsonl.N up by one, dropping whatever falls past `keep`
+fn rotate(dir: &Path, keep: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let path = dir.join(LOG_FILE);
+    if keep == 0 {
+        fs::remove_file(&path)?;
+        return Ok(());
+    }
+
+    let _ = fs::remove_file(rotated_path(dir, keep));
+    for n in (1..keep).rev() {
+        let from = rotated_path(dir, n);
+        if from.exists() {
+            fs::rename(&from, rotated_path(dir, n + 1))?;
+        }
+    }
+    fs::rename(&path, rotated_path(dir, 1))?;
+    Ok(())
+}
+
+// every event still on disk, oldest first; unreadable lines are skipped
+pub fn read_events(dir: &Path) -> Result<Vec<AuditEvent>, Box<dyn std::error::Error>> {
+    let mut rotated: Vec<usize> = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(n) = name.strip_prefix(&format!("{}.", LOG_FILE)).and_then(|n| n.parse().ok()) {
+                rotated.push(n);
+            }
+        }
+    }
+    rotated.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut files: Vec<PathBuf> = rotated.into_iter().map(|n| rotated_path(dir, n)).collect();
+    files.push(dir.join(LOG_FILE));
+
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    for file in files.iter().filter(|file| file.exists()) {
+        for line in fs::read_to_string(file)?.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(event) => events.push(event),
+                Err(_) => skipped += 1,
+            }
+        }
+    }
+    if skipped > 0 {
+        println!("⚠ skipped {} malformed audit log lines", skipped);
+    }
+    Ok(events)
+}
+
+#[derive(Debug, Default)]
+pub struct LogFilter {
+    pub package: Option<String>,
+    pub language: Option<String>,
+    pub command: Option<String>,
+    pub decision: Option<Decision>,
+    // YYYY-MM-DD, inclusive
+    pub since: Option<String>,
+}
+
+impl LogFilter {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(since) = &self.since {
+            NaiveDate::parse_from_str(since, "%Y-%m-%d")
+                .map_err(|_| format!("invalid --since date '{}', expected YYYY-MM-DD", since))?;
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, event: &AuditEvent) -> bool {
+        self.package.as_ref().is_none_or(|p| p.eq_ignore_ascii_case(&event.package))
+            && self.language.as_ref().is_none_or(|l| event.language.as_ref() == Some(l))
+            && self.command.as_ref().is_none_or(|c| c == &event.command)
+            && self.decision.is_none_or(|d| d == event.decision)
+            // rfc 3339 timestamps sort lexically, so the date prefix can be compared as a string
+            && self.since.as_ref().is_none_or(|since| event.timestamp.as_str() >= since.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sip-audit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_append_rotates_by_size() {
+        let dir = temp_log_dir("rotate");
+        let limits = LogConfig { max_bytes: 600, keep: 2 };
+
+        for i in 0..12 {
+            append(&dir, &AuditEvent::new("verify", &format!("pkg{}", i), Decision::Trusted), &limits).unwrap();
+        }
+
+        assert!(dir.join(LOG_FILE).exists());
+        assert!(rotated_path(&dir, 1).exists());
+        assert!(!rotated_path(&dir, 3).exists());
+
+        // the newest event is always last and nothing is reordered by rotation
+        let events = read_events(&dir).unwrap();
+        assert_eq!(events.last().unwrap().package, "pkg11");
+        assert!(events.windows(2).all(|w| w[0].package[3..].parse::<u32>().unwrap() < w[1].package[3..].parse::<u32>().unwrap()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_review_choice_round_trip() {
+        let event = AuditEvent {
+            review: Some(ReviewChoice::InstallAndTrust { score: 6.5 }),
+            ..AuditEvent::new("install", "flask", Decision::Allowed)
+        };
+        let line = serde_json::to_string(&event).unwrap();
+        assert!(line.contains(r#""review":{"choice":"install-and-trust","score":6.5}"#));
+        let parsed: AuditEvent = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.review, Some(ReviewChoice::InstallAndTrust { score: 6.5 }));
+        assert!(!serde_json::to_string(&AuditEvent::new("install", "flask", Decision::Allowed)).unwrap().contains("review"));
+    }
+
+    #[test]
+    fn test_filter_matches_fields() {
+        let mut event = AuditEvent::new("install", "Flask", Decision::Allowed);
+        event.language = Some("python".to_string());
+        event.timestamp = "2026-03-02T10:00:00Z".to_string();
+
+        let filter = LogFilter {
+            package: Some("flask".to_string()),
+            language: Some("python".to_string()),
+            since: Some("2026-03-01".to_string()),
+            ..LogFilter::default()
+        };
+        assert!(filter.matches(&event));
+        assert!(!LogFilter { decision: Some(Decision::Cancelled), ..LogFilter::default() }.matches(&event));
+        assert!(!LogFilter { since: Some("2026-03-03".to_string()), ..LogFilter::default() }.matches(&event));
+        assert!(LogFilter { since: Some("03/03/2026".to_string()), ..LogFilter::default() }.validate().is_err());
+    }
+}
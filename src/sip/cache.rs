@@ -0,0 +1,208 @@
+// compiled registry cache: a memory-mapped binary file holding already validated records,
+// tagged with the sha256 of the json it was built from so stale caches are detected cheaply
+//
+// layout (little endian):
+//   magic "SIPC" | format u32 | source sha256 [u8; 32] | entry count u32
+//   entry table: count * (name offset u32, name len u32, data offset u32, data len u32)
+//   data: normalized names and messagepack-encoded version lists, offsets relative to data start
+//
+// entries are sorted by normalized name so a reader can binary search the mapped table
+
+use crate::cli::Language;
+use crate::sip::normalize::normalize_name;
+use crate::sip::package::PackageRecord;
+use memmap2::Mmap;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"SIPC";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 4 + 4 + 32 + 4;
+const ENTRY_LEN: usize = 16;
+
+pub fn content_hash(json: &str) -> [u8; 32] {
+    Sha256::digest(json.as_bytes()).into()
+}
+
+// one cache file per language and registry source (embedded data or local files)
+pub fn cache_path(dir: &Path, lang: &Language, origin: &str) -> PathBuf {
+    dir.join(format!("{}-{}.sipc", lang, origin))
+}
+
+pub fn encode(records: &[PackageRecord], lang: &Language, source_hash: &[u8; 32]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut grouped: BTreeMap<String, Vec<&PackageRecord>> = BTreeMap::new();
+    for record in records {
+        grouped.entry(normalize_name(&record.name, lang)).or_default().push(record);
+    }
+
+    let mut table = Vec::with_capacity(grouped.len() * ENTRY_LEN);
+    let mut data = Vec::new();
+    for (name, versions) in &grouped {
+        let name_offset = data.len() as u32;
+        data.extend_from_slice(name.as_bytes());
+        let data_offset = data.len() as u32;
+        // named encoding keeps old caches readable when optional fields are added
+        data.extend_from_slice(&rmp_serde::to_vec_named(versions)?);
+
+        table.extend_from_slice(&name_offset.to_le_bytes());
+        table.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        table.extend_from_slice(&data_offset.to_le_bytes());
+        table.extend_from_slice(&(data.len() as u32 - data_offset).to_le_bytes());
+    }
+
+    let mut out = Vec::with_capacity(HEADER_LEN + table.len() + data.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.extend_from_slice(source_hash);
+    out.extend_from_slice(&(grouped.len() as u32).to_le_bytes());
+    out.extend_from_slice(&table);
+    out.extend_from_slice(&data);
+    Ok(out)
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+// a cache whose header, hash and entry table checked out; version lists are only
+// decoded when their entry is asked for
+#[derive(Debug)]
+pub struct CacheFile<B: AsRef<[u8]> = Mmap> {
+    bytes: B,
+    count: usize,
+}
+
+impl<B: AsRef<[u8]>> CacheFile<B> {
+    // None when the cache is missing, corrupt or built from other json
+    pub fn parse(bytes: B, source_hash: &[u8; 32]) -> Option<Self> {
+        let data = bytes.as_ref();
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC || read_u32(data, 4)? != FORMAT_VERSION {
+            return None;
+        }
+        if &data[8..40] != source_hash {
+            return None;
+        }
+
+        let count = read_u32(data, 40)? as usize;
+        let cache = CacheFile { bytes, count };
+        // every entry must point inside the file, so lookups never hit a truncated cache
+        let data_len = cache.data()?.len();
+        for i in 0..count {
+            let (name_offset, name_len, offset, len) = cache.entry(i)?;
+            if name_offset.checked_add(name_len)? > data_len || offset.checked_add(len)? > data_len {
+                return None;
+            }
+            std::str::from_utf8(&cache.data()?[name_offset..name_offset + name_len]).ok()?;
+        }
+        Some(cache)
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.count
+    }
+
+    fn data(&self) -> Option<&[u8]> {
+        self.bytes.as_ref().get(HEADER_LEN + self.count.checked_mul(ENTRY_LEN)?..)
+    }
+
+    fn entry(&self, i: usize) -> Option<(usize, usize, usize, usize)> {
+        let bytes = self.bytes.as_ref();
+        let at = HEADER_LEN + i * ENTRY_LEN;
+        Some((
+            read_u32(bytes, at)? as usize,
+            read_u32(bytes, at + 4)? as usize,
+            read_u32(bytes, at + 8)? as usize,
+            read_u32(bytes, at + 12)? as usize,
+        ))
+    }
+
+    // normalized name of entry i
+    pub fn name(&self, i: usize) -> Option<&str> {
+        let (offset, len, _, _) = self.entry(i)?;
+        std::str::from_utf8(self.data()?.get(offset..offset + len)?).ok()
+    }
+
+    // binary search of the sorted entry table
+    pub fn position(&self, normalized: &str) -> Option<usize> {
+        let (mut low, mut high) = (0, self.count);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.name(mid)?.cmp(normalized) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    pub fn versions(&self, i: usize) -> Option<Vec<PackageRecord>> {
+        let (_, _, offset, len) = self.entry(i)?;
+        rmp_serde::from_slice(self.data()?.get(offset..offset + len)?).ok()
+    }
+}
+
+pub fn load(path: &Path, source_hash: &[u8; 32]) -> Option<CacheFile> {
+    let file = fs::File::open(path).ok()?;
+    // SAFETY: cache files are only ever replaced by rename, never modified in place,
+    // so the mapping cannot change underneath us
+    let mmap = unsafe { Mmap::map(&file) }.ok()?;
+    CacheFile::parse(mmap, source_hash)
+}
+
+// write via a temp file and rename so concurrent readers never map a half-written cache
+pub fn store(path: &Path, records: &[PackageRecord], lang: &Language, source_hash: &[u8; 32]) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("sipc.{}", std::process::id()));
+    fs::write(&tmp, encode(records, lang, source_hash)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sip::testing::record;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let records = vec![record("requests", "2.31.0"), record("flask", "2.3.2"), record("requests", "2.32.4")];
+        let hash = content_hash("[...]");
+        let bytes = encode(&records, &Language::Python, &hash).unwrap();
+
+        let cache = CacheFile::parse(bytes.as_slice(), &hash).unwrap();
+        let decoded: Vec<PackageRecord> = (0..cache.package_count()).flat_map(|i| cache.versions(i).unwrap()).collect();
+        let names: Vec<(&str, &str)> = decoded.iter().map(|r| (r.name.as_str(), r.version.as_str())).collect();
+        assert_eq!(names, vec![("flask", "2.3.2"), ("requests", "2.31.0"), ("requests", "2.32.4")]);
+        assert_eq!(decoded[0].endorsed_by, vec!["core-team"]);
+    }
+
+    #[test]
+    fn test_stale_or_corrupt_cache_is_rejected() {
+        let hash = content_hash("[]");
+        let bytes = encode(&[record("flask", "2.3.2")], &Language::Python, &hash).unwrap();
+
+        assert!(CacheFile::parse(bytes.as_slice(), &content_hash("[ ]")).is_none());
+        assert!(CacheFile::parse(&bytes[..bytes.len() - 3], &hash).is_none());
+        assert!(CacheFile::parse(b"not a cache".as_slice(), &hash).is_none());
+    }
+
+    #[test]
+    fn test_lookup_decodes_one_entry() {
+        let records = vec![record("requests", "2.31.0"), record("flask", "2.3.2"), record("numpy", "1.24.3"), record("requests", "2.32.4")];
+        let hash = content_hash("[...]");
+        let bytes = encode(&records, &Language::Python, &hash).unwrap();
+
+        let cache = CacheFile::parse(bytes.as_slice(), &hash).unwrap();
+        assert_eq!(cache.package_count(), 3);
+        let requests = cache.versions(cache.position("requests").unwrap()).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(cache.name(cache.position("flask").unwrap()), Some("flask"));
+        assert_eq!(cache.position("django"), None);
+        assert_eq!(cache.position("zope"), None);
+    }
+}
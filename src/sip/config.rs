@@ -0,0 +1,392 @@
+// sip configuration: the user's ~/.sip/config.toml, extended by a project's sip.toml
+
+use crate::sip::detect::find_project_root;
+use crate::sip::paths::sip_home;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Config {
+    #[serde(default)]
+    pub registry: RegistryConfig,
+    #[serde(default)]
+    pub log: Option<LogConfig>,
+    #[serde(default)]
+    pub review: Option<ReviewConfig>,
+    #[serde(default)]
+    pub lint: Option<LintConfig>,
+    #[serde(default)]
+    pub provenance: Option<ProvenanceConfig>,
+    #[serde(default)]
+    pub policy: Option<PolicyConfig>,
+    #[serde(default)]
+    pub licenses: Option<LicenseConfig>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct RegistryConfig {
+    // extra registry sources layered over the community registry
+    #[serde(default)]
+    pub layers: Vec<LayerConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LayerConfig {
+    pub name: String,
+    // directory laid out like registry/data (<lang>/trusted-*.json)
+    pub path: String,
+    // higher priority wins when several layers record the same package
+    pub priority: Option<i32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LogConfig {
+    // the audit log is rotated once it would grow past this size
+    #[serde(default = "LogConfig::default_max_bytes")]
+    pub max_bytes: u64,
+    // number of rotated files kept next to the live log
+    #[serde(default = "LogConfig::default_keep")]
+    pub keep: usize,
+}
+
+impl LogConfig {
+    fn default_max_bytes() -> u64 {
+        10 * 1024 * 1024
+    }
+
+    fn default_keep() -> usize {
+        5
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            max_bytes: Self::default_max_bytes(),
+            keep: Self::default_keep(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StaleAction {
+    #[default]
+    Warn,
+    Block,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReviewConfig {
+    // a trust entry older than this is stale unless a rule says otherwise
+    #[serde(default = "ReviewConfig::default_max_age_days")]
+    pub max_age_days: i64,
+    // what verify and install do with a stale entry
+    #[serde(default)]
+    pub on_stale: StaleAction,
+    #[serde(default)]
+    pub rules: Vec<ReviewRule>,
+}
+
+// a review age for one language and/or score band
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReviewRule {
+    pub language: Option<String>,
+    #[serde(default)]
+    pub min_score: f64,
+    pub max_age_days: i64,
+}
+
+impl ReviewConfig {
+    fn default_max_age_days() -> i64 {
+        365
+    }
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        ReviewConfig {
+            max_age_days: Self::default_max_age_days(),
+            on_stale: StaleAction::default(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+// policy bands `sip registry lint` holds registry entries to
+#[derive(Deserialize, Debug, Clone)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub min_score: f64,
+    #[serde(default = "LintConfig::default_max_score")]
+    pub max_score: f64,
+}
+
+impl LintConfig {
+    fn default_max_score() -> f64 {
+        10.0
+    }
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            min_score: 0.0,
+            max_score: Self::default_max_score(),
+        }
+    }
+}
+
+// where the pinned sigstore trusted root used by provenance checks lives
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ProvenanceConfig {
+    pub trusted_root: Option<String>,
+}
+
+impl ProvenanceConfig {
+    pub fn trusted_root_path(&self) -> PathBuf {
+        self.trusted_root
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| sip_home().join("sigstore").join("trusted_root.json"))
+    }
+}
+
+// the organization's policy file, and which environment this project counts as; only the user
+// config points at the file, a project's sip.toml sets the environment
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PolicyConfig {
+    pub file: Option<String>,
+    pub environment: Option<String>,
+}
+
+// spdx license globs vet and install hold dependencies to; an empty allow list allows
+// everything that is not denied
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct LicenseConfig {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let mut config = Config::default();
+
+        let user_config = sip_home().join("config.toml");
+        if user_config.exists() {
+            config.merge(Self::load_file(&user_config)?, user_config.parent());
+        }
+
+        let cwd = std::env::current_dir()?;
+        if let Some(root) = find_project_root(&cwd) {
+            let project_config = root.join("sip.toml");
+            if project_config.exists() {
+                config.merge_project(Self::load_file(&project_config)?, &root);
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn load_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| format!("invalid config {}: {}", path.display(), e).into())
+    }
+
+    // a project's sip.toml comes with the checkout, so it may not swap out what the checks trust
+    fn merge_project(&mut self, mut other: Config, root: &Path) {
+        if let Some(provenance) = other.provenance.take()
+            && provenance.trusted_root.is_some()
+        {
+            eprintln!("⚠ ignoring [provenance] trusted_root in {}: set it in ~/.sip/config.toml", root.join("sip.toml").display());
+        }
+        if let Some(policy) = &mut other.policy
+            && policy.file.take().is_some()
+        {
+            eprintln!("⚠ ignoring [policy] file in {}: set it in ~/.sip/config.toml", root.join("sip.toml").display());
+        }
+        // a project can deny more licenses, but not allow any the user config does not
+        if let Some(licenses) = other.licenses.take() {
+            if !licenses.allow.is_empty() {
+                eprintln!("⚠ ignoring [licenses] allow in {}: set it in ~/.sip/config.toml", root.join("sip.toml").display());
+            }
+            let current = self.licenses.get_or_insert_with(LicenseConfig::default);
+            for glob in licenses.deny {
+                if !current.deny.contains(&glob) {
+                    current.deny.push(glob);
+                }
+            }
+        }
+        self.merge(other, Some(root));
+    }
+
+    // relative layer and trusted root paths are resolved against the directory of the file that declared them
+    fn merge(&mut self, other: Config, base: Option<&Path>) {
+        if other.log.is_some() {
+            self.log = other.log;
+        }
+        if other.review.is_some() {
+            self.review = other.review;
+        }
+        if other.lint.is_some() {
+            self.lint = other.lint;
+        }
+        if other.licenses.is_some() {
+            self.licenses = other.licenses;
+        }
+        if let Some(mut provenance) = other.provenance {
+            if let Some(base) = base
+                && let Some(root) = &provenance.trusted_root
+                && Path::new(root).is_relative()
+            {
+                provenance.trusted_root = Some(base.join(root).to_string_lossy().to_string());
+            }
+            self.provenance = Some(provenance);
+        }
+        if let Some(policy) = other.policy {
+            let current = self.policy.get_or_insert_with(PolicyConfig::default);
+            if let Some(file) = policy.file {
+                current.file = Some(match base {
+                    Some(base) if Path::new(&file).is_relative() => base.join(&file).to_string_lossy().to_string(),
+                    _ => file,
+                });
+            }
+            if policy.environment.is_some() {
+                current.environment = policy.environment;
+            }
+        }
+        for mut layer in other.registry.layers {
+            if let Some(base) = base
+                && Path::new(&layer.path).is_relative()
+            {
+                layer.path = base.join(&layer.path).to_string_lossy().to_string();
+            }
+            self.registry.layers.retain(|l| l.name != layer.name);
+            self.registry.layers.push(layer);
+        }
+    }
+}
+
+// the project overlay lives next to the project's manifests
+pub fn project_registry_dir() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    find_project_root(&cwd).map(|root| root.join(".sip").join("registry"))
+}
+
+pub fn personal_registry_dir() -> PathBuf {
+    sip_home().join("registry")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_layers_and_resolve_relative_paths() {
+        let config: Config = toml::from_str(r#"
+            [[registry.layers]]
+            name = "acme"
+            path = "registries/acme"
+            priority = 150
+        "#).unwrap();
+
+        let mut merged = Config::default();
+        merged.merge(config, Some(Path::new("/etc/sip")));
+        assert_eq!(merged.registry.layers.len(), 1);
+        assert_eq!(merged.registry.layers[0].path, "/etc/sip/registries/acme");
+        assert_eq!(merged.registry.layers[0].priority, Some(150));
+    }
+
+    #[test]
+    fn test_policy_sections_merge_field_by_field() {
+        let user: Config = toml::from_str(r#"
+            [policy]
+            file = "acme-policy.toml"
+        "#).unwrap();
+        let project: Config = toml::from_str(r#"
+            [policy]
+            environment = "production"
+        "#).unwrap();
+
+        let mut merged = Config::default();
+        merged.merge(user, Some(Path::new("/home/dev/.sip")));
+        merged.merge_project(project, Path::new("/src/app"));
+        let policy = merged.policy.unwrap();
+        assert_eq!(policy.file.as_deref(), Some("/home/dev/.sip/acme-policy.toml"));
+        assert_eq!(policy.environment.as_deref(), Some("production"));
+    }
+
+    #[test]
+    fn test_project_config_cannot_change_policy_file() {
+        let user: Config = toml::from_str(r#"
+            [policy]
+            file = "/etc/sip/sip-policy.toml"
+        "#).unwrap();
+        let project: Config = toml::from_str(r#"
+            [policy]
+            file = "allow-everything.toml"
+            environment = "development"
+        "#).unwrap();
+
+        let mut merged = Config::default();
+        merged.merge(user, Some(Path::new("/home/dev/.sip")));
+        merged.merge_project(project, Path::new("/src/app"));
+        let policy = merged.policy.unwrap();
+        assert_eq!(policy.file.as_deref(), Some("/etc/sip/sip-policy.toml"));
+        assert_eq!(policy.environment.as_deref(), Some("development"));
+    }
+
+    #[test]
+    fn test_project_config_can_only_add_denied_licenses() {
+        let user: Config = toml::from_str(r#"
+            [licenses]
+            allow = ["MIT", "Apache-2.0"]
+            deny = ["GPL-*"]
+        "#).unwrap();
+        let project: Config = toml::from_str(r#"
+            [licenses]
+            allow = ["*"]
+            deny = ["GPL-*", "AGPL-*"]
+        "#).unwrap();
+
+        let mut merged = Config::default();
+        merged.merge(user, Some(Path::new("/home/dev/.sip")));
+        merged.merge_project(project, Path::new("/src/app"));
+        let licenses = merged.licenses.unwrap();
+        assert_eq!(licenses.allow, vec!["MIT", "Apache-2.0"]);
+        assert_eq!(licenses.deny, vec!["GPL-*", "AGPL-*"]);
+    }
+
+    #[test]
+    fn test_provenance_trusted_root_is_resolved() {
+        let config: Config = toml::from_str(r#"
+            [provenance]
+            trusted_root = "sigstore/trusted_root.json"
+        "#).unwrap();
+
+        let mut merged = Config::default();
+        merged.merge(config, Some(Path::new("/etc/sip")));
+        let provenance = merged.provenance.unwrap();
+        assert_eq!(provenance.trusted_root_path(), PathBuf::from("/etc/sip/sigstore/trusted_root.json"));
+    }
+
+    #[test]
+    fn test_project_config_cannot_set_trusted_root() {
+        let user: Config = toml::from_str(r#"
+            [provenance]
+            trusted_root = "/etc/sip/trusted_root.json"
+        "#).unwrap();
+        let project: Config = toml::from_str(r#"
+            [provenance]
+            trusted_root = "fake_root.json"
+        "#).unwrap();
+
+        let mut merged = Config::default();
+        merged.merge(user, Some(Path::new("/home/dev/.sip")));
+        merged.merge_project(project, Path::new("/src/app"));
+        assert_eq!(merged.provenance.unwrap().trusted_root.as_deref(), Some("/etc/sip/trusted_root.json"));
+    }
+}
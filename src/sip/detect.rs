@@ -0,0 +1,263 @@
+// project language detection: walks up to the project root, collects every ecosystem
+// present there and only picks one when the evidence is unambiguous
+
+use crate::cli::Language;
+use crate::sip::registry::Registry;
+use dialoguer::Select;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+// files that mark a directory as belonging to an ecosystem
+const MARKERS: &[(&str, Language)] = &[
+    ("Cargo.toml", Language::Rust),
+    ("Cargo.lock", Language::Rust),
+    ("pyproject.toml", Language::Python),
+    ("requirements.txt", Language::Python),
+    ("setup.py", Language::Python),
+    ("setup.cfg", Language::Python),
+    ("Pipfile", Language::Python),
+    ("poetry.lock", Language::Python),
+    ("go.mod", Language::Go),
+    ("go.sum", Language::Go),
+    ("package.json", Language::Node),
+    ("package-lock.json", Language::Node),
+    ("pnpm-lock.yaml", Language::Node),
+    ("yarn.lock", Language::Node),
+    ("pom.xml", Language::Java),
+    ("build.gradle", Language::Java),
+    ("build.gradle.kts", Language::Java),
+    ("Gemfile", Language::Ruby),
+    ("Gemfile.lock", Language::Ruby),
+    ("composer.json", Language::Php),
+    ("composer.lock", Language::Php),
+];
+
+const SOURCE_EXTENSIONS: &[(&str, Language)] = &[
+    ("rs", Language::Rust),
+    ("py", Language::Python),
+    ("go", Language::Go),
+    ("js", Language::Node),
+    ("ts", Language::Node),
+    ("java", Language::Java),
+    ("kt", Language::Java),
+    ("rb", Language::Ruby),
+    ("php", Language::Php),
+];
+
+// one ecosystem found in the project, with the file that gave it away
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub language: Language,
+    pub marker: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Detection {
+    pub root: Option<PathBuf>,
+    pub candidates: Vec<Candidate>,
+}
+
+// nearest ancestor (including `start`) holding an ecosystem marker, without crossing a repository root
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    for dir in start.ancestors() {
+        if MARKERS.iter().any(|(file, _)| dir.join(file).exists()) {
+            return Some(dir.to_path_buf());
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+pub fn detect_project(start: &Path) -> Detection {
+    let Some(root) = find_project_root(start) else {
+        // no manifests anywhere, fall back to looking at source files
+        return Detection {
+            root: None,
+            candidates: detect_from_sources(start),
+        };
+    };
+
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for (file, language) in MARKERS {
+        if root.join(file).exists() && !candidates.iter().any(|c| &c.language == language) {
+            candidates.push(Candidate {
+                language: language.clone(),
+                marker: file.to_string(),
+            });
+        }
+    }
+
+    Detection {
+        root: Some(root),
+        candidates,
+    }
+}
+
+fn detect_from_sources(dir: &Path) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+
+    for source_dir in [dir.join("src"), dir.to_path_buf()] {
+        let Ok(entries) = fs::read_dir(&source_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+                continue;
+            };
+            if let Some((_, language)) = SOURCE_EXTENSIONS.iter().find(|(e, _)| *e == ext)
+                && !candidates.iter().any(|c| &c.language == language)
+            {
+                candidates.push(Candidate {
+                    language: language.clone(),
+                    marker: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                });
+            }
+        }
+        if !candidates.is_empty() {
+            break;
+        }
+    }
+
+    candidates
+}
+
+// some package specs can only belong to one ecosystem
+pub fn infer_from_package(spec: &str) -> Option<Language> {
+    // @scope/name is npm-only syntax
+    if spec.starts_with('@') && spec.contains('/') {
+        return Some(Language::Node);
+    }
+
+    // groupId:artifactId[:version] with a dotted group id
+    let coords: Vec<&str> = spec.split(':').collect();
+    if (2..=4).contains(&coords.len()) && coords[0].contains('.') && coords.iter().all(|c| !c.is_empty()) {
+        return Some(Language::Java);
+    }
+
+    // go module paths start with a hostname (github.com/..., golang.org/x/...); a plain
+    // vendor/name is not enough for composer, other ecosystems use slashes too, so php is
+    // only ever picked up from composer.json or composer.lock
+    let segments: Vec<&str> = spec.split('/').collect();
+    if segments.len() >= 2 && segments.iter().all(|s| !s.is_empty()) && segments[0].contains('.') {
+        return Some(Language::Go);
+    }
+
+    None
+}
+
+fn describe(candidates: &[Candidate]) -> String {
+    candidates
+        .iter()
+        .map(|c| format!("{} ({})", c.language, c.marker))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// decide which ecosystem a command targets: explicit flag, then package syntax, then project
+// files; a registry hit narrows ambiguous projects, and what is left is asked or reported
+pub fn resolve_language(
+    explicit: Option<Language>,
+    package: Option<&str>,
+    registry: Option<&Registry>,
+    interactive: bool,
+) -> Result<Language, Box<dyn std::error::Error>> {
+    if let Some(language) = explicit {
+        return Ok(language);
+    }
+
+    if let Some(language) = package.and_then(infer_from_package) {
+        return Ok(language);
+    }
+
+    let cwd = std::env::current_dir()?;
+    let detection = detect_project(&cwd);
+    let mut candidates = detection.candidates;
+
+    if candidates.len() > 1
+        && let (Some(package), Some(registry)) = (package, registry)
+    {
+        let known: Vec<Candidate> = candidates
+            .iter()
+            .filter(|c| matches!(registry.lookup_package(package, &c.language), Ok(Some(_))))
+            .cloned()
+            .collect();
+        if known.len() == 1 {
+            candidates = known;
+        }
+    }
+
+    match candidates.len() {
+        0 => Err("could not detect language - please specify with --lang flag".into()),
+        1 => Ok(candidates.remove(0).language),
+        _ if interactive && std::io::stdin().is_terminal() => {
+            let items: Vec<String> = candidates
+                .iter()
+                .map(|c| format!("{} ({})", c.language, c.marker))
+                .collect();
+            let choice = Select::new()
+                .with_prompt("multiple ecosystems found in this project, which one?")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            Ok(candidates.remove(choice).language)
+        }
+        _ => Err(format!(
+            "ambiguous project - found {}; please specify with --lang flag",
+            describe(&candidates)
+        ).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sip-detect-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_infer_from_package() {
+        assert_eq!(infer_from_package("github.com/gin-gonic/gin"), Some(Language::Go));
+        assert_eq!(infer_from_package("@types/node"), Some(Language::Node));
+        assert_eq!(infer_from_package("com.google.guava:guava:32.1.2-jre"), Some(Language::Java));
+        assert_eq!(infer_from_package("monolog/monolog"), None);
+        assert_eq!(infer_from_package("requests"), None);
+    }
+
+    #[test]
+    fn test_detect_reports_every_ecosystem_at_root() {
+        let root = temp_project("monorepo");
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        fs::write(root.join("pyproject.toml"), "").unwrap();
+        fs::create_dir_all(root.join("tools/scripts")).unwrap();
+
+        let detection = detect_project(&root.join("tools/scripts"));
+        let languages: Vec<Language> = detection.candidates.into_iter().map(|c| c.language).collect();
+        assert_eq!(detection.root, Some(root.clone()));
+        assert_eq!(languages, vec![Language::Rust, Language::Python]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_detect_prefers_nearest_project() {
+        let root = temp_project("nested");
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::write(root.join("web/package.json"), "{}").unwrap();
+
+        let detection = detect_project(&root.join("web"));
+        assert_eq!(detection.candidates.len(), 1);
+        assert_eq!(detection.candidates[0].language, Language::Node);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,14 +1,30 @@
+use crate::sip::maven::{self, MavenCoordinate};
+use crate::cli::Language;
+use crate::sip::license;
+use crate::sip::normalize::normalize_name;
 use crate::sip::package::PackageRecord;
+use crate::sip::source_check::python_repository;
+use crate::sip::upstream::{self, get_json};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use chrono::Utc;
+use flate2::read::GzDecoder;
 use reqwest::Error as ReqwestError;
 use serde::Deserialize;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
 
 #[derive(Deserialize)]
 struct PyPiInfo {
     version: String,
     home_page: Option<String>,
     project_urls: Option<std::collections::HashMap<String, String>>,
+    // pep 639
+    license_expression: Option<String>,
+    license: Option<String>,
+    #[serde(default)]
+    classifiers: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -16,6 +32,10 @@ struct PyPiReleaseFile {
     packagetype: String,
     url: String,
     digests: HashMap<String, String>,
+    // pep 592
+    #[serde(default)]
+    yanked: bool,
+    yanked_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -41,10 +61,12 @@ pub async fn fetch_from_pypi(name: &str) -> Result<PackageRecord, ReqwestError>
         .unwrap_or(&files[0]);
 
     let sha = file.digests.get("sha256").expect("sha256 missing");
+    let license = license::pypi_license(resp.info.license_expression.as_deref(), &resp.info.classifiers, resp.info.license.as_deref());
 
-    let source = resp
-        .info
-        .home_page
+    // a repository among the project urls beats a homepage, which is often the docs site
+    let repository = resp.info.project_urls.as_ref().and_then(python_repository);
+    let source = repository
+        .or(resp.info.home_page)
         .or_else(|| {
             resp.info
                 .project_urls
@@ -60,13 +82,28 @@ pub async fn fetch_from_pypi(name: &str) -> Result<PackageRecord, ReqwestError>
         endorsed_by: Vec::new(),
         last_reviewed: Utc::now().date_naive().to_string(),
         source,
+        yanked: file.yanked,
+        yank_reason: file.yanked_reason.clone().filter(|reason| !reason.is_empty()),
+        maintainers: current_maintainers(name, &Language::Python).await,
+        license,
+        ..Default::default()
     })
 }
 
+// owners are captured alongside the release; a failed lookup leaves them empty rather than
+// failing the whole fetch
+async fn current_maintainers(name: &str, lang: &Language) -> Vec<String> {
+    match upstream::client() {
+        Ok(client) => upstream::fetch_maintainers(&client, name, lang).await.ok().flatten().unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
 #[derive(Deserialize)]
 struct CrateData {
     #[serde(rename = "max_version")]
     version: String,
+    repository: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -78,6 +115,10 @@ struct CratesResponse {
 #[derive(Deserialize)]
 struct VersionData {
     checksum: String,
+    license: Option<String>,
+    #[serde(default)]
+    yanked: bool,
+    yank_message: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -96,7 +137,11 @@ pub async fn fetch_from_crates(name: &str) -> Result<PackageRecord, ReqwestError
     let vr: VersionResponse = reqwest::get(&ver_url).await?.json().await?;
     let sha = vr.version.checksum;
 
-    let source = format!("https://crates.io/crates/{}/{}", name, version);
+    let source = cr
+        .krate
+        .repository
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| format!("https://crates.io/crates/{}/{}", name, version));
 
     Ok(PackageRecord {
         name: name.to_string(),
@@ -106,5 +151,579 @@ pub async fn fetch_from_crates(name: &str) -> Result<PackageRecord, ReqwestError
         endorsed_by: Vec::new(),
         last_reviewed: Utc::now().date_naive().to_string(),
         source,
+        yanked: vr.version.yanked,
+        yank_reason: vr.version.yank_message,
+        maintainers: current_maintainers(name, &Language::Rust).await,
+        license: vr.version.license.as_deref().and_then(license::spdx_expression),
+        ..Default::default()
+    })
+}
+
+#[derive(Deserialize)]
+struct NpmDist {
+    integrity: Option<String>,
+    shasum: String,
+    tarball: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NpmRepository {
+    Url(String),
+    Object { url: String },
+}
+
+#[derive(Deserialize)]
+struct NpmVersionResponse {
+    version: String,
+    dist: NpmDist,
+    repository: Option<NpmRepository>,
+    homepage: Option<String>,
+    deprecated: Option<String>,
+    #[serde(default)]
+    maintainers: Vec<NpmMaintainer>,
+    license: Option<serde_json::Value>,
+    licenses: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct NpmMaintainer {
+    name: String,
+}
+
+// scoped packages (@scope/name) need the slash escaped in registry urls
+fn npm_registry_path(name: &str) -> String {
+    name.replace('/', "%2f")
+}
+
+// npm repository urls come in many shapes: git+https://...git, git://..., github:owner/repo
+pub fn normalize_repository_url(url: &str) -> String {
+    let url = url.trim_start_matches("git+");
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    if let Some(rest) = url.strip_prefix("git://") {
+        format!("https://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("ssh://git@") {
+        format!("https://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("github:") {
+        format!("https://github.com/{}", rest)
+    } else {
+        url.to_string()
+    }
+}
+
+pub async fn fetch_from_npm(name: &str) -> Result<PackageRecord, ReqwestError> {
+    let url = format!("https://registry.npmjs.org/{}/latest", npm_registry_path(name));
+    let resp: NpmVersionResponse = reqwest::get(&url).await?.json().await?;
+    let license = license::npm_license(resp.license.as_ref(), resp.licenses.as_ref());
+
+    // prefer the sri integrity string, older packages only publish a sha1 shasum
+    let hash = resp
+        .dist
+        .integrity
+        .unwrap_or_else(|| format!("sha1:{}", resp.dist.shasum));
+
+    let source = resp
+        .repository
+        .map(|repo| match repo {
+            NpmRepository::Url(url) | NpmRepository::Object { url } => normalize_repository_url(&url),
+        })
+        .or(resp.homepage)
+        .unwrap_or_else(|| format!("https://www.npmjs.com/package/{}", name));
+
+    Ok(PackageRecord {
+        name: name.to_string(),
+        version: resp.version,
+        hash,
+        trust_score: 0.0,
+        endorsed_by: Vec::new(),
+        last_reviewed: Utc::now().date_naive().to_string(),
+        source,
+        // npm has no yank, deprecation is how authors pull a release
+        yanked: resp.deprecated.is_some(),
+        yank_reason: resp.deprecated,
+        maintainers: {
+            let mut maintainers: Vec<String> = resp.maintainers.into_iter().map(|m| m.name).collect();
+            maintainers.sort();
+            maintainers
+        },
+        license,
+        ..Default::default()
+    })
+}
+
+// checksum files hold either the bare digest or `<digest>  <filename>`
+fn parse_checksum_file(content: &str) -> Option<String> {
+    content.split_whitespace().next().map(|d| d.to_lowercase())
+}
+
+pub async fn fetch_from_maven(spec: &str, repository: &str) -> Result<PackageRecord, Box<dyn std::error::Error>> {
+    let coord = MavenCoordinate::parse(spec)?;
+    let artifact_path = coord.artifact_path();
+
+    // 1. resolve the version from maven-metadata.xml unless pinned
+    let version = match &coord.version {
+        Some(v) => v.clone(),
+        None => {
+            let metadata = maven::read_repository_file(repository, &format!("{}/maven-metadata.xml", artifact_path)).await?;
+            maven::xml_tag(&metadata, "release")
+                .or_else(|| maven::xml_tag(&metadata, "latest"))
+                .ok_or_else(|| format!("no release version in maven-metadata.xml for {}", coord.name()))?
+        }
+    };
+
+    // 2. checksum of the main artifact, pom-only artifacts (boms, parents) fall back to the pom
+    let base = format!("{}/{}/{}-{}", artifact_path, version, coord.artifact_id, version);
+    let mut hash = None;
+    for (ext, algo) in [("jar.sha256", "sha256"), ("jar.sha1", "sha1"), ("pom.sha256", "sha256"), ("pom.sha1", "sha1")] {
+        if let Ok(content) = maven::read_repository_file(repository, &format!("{}.{}", base, ext)).await
+            && let Some(digest) = parse_checksum_file(&content)
+        {
+            hash = Some(format!("{}:{}", algo, digest));
+            break;
+        }
+    }
+    let hash = hash.ok_or_else(|| format!("no checksum published for {}:{}", coord.name(), version))?;
+
+    // 3. source url from the pom's <scm> section when present
+    let pom = maven::read_repository_file(repository, &format!("{}.pom", base)).await.ok();
+    let source = pom
+        .as_deref()
+        .and_then(|pom| maven::xml_tag(pom, "scm").and_then(|scm| maven::xml_tag(&scm, "url")).or_else(|| maven::xml_tag(pom, "url")))
+        .filter(|url| url.starts_with("http"))
+        .unwrap_or_else(|| format!("https://central.sonatype.com/artifact/{}/{}/{}", coord.group_id, coord.artifact_id, version));
+
+    Ok(PackageRecord {
+        name: coord.name(),
+        version,
+        hash,
+        trust_score: 0.0,
+        endorsed_by: Vec::new(),
+        last_reviewed: Utc::now().date_naive().to_string(),
+        source,
+        ..Default::default()
+    })
+}
+
+#[derive(Deserialize)]
+struct RubyGemsResponse {
+    version: String,
+    sha: String,
+    source_code_uri: Option<String>,
+    homepage_uri: Option<String>,
+}
+
+pub async fn fetch_from_rubygems(name: &str) -> Result<PackageRecord, ReqwestError> {
+    let url = format!("https://rubygems.org/api/v1/gems/{}.json", name);
+    let resp: RubyGemsResponse = reqwest::get(&url).await?.json().await?;
+
+    let source = resp
+        .source_code_uri
+        .or(resp.homepage_uri)
+        .filter(|uri| !uri.is_empty())
+        .unwrap_or_else(|| format!("https://rubygems.org/gems/{}", name));
+
+    Ok(PackageRecord {
+        name: name.to_string(),
+        version: resp.version,
+        // sha is the sha256 of the .gem file
+        hash: format!("sha256:{}", resp.sha),
+        trust_score: 0.0,
+        endorsed_by: Vec::new(),
+        last_reviewed: Utc::now().date_naive().to_string(),
+        source,
+        ..Default::default()
+    })
+}
+
+#[derive(Deserialize)]
+struct PackagistDist {
+    shasum: Option<String>,
+    reference: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PackagistSource {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct PackagistVersion {
+    version: String,
+    dist: Option<PackagistDist>,
+    source: Option<PackagistSource>,
+}
+
+#[derive(Deserialize)]
+struct PackagistResponse {
+    packages: HashMap<String, Vec<PackagistVersion>>,
+}
+
+// packagist tags are usually `v1.2.3`, the registry schema wants bare semver
+fn composer_version(version: &str) -> String {
+    version.trim_start_matches('v').to_string()
+}
+
+// github-hosted dists have an empty shasum, the pinned commit is the next best content identifier
+fn composer_hash(dist: Option<&PackagistDist>) -> String {
+    match dist {
+        Some(PackagistDist { shasum: Some(sha), .. }) if !sha.is_empty() => format!("sha1:{}", sha),
+        Some(PackagistDist { reference: Some(reference), .. }) if !reference.is_empty() => format!("git:{}", reference),
+        _ => "unknown".to_string(),
+    }
+}
+
+pub async fn fetch_from_packagist(name: &str) -> Result<PackageRecord, Box<dyn std::error::Error>> {
+    // p2 metadata only lists tagged releases, newest first
+    let url = format!("https://repo.packagist.org/p2/{}.json", name);
+    let resp: PackagistResponse = reqwest::get(&url).await?.json().await?;
+
+    let latest = resp
+        .packages
+        .get(name)
+        .and_then(|versions| versions.first())
+        .ok_or_else(|| format!("no releases found on packagist for {}", name))?;
+
+    let source = latest
+        .source
+        .as_ref()
+        .map(|s| normalize_repository_url(&s.url))
+        .unwrap_or_else(|| format!("https://packagist.org/packages/{}", name));
+
+    Ok(PackageRecord {
+        name: name.to_string(),
+        version: composer_version(&latest.version),
+        hash: composer_hash(latest.dist.as_ref()),
+        trust_score: 0.0,
+        endorsed_by: Vec::new(),
+        last_reviewed: Utc::now().date_naive().to_string(),
+        source,
+        ..Default::default()
     })
 }
+
+// a downloaded package artifact and the version it belongs to
+pub struct Artifact {
+    pub version: String,
+    pub url: String,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct PyPiArtifacts {
+    info: PyPiInfo,
+    urls: Vec<PyPiReleaseFile>,
+}
+
+#[derive(Deserialize)]
+struct GoProxyInfo {
+    #[serde(rename = "Version")]
+    version: String,
+}
+
+// download what the package manager would build from: the sdist (wheels run no code on
+// install), the .crate, the npm tarball or the go module zip; latest when no version is given
+pub async fn download_artifact(client: &reqwest::Client, name: &str, version: Option<&str>, lang: &Language) -> Result<Artifact, Box<dyn std::error::Error>> {
+    let not_found = || format!("{} {} was not found upstream", name, version.unwrap_or("(latest)"));
+    let (version, url) = match lang {
+        Language::Python => {
+            let url = match version {
+                Some(v) => format!("https://pypi.org/pypi/{}/{}/json", name, v),
+                None => format!("https://pypi.org/pypi/{}/json", name),
+            };
+            let release: PyPiArtifacts = get_json(client, &url).await?.ok_or_else(not_found)?;
+            let sdist = release
+                .urls
+                .into_iter()
+                .find(|u| u.packagetype == "sdist")
+                .ok_or_else(|| format!("{} {} has no sdist on pypi", name, release.info.version))?;
+            (release.info.version, sdist.url)
+        }
+        Language::Rust => {
+            let version = match version {
+                Some(v) => v.to_string(),
+                None => {
+                    let resp: CratesResponse = get_json(client, &format!("https://crates.io/api/v1/crates/{}", name)).await?.ok_or_else(not_found)?;
+                    resp.krate.version
+                }
+            };
+            let url = format!("https://static.crates.io/crates/{}/{}-{}.crate", name, name, version);
+            (version, url)
+        }
+        Language::Node => {
+            let url = format!("https://registry.npmjs.org/{}/{}", npm_registry_path(name), version.unwrap_or("latest"));
+            let resp: NpmVersionResponse = get_json(client, &url).await?.ok_or_else(not_found)?;
+            (resp.version, resp.dist.tarball)
+        }
+        Language::Go => {
+            let base = format!("https://proxy.golang.org/{}", normalize_name(name, lang));
+            let version = match version {
+                Some(v) => format!("v{}", v.trim_start_matches('v')),
+                None => {
+                    let info: GoProxyInfo = get_json(client, &format!("{}/@latest", base)).await?.ok_or_else(not_found)?;
+                    info.version
+                }
+            };
+            let url = format!("{}/@v/{}.zip", base, version);
+            (version.trim_start_matches('v').to_string(), url)
+        }
+        _ => return Err(format!("artifact downloads are not supported for {} yet", lang).into()),
+    };
+
+    let resp = client.get(&url).send().await?;
+    if matches!(resp.status().as_u16(), 404 | 410) {
+        return Err(not_found().into());
+    }
+    let bytes = resp.error_for_status()?.bytes().await?.to_vec();
+    Ok(Artifact { version, url, bytes })
+}
+
+// decompression bomb limits, far above what any real package ships
+const MAX_UNPACKED_ENTRIES: usize = 100_000;
+const MAX_UNPACKED_BYTES: u64 = 512 * 1024 * 1024;
+
+// running totals of one unpack, enforced while reading rather than after
+struct UnpackBudget {
+    entries: usize,
+    bytes: u64,
+}
+
+impl UnpackBudget {
+    fn new(entries: usize, bytes: u64) -> Self {
+        UnpackBudget { entries, bytes }
+    }
+
+    fn entry(&mut self) -> Result<(), String> {
+        self.entries = self.entries.checked_sub(1).ok_or("artifact has too many entries to unpack")?;
+        Ok(())
+    }
+
+    fn read(&mut self, reader: impl Read) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut content = Vec::new();
+        // one byte past the budget is enough to tell it was exceeded
+        reader.take(self.bytes + 1).read_to_end(&mut content)?;
+        self.bytes = self
+            .bytes
+            .checked_sub(content.len() as u64)
+            .ok_or("artifact unpacks to more data than allowed")?;
+        Ok(content)
+    }
+}
+
+impl Default for UnpackBudget {
+    fn default() -> Self {
+        UnpackBudget::new(MAX_UNPACKED_ENTRIES, MAX_UNPACKED_BYTES)
+    }
+}
+
+// every file of a .tar.gz or .zip artifact, keyed by its path below the top-level directory
+// (`<name>-<version>/`, npm's `package/`, or go's `<module>@<version>/`)
+pub fn unpack_artifact(bytes: &[u8]) -> Result<BTreeMap<String, Vec<u8>>, Box<dyn std::error::Error>> {
+    unpack_within(bytes, UnpackBudget::default())
+}
+
+fn unpack_within(bytes: &[u8], mut budget: UnpackBudget) -> Result<BTreeMap<String, Vec<u8>>, Box<dyn std::error::Error>> {
+    let mut files = BTreeMap::new();
+    if bytes.starts_with(b"PK\x03\x04") {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+        for i in 0..archive.len() {
+            budget.entry()?;
+            let file = archive.by_index(i)?;
+            if !file.is_file() {
+                continue;
+            }
+            let name = file.name().to_string();
+            // go module paths contain slashes, the version marks the end of the prefix
+            let relative = match name.find('@') {
+                Some(at) => name[at..].split_once('/').map(|(_, rest)| rest),
+                None => name.split_once('/').map(|(_, rest)| rest),
+            };
+            let Some(relative) = relative.map(str::to_string) else {
+                continue;
+            };
+            files.insert(relative, budget.read(file)?);
+        }
+        return Ok(files);
+    }
+
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    for entry in archive.entries()? {
+        budget.entry()?;
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().replace('\\', "/");
+        let Some((_, relative)) = path.split_once('/') else {
+            continue;
+        };
+        let relative = relative.to_string();
+        files.insert(relative, budget.read(entry)?);
+    }
+    Ok(files)
+}
+
+// check an artifact against a recorded hash: Ok(true) when it matches, Ok(false) when the hash
+// is of a form that cannot be recomputed from the artifact (git refs, placeholders)
+pub fn check_digest(bytes: &[u8], recorded: &str) -> Result<bool, String> {
+    let is_hex = |hex: &&str, len: usize| hex.len() == len && hex.chars().all(|c| c.is_ascii_hexdigit());
+    let actual = if let Some(expected) = recorded.strip_prefix("sha256:").filter(|hex| is_hex(hex, 64)) {
+        let actual: String = Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect();
+        if actual.eq_ignore_ascii_case(expected) {
+            return Ok(true);
+        }
+        format!("sha256:{}", actual)
+    } else if let Some(expected) = recorded.strip_prefix("sha1:").filter(|hex| is_hex(hex, 40)) {
+        // npm shasums, packagist dists and maven .sha1 files only publish sha1
+        let digest = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, bytes);
+        let actual: String = digest.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+        if actual.eq_ignore_ascii_case(expected) {
+            return Ok(true);
+        }
+        format!("sha1:{}", actual)
+    } else if recorded.starts_with("sha512-") {
+        format!("sha512-{}", BASE64.encode(Sha512::digest(bytes)))
+    } else if recorded.starts_with("sha256-") {
+        format!("sha256-{}", BASE64.encode(Sha256::digest(bytes)))
+    } else if recorded.starts_with("h1:") {
+        go_module_hash(bytes).map_err(|e| format!("cannot hash go module zip: {}", e))?
+    } else {
+        return Ok(false);
+    };
+
+    if actual == recorded {
+        Ok(true)
+    } else {
+        Err(format!("downloaded artifact is {}, expected {}", actual, recorded))
+    }
+}
+
+// go's dirhash "h1": sha256 over the sorted `<sha256>  <path>` lines of every file in the zip
+fn go_module_hash(bytes: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+    let mut budget = UnpackBudget::default();
+    let mut lines = Vec::new();
+    for i in 0..archive.len() {
+        budget.entry()?;
+        let mut file = archive.by_index(i)?;
+        if !file.is_file() {
+            continue;
+        }
+        let content = budget.read(&mut file)?;
+        let digest: String = Sha256::digest(&content).iter().map(|b| format!("{:02x}", b)).collect();
+        lines.push((file.name().to_string(), digest));
+    }
+    lines.sort();
+
+    let summary: String = lines.iter().map(|(name, digest)| format!("{}  {}\n", digest, name)).collect();
+    Ok(format!("h1:{}", BASE64.encode(Sha256::digest(summary.as_bytes()))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpack_strips_top_level_directory() {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        let content = b"import os\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "example-1.0.0/setup.py", &content[..]).unwrap();
+        let bytes = builder.into_inner().unwrap().finish().unwrap();
+        assert_eq!(unpack_artifact(&bytes).unwrap().get("setup.py").map(Vec::as_slice), Some(&content[..]));
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.start_file("github.com/example/mod@v1.0.0/go.mod", zip::write::SimpleFileOptions::default()).unwrap();
+        std::io::Write::write_all(&mut writer, b"module github.com/example/mod\n").unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+        let files = unpack_artifact(&bytes).unwrap();
+        assert_eq!(files.keys().collect::<Vec<_>>(), vec!["go.mod"]);
+        assert!(check_digest(&bytes, &go_module_hash(&bytes).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn test_unpack_stops_at_limits() {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        for name in ["a", "b", "c"] {
+            let content = vec![0u8; 1024];
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, format!("bomb-1.0.0/{}", name), &content[..]).unwrap();
+        }
+        let bytes = builder.into_inner().unwrap().finish().unwrap();
+
+        assert_eq!(unpack_within(&bytes, UnpackBudget::new(3, 3 * 1024)).unwrap().len(), 3);
+        assert!(unpack_within(&bytes, UnpackBudget::new(2, 3 * 1024)).is_err());
+        assert!(unpack_within(&bytes, UnpackBudget::new(3, 3 * 1024 - 1)).is_err());
+    }
+
+    #[test]
+    fn test_check_digest() {
+        let digest = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert_eq!(check_digest(b"hello", digest), Ok(true));
+        assert!(check_digest(b"hello!", digest).is_err());
+        let sri = "sha512-m3HSJL1i83hdltRq0+o9czGb+8KJDKra4t/3JRlnPKcjI8PZm6XBHXx6zG4UuMXaDEZjR1wuXDre9G9zvN7AQw==";
+        assert_eq!(check_digest(b"hello", sri), Ok(true));
+        assert_eq!(check_digest(b"hello", "sha1:aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"), Ok(true));
+        assert!(check_digest(b"hello!", "sha1:aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d").is_err());
+        assert_eq!(check_digest(b"hello", "sha256:manual"), Ok(false));
+    }
+
+    #[test]
+    fn test_npm_registry_path_escapes_scope() {
+        assert_eq!(npm_registry_path("@types/node"), "@types%2fnode");
+        assert_eq!(npm_registry_path("lodash"), "lodash");
+    }
+
+    #[test]
+    fn test_normalize_repository_url() {
+        assert_eq!(
+            normalize_repository_url("git+https://github.com/expressjs/express.git"),
+            "https://github.com/expressjs/express"
+        );
+        assert_eq!(
+            normalize_repository_url("git://github.com/lodash/lodash.git"),
+            "https://github.com/lodash/lodash"
+        );
+        assert_eq!(
+            normalize_repository_url("github:DefinitelyTyped/DefinitelyTyped"),
+            "https://github.com/DefinitelyTyped/DefinitelyTyped"
+        );
+    }
+
+    #[test]
+    fn test_composer_hash_falls_back_to_reference() {
+        let dist = PackagistDist { shasum: Some(String::new()), reference: Some("a1b2c3".to_string()) };
+        assert_eq!(composer_hash(Some(&dist)), "git:a1b2c3");
+        assert_eq!(composer_version("v6.3.0"), "6.3.0");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_local_maven_repository() {
+        let repo = std::env::temp_dir().join(format!("sip-maven-repo-{}", std::process::id()));
+        let dir = repo.join("org/example/widget/1.2.0");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            repo.join("org/example/widget/maven-metadata.xml"),
+            "<metadata><versioning><latest>1.2.0</latest><release>1.2.0</release></versioning></metadata>",
+        ).unwrap();
+        std::fs::write(dir.join("widget-1.2.0.jar.sha1"), "da39a3ee5e6b4b0d3255bfef95601890afd80709  widget-1.2.0.jar\n").unwrap();
+        std::fs::write(
+            dir.join("widget-1.2.0.pom"),
+            "<project><scm><url>https://github.com/example/widget</url></scm></project>",
+        ).unwrap();
+
+        let record = fetch_from_maven("org.example:widget", repo.to_str().unwrap()).await.unwrap();
+        std::fs::remove_dir_all(&repo).unwrap();
+
+        assert_eq!(record.name, "org.example:widget");
+        assert_eq!(record.version, "1.2.0");
+        assert_eq!(record.hash, "sha1:da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(record.source, "https://github.com/example/widget");
+    }
+}
@@ -0,0 +1,289 @@
+// license metadata: spdx expressions read from each ecosystem's own fields (or a LICENSE file
+// when that is all there is), checked against the `[licenses]` allow and deny lists
+
+use crate::sip::config::LicenseConfig;
+use crate::sip::policy::glob_match;
+use std::collections::BTreeMap;
+
+// trove classifiers that name exactly one spdx license; "BSD License" and friends do not
+const CLASSIFIERS: &[(&str, &str)] = &[
+    ("License :: OSI Approved :: MIT License", "MIT"),
+    ("License :: OSI Approved :: MIT No Attribution License (MIT-0)", "MIT-0"),
+    ("License :: OSI Approved :: Apache Software License", "Apache-2.0"),
+    ("License :: OSI Approved :: ISC License (ISCL)", "ISC"),
+    ("License :: OSI Approved :: Mozilla Public License 2.0 (MPL 2.0)", "MPL-2.0"),
+    ("License :: OSI Approved :: Python Software Foundation License", "PSF-2.0"),
+    ("License :: OSI Approved :: The Unlicense (Unlicense)", "Unlicense"),
+    ("License :: OSI Approved :: zlib/libpng License", "Zlib"),
+    ("License :: OSI Approved :: Eclipse Public License 2.0 (EPL-2.0)", "EPL-2.0"),
+    ("License :: OSI Approved :: GNU General Public License v2 (GPLv2)", "GPL-2.0-only"),
+    ("License :: OSI Approved :: GNU General Public License v2 or later (GPLv2+)", "GPL-2.0-or-later"),
+    ("License :: OSI Approved :: GNU General Public License v3 (GPLv3)", "GPL-3.0-only"),
+    ("License :: OSI Approved :: GNU General Public License v3 or later (GPLv3+)", "GPL-3.0-or-later"),
+    ("License :: OSI Approved :: GNU Lesser General Public License v2 (LGPLv2)", "LGPL-2.0-only"),
+    ("License :: OSI Approved :: GNU Lesser General Public License v2 or later (LGPLv2+)", "LGPL-2.0-or-later"),
+    ("License :: OSI Approved :: GNU Lesser General Public License v3 (LGPLv3)", "LGPL-3.0-only"),
+    ("License :: OSI Approved :: GNU Lesser General Public License v3 or later (LGPLv3+)", "LGPL-3.0-or-later"),
+    ("License :: OSI Approved :: GNU Affero General Public License v3", "AGPL-3.0-only"),
+    ("License :: OSI Approved :: GNU Affero General Public License v3 or later (AGPLv3+)", "AGPL-3.0-or-later"),
+    ("License :: CC0 1.0 Universal (CC0 1.0) Public Domain Dedication", "CC0-1.0"),
+];
+
+// phrases that identify a license text; the gnu variants come first because the lesser and
+// affero texts mention the plain gpl, and bsd-3 is bsd-2 plus the endorsement clause
+const LICENSE_TEXTS: &[(&str, &[&str])] = &[
+    ("AGPL-3.0-only", &["gnu affero general public license", "version 3"]),
+    ("LGPL-3.0-only", &["gnu lesser general public license", "version 3"]),
+    ("LGPL-2.1-only", &["gnu lesser general public license", "version 2.1"]),
+    ("GPL-3.0-only", &["gnu general public license", "version 3"]),
+    ("GPL-2.0-only", &["gnu general public license", "version 2"]),
+    ("MPL-2.0", &["mozilla public license version 2.0"]),
+    ("Apache-2.0", &["apache license", "version 2.0"]),
+    ("BSD-3-Clause", &["redistribution and use in source and binary forms", "neither the name"]),
+    ("BSD-2-Clause", &["redistribution and use in source and binary forms"]),
+    ("ISC", &["permission to use, copy, modify, and", "distribute this software for any purpose with or without fee"]),
+    ("MIT", &["permission is hereby granted, free of charge"]),
+    ("Unlicense", &["this is free and unencumbered software released into the public domain"]),
+];
+
+// free-text license fields that are spdx ids in all but spelling
+const ALIASES: &[(&str, &str)] = &[
+    ("mit license", "MIT"),
+    ("apache 2.0", "Apache-2.0"),
+    ("apache-2", "Apache-2.0"),
+    ("apache license 2.0", "Apache-2.0"),
+    ("apache license, version 2.0", "Apache-2.0"),
+    ("apache software license", "Apache-2.0"),
+    ("bsd-3", "BSD-3-Clause"),
+    ("new bsd", "BSD-3-Clause"),
+    ("bsd-2", "BSD-2-Clause"),
+    ("mpl 2.0", "MPL-2.0"),
+    ("isc license", "ISC"),
+];
+
+pub fn classifier_license(classifier: &str) -> Option<&'static str> {
+    CLASSIFIERS.iter().find(|(c, _)| *c == classifier.trim()).map(|(_, id)| *id)
+}
+
+pub fn detect_license_text(text: &str) -> Option<&'static str> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    LICENSE_TEXTS
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+        .map(|(id, _)| *id)
+}
+
+// an spdx expression as written, or a well-known alias of one; free text is not a license id
+pub fn spdx_expression(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Some((_, id)) = ALIASES.iter().find(|(alias, _)| value.eq_ignore_ascii_case(alias)) {
+        return Some(id.to_string());
+    }
+    let looks_like_id = |token: &str| token.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+    let tokens: Vec<&str> = value.split(|c: char| c.is_whitespace() || c == '(' || c == ')').filter(|t| !t.is_empty()).collect();
+    let plausible = !tokens.is_empty()
+        && value.len() <= 100
+        && tokens.iter().all(|token| looks_like_id(token))
+        && parse(value).is_some();
+    plausible.then(|| value.to_string())
+}
+
+// pep 639 `license_expression`, else the license classifiers, else a `license` field that is
+// already an spdx id
+pub fn pypi_license(expression: Option<&str>, classifiers: &[String], license: Option<&str>) -> Option<String> {
+    // an expression that does not parse is ignored rather than recorded as if it were spdx
+    if let Some(expression) = expression.and_then(spdx_expression) {
+        return Some(expression);
+    }
+    let mut ids: Vec<&str> = classifiers.iter().filter_map(|c| classifier_license(c)).collect();
+    ids.sort();
+    ids.dedup();
+    if !ids.is_empty() {
+        // classifiers do not say whether the licenses are alternatives, so all of them apply
+        return Some(ids.join(" AND "));
+    }
+    license.and_then(spdx_expression)
+}
+
+// npm's `license` is an spdx expression, or an object with a `type` in older manifests, and
+// older still a `licenses` list whose entries all apply
+pub fn npm_license(license: Option<&serde_json::Value>, licenses: Option<&serde_json::Value>) -> Option<String> {
+    let name = |value: &serde_json::Value| value.as_str().or_else(|| value.get("type")?.as_str()).map(str::to_string);
+    if let Some(license) = license.and_then(name) {
+        return spdx_expression(&license);
+    }
+    let ids: Vec<String> = licenses?.as_array()?.iter().filter_map(name).collect();
+    (!ids.is_empty()).then(|| ids.join(" AND ")).and_then(|ids| spdx_expression(&ids))
+}
+
+// the licenses of the LICENSE/COPYING files at the root of an unpacked artifact; several files
+// are combined with AND, the conservative reading when nothing says the user may choose
+pub fn license_from_files(files: &BTreeMap<String, Vec<u8>>) -> Option<String> {
+    let mut ids: Vec<&str> = files
+        .iter()
+        .filter(|(path, _)| {
+            let name = path.to_uppercase();
+            !name.contains('/') && (name.starts_with("LICENSE") || name.starts_with("LICENCE") || name.starts_with("COPYING"))
+        })
+        .filter_map(|(_, content)| std::str::from_utf8(content).ok().and_then(detect_license_text))
+        .collect();
+    ids.sort();
+    ids.dedup();
+    (!ids.is_empty()).then(|| ids.join(" AND "))
+}
+
+// an spdx expression in disjunctive normal form: any one clause, every id within it.
+// exceptions (`WITH ...`) stay attached to nothing, only the license ids are policy-relevant
+type Clauses = Vec<Vec<String>>;
+
+fn parse(expression: &str) -> Option<Clauses> {
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut pos = 0;
+    let clauses = parse_or(&tokens, &mut pos)?;
+    (pos == tokens.len()).then_some(clauses)
+}
+
+fn parse_or(tokens: &[&str], pos: &mut usize) -> Option<Clauses> {
+    let mut clauses = parse_and(tokens, pos)?;
+    while tokens.get(*pos).is_some_and(|t| t.eq_ignore_ascii_case("OR")) {
+        *pos += 1;
+        clauses.extend(parse_and(tokens, pos)?);
+    }
+    Some(clauses)
+}
+
+fn parse_and(tokens: &[&str], pos: &mut usize) -> Option<Clauses> {
+    let mut clauses = parse_term(tokens, pos)?;
+    while tokens.get(*pos).is_some_and(|t| t.eq_ignore_ascii_case("AND")) {
+        *pos += 1;
+        let right = parse_term(tokens, pos)?;
+        clauses = clauses
+            .iter()
+            .flat_map(|left| right.iter().map(move |r| left.iter().chain(r).cloned().collect()))
+            .collect();
+    }
+    Some(clauses)
+}
+
+fn parse_term(tokens: &[&str], pos: &mut usize) -> Option<Clauses> {
+    let token = *tokens.get(*pos)?;
+    *pos += 1;
+    if token == "(" {
+        let clauses = parse_or(tokens, pos)?;
+        (tokens.get(*pos) == Some(&")")).then_some(())?;
+        *pos += 1;
+        return Some(clauses);
+    }
+    if token == ")" || ["AND", "OR", "WITH"].iter().any(|op| token.eq_ignore_ascii_case(op)) {
+        return None;
+    }
+    if tokens.get(*pos).is_some_and(|t| t.eq_ignore_ascii_case("WITH")) {
+        tokens.get(*pos + 1)?;
+        *pos += 2;
+    }
+    Some(vec![vec![token.to_string()]])
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseCheck {
+    Allowed,
+    // ids on the deny list
+    Denied(Vec<String>),
+    // ids missing from a non-empty allow list
+    NotAllowed(Vec<String>),
+    // not a parseable spdx expression
+    Unparsed,
+}
+
+impl std::fmt::Display for LicenseCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LicenseCheck::Allowed => write!(f, "allowed"),
+            LicenseCheck::Denied(ids) => write!(f, "{} is denied", ids.join(", ")),
+            LicenseCheck::NotAllowed(ids) => write!(f, "{} is not on the allowed list", ids.join(", ")),
+            LicenseCheck::Unparsed => write!(f, "not a valid spdx expression"),
+        }
+    }
+}
+
+impl LicenseConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    fn denies(&self, id: &str) -> bool {
+        self.deny.iter().any(|pattern| glob_match(pattern, id))
+    }
+
+    fn allows(&self, id: &str) -> bool {
+        !self.denies(id) && (self.allow.is_empty() || self.allow.iter().any(|pattern| glob_match(pattern, id)))
+    }
+
+    // an expression passes when the user can pick a clause made only of acceptable licenses
+    pub fn check(&self, expression: &str) -> LicenseCheck {
+        let Some(clauses) = parse(expression) else {
+            return LicenseCheck::Unparsed;
+        };
+        if clauses.iter().any(|clause| clause.iter().all(|id| self.allows(id))) {
+            return LicenseCheck::Allowed;
+        }
+        let mut denied: Vec<String> = clauses.iter().flatten().filter(|id| self.denies(id)).cloned().collect();
+        denied.sort();
+        denied.dedup();
+        if !denied.is_empty() {
+            return LicenseCheck::Denied(denied);
+        }
+        let mut missing: Vec<String> = clauses.iter().flatten().filter(|id| !self.allows(id)).cloned().collect();
+        missing.sort();
+        missing.dedup();
+        LicenseCheck::NotAllowed(missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_sources() {
+        let classifiers = vec![
+            "Programming Language :: Python :: 3".to_string(),
+            "License :: OSI Approved :: Apache Software License".to_string(),
+        ];
+        assert_eq!(pypi_license(Some("BSD-3-Clause"), &classifiers, None).as_deref(), Some("BSD-3-Clause"));
+        assert_eq!(pypi_license(None, &classifiers, Some("whatever")).as_deref(), Some("Apache-2.0"));
+        assert_eq!(pypi_license(None, &[], Some("MIT License")).as_deref(), Some("MIT"));
+        assert_eq!(pypi_license(None, &[], Some("Copyright (c) 2024, all rights reserved.")), None);
+        assert_eq!(pypi_license(Some("see LICENSE.txt, or not"), &classifiers, None).as_deref(), Some("Apache-2.0"));
+        let dual = vec![
+            "License :: OSI Approved :: MIT License".to_string(),
+            "License :: OSI Approved :: GNU General Public License v3 (GPLv3)".to_string(),
+        ];
+        assert_eq!(pypi_license(None, &dual, None).as_deref(), Some("GPL-3.0-only AND MIT"));
+
+        let files: BTreeMap<String, Vec<u8>> = [
+            ("LICENSE".to_string(), b"MIT License\n\nPermission is hereby granted, free of charge, to any person".to_vec()),
+            ("vendor/LICENSE".to_string(), b"GNU GENERAL PUBLIC LICENSE\nVersion 3, 29 June 2007".to_vec()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(license_from_files(&files).as_deref(), Some("MIT"));
+        assert_eq!(detect_license_text("GNU LESSER GENERAL PUBLIC LICENSE\n   Version 2.1, February 1999"), Some("LGPL-2.1-only"));
+    }
+
+    #[test]
+    fn test_license_lists() {
+        let config = LicenseConfig { allow: Vec::new(), deny: vec!["GPL-*".to_string(), "AGPL-*".to_string()] };
+        assert_eq!(config.check("MIT OR Apache-2.0"), LicenseCheck::Allowed);
+        assert_eq!(config.check("MIT OR GPL-3.0-only"), LicenseCheck::Allowed);
+        assert_eq!(config.check("MIT AND GPL-3.0-only"), LicenseCheck::Denied(vec!["GPL-3.0-only".to_string()]));
+        assert_eq!(config.check("(MIT OR GPL-2.0-only) AND AGPL-3.0-only"), LicenseCheck::Denied(vec!["AGPL-3.0-only".to_string(), "GPL-2.0-only".to_string()]));
+        assert_eq!(config.check("GPL-2.0-only WITH Classpath-exception-2.0"), LicenseCheck::Denied(vec!["GPL-2.0-only".to_string()]));
+        assert_eq!(config.check("MIT AND"), LicenseCheck::Unparsed);
+
+        let config = LicenseConfig { allow: vec!["MIT".to_string(), "Apache-2.0".to_string()], deny: Vec::new() };
+        assert_eq!(config.check("Apache-2.0"), LicenseCheck::Allowed);
+        assert_eq!(config.check("MPL-2.0"), LicenseCheck::NotAllowed(vec!["MPL-2.0".to_string()]));
+    }
+}
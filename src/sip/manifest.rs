@@ -0,0 +1,506 @@
+// dependency extraction from project manifests, used by `sip vet`, and from lockfiles, used
+// by `sip sbom` to list what a project actually resolves to
+
+use crate::cli::Language;
+use crate::sip::maven;
+use crate::sip::version::is_exact;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    pub version: Option<String>,
+    pub language: Language,
+    pub manifest: String,
+    // the digest a lockfile pins, in registry record form (sha256:<hex>, sha512-<base64>, h1:...)
+    pub hash: Option<String>,
+}
+
+impl Dependency {
+    // the one version this dependency can resolve to, if the manifest pins it; a range
+    // cannot rule out any version, so blocks and reviews must treat it as unpinned
+    pub fn pinned_version(&self) -> Option<&str> {
+        // cargo reads a bare "1.2.3" as "^1.2.3"
+        let caret_by_default = self.language == Language::Rust && self.manifest == "Cargo.toml";
+        self.version.as_deref().filter(|v| is_exact(v) && !caret_by_default)
+    }
+}
+
+// manifests sip knows how to read, per language
+const MANIFESTS: &[(&str, Language)] = &[
+    ("requirements.txt", Language::Python),
+    ("Cargo.toml", Language::Rust),
+    ("go.mod", Language::Go),
+    ("package.json", Language::Node),
+    ("pom.xml", Language::Java),
+    ("build.gradle", Language::Java),
+    ("build.gradle.kts", Language::Java),
+    ("Gemfile", Language::Ruby),
+    ("composer.json", Language::Php),
+];
+
+pub fn collect_dependencies(
+    dir: &Path,
+    lang: Option<&Language>,
+) -> Result<Vec<Dependency>, Box<dyn std::error::Error>> {
+    let mut dependencies = Vec::new();
+
+    for (file, language) in MANIFESTS {
+        if lang.is_some_and(|l| l != language) {
+            continue;
+        }
+
+        let path = dir.join(file);
+        if !path.exists() {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let parsed = match file.to_owned() {
+            "requirements.txt" => parse_requirements(&content),
+            "Cargo.toml" => parse_cargo_toml(&content)?,
+            "go.mod" => parse_go_mod(&content),
+            "package.json" => parse_package_json(&content)?,
+            "pom.xml" => to_pairs(maven::parse_pom_dependencies(&content)),
+            "Gemfile" => parse_gemfile(&content),
+            "composer.json" => parse_composer_json(&content)?,
+            _ => to_pairs(maven::parse_gradle_dependencies(&content)),
+        };
+
+        dependencies.extend(parsed.into_iter().map(|(name, version)| Dependency {
+            name,
+            version,
+            language: language.clone(),
+            manifest: file.to_string(),
+            hash: None,
+        }));
+    }
+
+    Ok(dependencies)
+}
+
+// (name, resolved version, pinned digest) as recorded in a lockfile
+type LockedDependency = (String, String, Option<String>);
+
+// lockfiles sip knows how to read; go.sum only adds digests to go.mod's exact requirements
+const LOCKFILES: &[(&str, Language)] = &[
+    ("poetry.lock", Language::Python),
+    ("Cargo.lock", Language::Rust),
+    ("package-lock.json", Language::Node),
+    ("Gemfile.lock", Language::Ruby),
+    ("composer.lock", Language::Php),
+];
+
+// the resolved dependency tree: a language's lockfile replaces its manifest, languages without
+// one fall back to the declared requirements
+pub fn collect_resolved_dependencies(
+    dir: &Path,
+    lang: Option<&Language>,
+) -> Result<Vec<Dependency>, Box<dyn std::error::Error>> {
+    let mut locked = Vec::new();
+    for (file, language) in LOCKFILES {
+        if lang.is_some_and(|l| l != language) {
+            continue;
+        }
+
+        let path = dir.join(file);
+        if !path.exists() {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let parsed = match file.to_owned() {
+            "poetry.lock" => parse_poetry_lock(&content)?,
+            "Cargo.lock" => parse_cargo_lock(&content)?,
+            "package-lock.json" => parse_package_lock(&content)?,
+            "Gemfile.lock" => parse_gemfile_lock(&content),
+            _ => parse_composer_lock(&content)?,
+        };
+
+        locked.extend(parsed.into_iter().map(|(name, version, hash)| Dependency {
+            name,
+            version: Some(version),
+            language: language.clone(),
+            manifest: file.to_string(),
+            hash,
+        }));
+    }
+
+    let go_sum = dir.join("go.sum");
+    let go_sums = if go_sum.exists() { parse_go_sum(&fs::read_to_string(&go_sum)?) } else { Vec::new() };
+
+    let mut dependencies: Vec<Dependency> = collect_dependencies(dir, lang)?
+        .into_iter()
+        .filter(|dep| !locked.iter().any(|l| l.language == dep.language))
+        .map(|mut dep| {
+            if dep.language == Language::Go {
+                dep.hash = go_sums
+                    .iter()
+                    .find(|(module, version, _)| *module == dep.name && Some(version) == dep.version.as_ref())
+                    .and_then(|(_, _, hash)| hash.clone());
+            }
+            dep
+        })
+        .collect();
+    dependencies.extend(locked);
+    Ok(dependencies)
+}
+
+// (name, version requirement) as written in the manifest
+type DeclaredDependency = (String, Option<String>);
+
+fn to_pairs(coords: Vec<maven::MavenCoordinate>) -> Vec<DeclaredDependency> {
+    coords.into_iter().map(|c| (c.name(), c.version)).collect()
+}
+
+fn parse_requirements(content: &str) -> Vec<DeclaredDependency> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .map(|line| {
+            // drop environment markers and extras: `pkg[extra]==1.0; python_version < "3.8"`
+            let spec = line.split(';').next().unwrap_or(line).trim();
+            let name_end = spec
+                .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == '.'))
+                .unwrap_or(spec.len());
+            let name = spec[..name_end].to_string();
+            let version = spec
+                .split_once("==")
+                .map(|(_, v)| v.trim().to_string());
+            (name, version)
+        })
+        .collect()
+}
+
+fn parse_cargo_toml(content: &str) -> Result<Vec<DeclaredDependency>, Box<dyn std::error::Error>> {
+    let manifest: toml::Table = toml::from_str(content)?;
+    let mut deps = Vec::new();
+
+    for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        let Some(table) = manifest.get(section).and_then(|t| t.as_table()) else {
+            continue;
+        };
+
+        for (key, value) in table {
+            let (name, version) = match value {
+                toml::Value::String(v) => (key.clone(), Some(v.clone())),
+                toml::Value::Table(t) => {
+                    // path/git dependencies have no registry version, renamed ones use `package`
+                    let name = t.get("package").and_then(|p| p.as_str()).unwrap_or(key).to_string();
+                    let version = t.get("version").and_then(|v| v.as_str()).map(|v| v.to_string());
+                    if version.is_none() && !t.contains_key("workspace") {
+                        continue;
+                    }
+                    (name, version)
+                }
+                _ => continue,
+            };
+            deps.push((name, version));
+        }
+    }
+
+    Ok(deps)
+}
+
+fn parse_go_mod(content: &str) -> Vec<DeclaredDependency> {
+    let mut deps = Vec::new();
+    let mut in_block = false;
+
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+
+        let spec = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "require (" {
+            in_block = true;
+            continue;
+        } else if let Some(rest) = line.strip_prefix("require ") {
+            rest
+        } else {
+            continue;
+        };
+
+        let mut parts = spec.split_whitespace();
+        if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+            deps.push((module.to_string(), Some(version.trim_start_matches('v').to_string())));
+        }
+    }
+
+    deps
+}
+
+fn parse_package_json(content: &str) -> Result<Vec<DeclaredDependency>, Box<dyn std::error::Error>> {
+    let manifest: serde_json::Value = serde_json::from_str(content)?;
+    let mut deps = Vec::new();
+
+    for section in ["dependencies", "devDependencies", "optionalDependencies"] {
+        if let Some(table) = manifest.get(section).and_then(|t| t.as_object()) {
+            for (name, version) in table {
+                deps.push((name.clone(), version.as_str().map(|v| v.to_string())));
+            }
+        }
+    }
+
+    Ok(deps)
+}
+
+// `gem "rails", "~> 7.0"` - the first quoted argument is the name, the second the requirement
+fn parse_gemfile(content: &str) -> Vec<DeclaredDependency> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter_map(|line| line.strip_prefix("gem "))
+        .filter_map(|args| {
+            let mut quoted = args
+                .split(',')
+                .map(|arg| arg.trim())
+                .take_while(|arg| arg.starts_with(['\'', '"']))
+                .map(|arg| arg.trim_matches(['\'', '"']).to_string());
+            let name = quoted.next()?;
+            Some((name, quoted.next()))
+        })
+        .collect()
+}
+
+fn parse_composer_json(content: &str) -> Result<Vec<DeclaredDependency>, Box<dyn std::error::Error>> {
+    let manifest: serde_json::Value = serde_json::from_str(content)?;
+    let mut deps = Vec::new();
+
+    for section in ["require", "require-dev"] {
+        if let Some(table) = manifest.get(section).and_then(|t| t.as_object()) {
+            for (name, version) in table {
+                // platform requirements (php, ext-json, lib-curl) are not packages
+                if !name.contains('/') {
+                    continue;
+                }
+                deps.push((name.clone(), version.as_str().map(|v| v.to_string())));
+            }
+        }
+    }
+
+    Ok(deps)
+}
+
+fn parse_poetry_lock(content: &str) -> Result<Vec<LockedDependency>, Box<dyn std::error::Error>> {
+    let lock: toml::Table = toml::from_str(content)?;
+    let packages = lock.get("package").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    Ok(packages
+        .iter()
+        .filter_map(|package| {
+            let name = package.get("name")?.as_str()?.to_string();
+            let version = package.get("version")?.as_str()?.to_string();
+            // the sdist's digest, as fetch_from_pypi records it
+            let files = package.get("files").and_then(|f| f.as_array()).cloned().unwrap_or_default();
+            let file = files
+                .iter()
+                .find(|f| f.get("file").and_then(|f| f.as_str()).is_some_and(|f| f.ends_with(".tar.gz")))
+                .or(files.first());
+            let hash = file.and_then(|f| f.get("hash")?.as_str()).map(str::to_string);
+            Some((name, version, hash))
+        })
+        .collect())
+}
+
+// workspace members and git checkouts have no registry source to check against
+fn parse_cargo_lock(content: &str) -> Result<Vec<LockedDependency>, Box<dyn std::error::Error>> {
+    let lock: toml::Table = toml::from_str(content)?;
+    let packages = lock.get("package").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    Ok(packages
+        .iter()
+        .filter(|package| package.get("source").and_then(|s| s.as_str()).is_some_and(|s| s.starts_with("registry+")))
+        .filter_map(|package| {
+            let name = package.get("name")?.as_str()?.to_string();
+            let version = package.get("version")?.as_str()?.to_string();
+            let hash = package.get("checksum").and_then(|c| c.as_str()).map(|c| format!("sha256:{}", c));
+            Some((name, version, hash))
+        })
+        .collect())
+}
+
+// lockfile v2/v3 list every installed path under `packages`, v1 nests `dependencies`
+fn parse_package_lock(content: &str) -> Result<Vec<LockedDependency>, Box<dyn std::error::Error>> {
+    let lock: serde_json::Value = serde_json::from_str(content)?;
+    let mut deps = Vec::new();
+
+    if let Some(packages) = lock.get("packages").and_then(|p| p.as_object()) {
+        for (path, package) in packages {
+            let Some((_, name)) = path.rsplit_once("node_modules/") else {
+                continue;
+            };
+            if package.get("link").and_then(|l| l.as_bool()) == Some(true) {
+                continue;
+            }
+            if let Some(version) = package.get("version").and_then(|v| v.as_str()) {
+                let hash = package.get("integrity").and_then(|i| i.as_str()).map(str::to_string);
+                deps.push((name.to_string(), version.to_string(), hash));
+            }
+        }
+    } else if let Some(dependencies) = lock.get("dependencies") {
+        let mut pending = vec![dependencies];
+        while let Some(table) = pending.pop().and_then(|t| t.as_object()) {
+            for (name, package) in table {
+                if let Some(version) = package.get("version").and_then(|v| v.as_str()) {
+                    let hash = package.get("integrity").and_then(|i| i.as_str()).map(str::to_string);
+                    deps.push((name.clone(), version.to_string(), hash));
+                }
+                pending.extend(package.get("dependencies"));
+            }
+        }
+    }
+
+    // the same version can be installed at several paths
+    deps.sort();
+    deps.dedup();
+    Ok(deps)
+}
+
+// `    name (version)` lines of the GEM specs; deeper lines are the gems' own requirements
+fn parse_gemfile_lock(content: &str) -> Vec<LockedDependency> {
+    let mut deps = Vec::new();
+    let mut in_specs = false;
+    let mut section = "";
+
+    for line in content.lines() {
+        if !line.starts_with(' ') {
+            section = line.trim();
+            in_specs = false;
+            continue;
+        }
+        if line.trim() == "specs:" {
+            in_specs = section == "GEM";
+            continue;
+        }
+        if !in_specs || !line.starts_with("    ") || line.starts_with("     ") {
+            continue;
+        }
+        if let Some((name, version)) = line.trim().split_once(" (") {
+            deps.push((name.to_string(), version.trim_end_matches(')').to_string(), None));
+        }
+    }
+
+    deps
+}
+
+fn parse_composer_lock(content: &str) -> Result<Vec<LockedDependency>, Box<dyn std::error::Error>> {
+    let lock: serde_json::Value = serde_json::from_str(content)?;
+    let mut deps = Vec::new();
+
+    for section in ["packages", "packages-dev"] {
+        for package in lock.get(section).and_then(|p| p.as_array()).into_iter().flatten() {
+            let (Some(name), Some(version)) = (package.get("name").and_then(|n| n.as_str()), package.get("version").and_then(|v| v.as_str())) else {
+                continue;
+            };
+            let hash = package
+                .get("dist")
+                .and_then(|d| d.get("shasum")?.as_str())
+                .filter(|s| !s.is_empty())
+                .map(|s| format!("sha1:{}", s));
+            deps.push((name.to_string(), version.trim_start_matches('v').to_string(), hash));
+        }
+    }
+
+    Ok(deps)
+}
+
+// `module version h1:...` lines; the `/go.mod` lines only cover the module's go.mod file
+fn parse_go_sum(content: &str) -> Vec<LockedDependency> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (module, version, hash) = (fields.next()?, fields.next()?, fields.next()?);
+            (!version.ends_with("/go.mod")).then(|| (module.to_string(), version.trim_start_matches('v').to_string(), Some(hash.to_string())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_requirements() {
+        let deps = parse_requirements("requests==2.31.0\n# comment\n-r dev.txt\nflask[async]>=2.0 ; python_version > \"3.7\"\n");
+        assert_eq!(deps, vec![
+            ("requests".to_string(), Some("2.31.0".to_string())),
+            ("flask".to_string(), None),
+        ]);
+    }
+
+    #[test]
+    fn test_ranges_are_not_pinned() {
+        let dep = |version: &str, language: Language, manifest: &str| Dependency {
+            name: "x".to_string(),
+            version: Some(version.to_string()),
+            language,
+            manifest: manifest.to_string(),
+            hash: None,
+        };
+        assert_eq!(dep("3.3.6", Language::Node, "package.json").pinned_version(), Some("3.3.6"));
+        assert_eq!(dep("^3.3.0", Language::Node, "package.json").pinned_version(), None);
+        assert_eq!(dep("1.0.188", Language::Rust, "Cargo.toml").pinned_version(), None);
+        assert_eq!(dep("1.0.188", Language::Rust, "Cargo.lock").pinned_version(), Some("1.0.188"));
+    }
+
+    #[test]
+    fn test_parse_go_mod() {
+        let deps = parse_go_mod("module x\n\nrequire github.com/pkg/errors v0.9.1\nrequire (\n    github.com/gin-gonic/gin v1.9.1 // indirect\n)\n");
+        assert_eq!(deps, vec![
+            ("github.com/pkg/errors".to_string(), Some("0.9.1".to_string())),
+            ("github.com/gin-gonic/gin".to_string(), Some("1.9.1".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_gemfile_and_composer_json() {
+        let gems = parse_gemfile("source \"https://rubygems.org\"\ngem \"rails\", \"~> 7.0\"\ngem 'pg' # database\ngem 'puma', require: false\n");
+        assert_eq!(gems, vec![
+            ("rails".to_string(), Some("~> 7.0".to_string())),
+            ("pg".to_string(), None),
+            ("puma".to_string(), None),
+        ]);
+
+        let composer = parse_composer_json(r#"{"require": {"php": ">=8.1", "ext-json": "*", "monolog/monolog": "^3.0"}}"#).unwrap();
+        assert_eq!(composer, vec![("monolog/monolog".to_string(), Some("^3.0".to_string()))]);
+    }
+
+    #[test]
+    fn test_parse_cargo_toml_skips_path_dependencies() {
+        let mut deps = parse_cargo_toml("[dependencies]\nserde = \"1.0\"\nlocal = { path = \"../local\" }\nrenamed = { package = \"tokio\", version = \"1\" }\n").unwrap();
+        deps.sort();
+        assert_eq!(deps, vec![
+            ("serde".to_string(), Some("1.0".to_string())),
+            ("tokio".to_string(), Some("1".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_lockfiles() {
+        let cargo = parse_cargo_lock("version = 3\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.188\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\nchecksum = \"cf9e\"\n").unwrap();
+        assert_eq!(cargo, vec![("serde".to_string(), "1.0.188".to_string(), Some("sha256:cf9e".to_string()))]);
+
+        let npm = parse_package_lock(r#"{"lockfileVersion": 3, "packages": {
+            "": {"name": "app"},
+            "node_modules/@types/node": {"version": "20.1.0", "integrity": "sha512-abc"},
+            "node_modules/a/node_modules/left-pad": {"version": "1.3.0"},
+            "node_modules/left-pad": {"version": "1.3.0"},
+            "node_modules/local": {"link": true}
+        }}"#).unwrap();
+        assert_eq!(npm, vec![
+            ("@types/node".to_string(), "20.1.0".to_string(), Some("sha512-abc".to_string())),
+            ("left-pad".to_string(), "1.3.0".to_string(), None),
+        ]);
+
+        let gems = parse_gemfile_lock("GEM\n  remote: https://rubygems.org/\n  specs:\n    rack (3.0.8)\n    rails (7.1.0)\n      rack (>= 2.2.4)\n\nPLATFORMS\n  ruby\n");
+        assert_eq!(gems.iter().map(|(n, v, _)| format!("{} {}", n, v)).collect::<Vec<_>>(), vec!["rack 3.0.8", "rails 7.1.0"]);
+
+        let sums = parse_go_sum("github.com/pkg/errors v0.9.1 h1:FEBL=\ngithub.com/pkg/errors v0.9.1/go.mod h1:bwaw=\n");
+        assert_eq!(sums, vec![("github.com/pkg/errors".to_string(), "0.9.1".to_string(), Some("h1:FEBL=".to_string()))]);
+    }
+}
@@ -0,0 +1,310 @@
+// maven coordinates, repository layout and pom/gradle parsing for the jvm backend
+
+use std::fs;
+
+pub const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2";
+
+// `groupId:artifactId[:version]`, the registry name is always `groupId:artifactId`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MavenCoordinate {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+}
+
+impl MavenCoordinate {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = spec.trim().split(':').collect();
+        let (group_id, artifact_id, version) = match parts.as_slice() {
+            [g, a] => (*g, *a, None),
+            [g, a, v] => (*g, *a, Some(*v)),
+            // groupId:artifactId:packaging:version, as printed by `mvn dependency:list`
+            [g, a, _, v] => (*g, *a, Some(*v)),
+            _ => return Err(format!("invalid maven coordinate '{}' (expected groupId:artifactId[:version])", spec)),
+        };
+
+        if group_id.is_empty() || artifact_id.is_empty() || version == Some("") {
+            return Err(format!("invalid maven coordinate '{}' (expected groupId:artifactId[:version])", spec));
+        }
+
+        Ok(MavenCoordinate {
+            group_id: group_id.to_string(),
+            artifact_id: artifact_id.to_string(),
+            version: version.map(|v| v.to_string()),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        format!("{}:{}", self.group_id, self.artifact_id)
+    }
+
+    // directory of the artifact inside a maven2 repository layout
+    pub fn artifact_path(&self) -> String {
+        format!("{}/{}", self.group_id.replace('.', "/"), self.artifact_id)
+    }
+}
+
+// the repository to resolve artifacts from, overridable for mirrors and local file repositories
+pub fn default_repository() -> String {
+    std::env::var("SIP_MAVEN_REPOSITORY").unwrap_or_else(|_| MAVEN_CENTRAL.to_string())
+}
+
+// read a file from a remote (http/https) or local (file:// or plain path) maven repository
+pub async fn read_repository_file(repository: &str, path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let repository = repository.trim_end_matches('/');
+
+    if repository.starts_with("http://") || repository.starts_with("https://") {
+        let url = format!("{}/{}", repository, path);
+        let resp = reqwest::get(&url).await?;
+        if !resp.status().is_success() {
+            return Err(format!("{} returned {}", url, resp.status()).into());
+        }
+        Ok(resp.text().await?)
+    } else {
+        let root = repository.strip_prefix("file://").unwrap_or(repository);
+        let file = format!("{}/{}", root, path);
+        fs::read_to_string(&file).map_err(|e| format!("failed to read {}: {}", file, e).into())
+    }
+}
+
+// text of the first `<tag>...</tag>` element, without any xml parsing beyond that
+pub fn xml_tag(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    Some(xml[start..end].trim().to_string())
+}
+
+// every `<tag>...</tag>` block in document order
+pub fn xml_blocks<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut blocks = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&open) {
+        let body = &rest[start + open.len()..];
+        match body.find(&close) {
+            Some(end) => {
+                blocks.push(&body[..end]);
+                rest = &body[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+
+    blocks
+}
+
+// resolve `${property}` references against the pom's <properties> section
+fn resolve_pom_property(value: &str, pom: &str) -> Option<String> {
+    match value.strip_prefix("${").and_then(|v| v.strip_suffix('}')) {
+        Some(property) => {
+            let properties = xml_tag(pom, "properties")?;
+            xml_tag(&properties, property)
+        }
+        None => Some(value.to_string()),
+    }
+}
+
+// the pom without any `<tag>...</tag>` blocks
+fn without_blocks(xml: &str, tag: &str) -> String {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut kept = String::with_capacity(xml.len());
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&open) {
+        kept.push_str(&rest[..start]);
+        match rest[start..].find(&close) {
+            Some(end) => rest = &rest[start + end + close.len()..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+
+    kept.push_str(rest);
+    kept
+}
+
+fn dependency_blocks(dependencies: &str, pom: &str) -> Vec<MavenCoordinate> {
+    xml_blocks(dependencies, "dependency")
+        .into_iter()
+        .filter_map(|block| {
+            let group_id = xml_tag(block, "groupId")?;
+            let artifact_id = xml_tag(block, "artifactId")?;
+            let version = xml_tag(block, "version").and_then(|v| resolve_pom_property(&v, pom));
+            Some(MavenCoordinate { group_id, artifact_id, version })
+        })
+        .collect()
+}
+
+pub fn parse_pom_dependencies(pom: &str) -> Vec<MavenCoordinate> {
+    // <dependencyManagement> only pins versions and plugin <dependencies> live under <build>,
+    // neither adds a dependency to the project
+    let managed = xml_tag(pom, "dependencyManagement")
+        .and_then(|management| xml_tag(&management, "dependencies"))
+        .map(|dependencies| dependency_blocks(&dependencies, pom))
+        .unwrap_or_default();
+    let project = without_blocks(&without_blocks(pom, "dependencyManagement"), "build");
+    let Some(dependencies) = xml_tag(&project, "dependencies") else {
+        return Vec::new();
+    };
+
+    dependency_blocks(&dependencies, pom)
+        .into_iter()
+        .map(|mut dep| {
+            if dep.version.is_none() {
+                dep.version = managed.iter().find(|m| m.name() == dep.name()).and_then(|m| m.version.clone());
+            }
+            dep
+        })
+        .collect()
+}
+
+const GRADLE_CONFIGURATIONS: &[&str] = &[
+    "implementation",
+    "api",
+    "compileOnly",
+    "runtimeOnly",
+    "testImplementation",
+    "testCompileOnly",
+    "testRuntimeOnly",
+    "annotationProcessor",
+    "kapt",
+    "classpath",
+    "compile",
+    "testCompile",
+];
+
+// handles both groovy (`implementation 'g:a:v'`) and kotlin dsl (`implementation("g:a:v")`)
+pub fn parse_gradle_dependencies(script: &str) -> Vec<MavenCoordinate> {
+    script
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| {
+            GRADLE_CONFIGURATIONS.iter().any(|conf| {
+                line.strip_prefix(conf)
+                    .is_some_and(|rest| rest.starts_with([' ', '(', '\'', '"']))
+            })
+        })
+        .filter_map(|line| {
+            let start = line.find(['\'', '"'])?;
+            let quote = line[start..].chars().next()?;
+            let end = line[start + 1..].find(quote)? + start + 1;
+            MavenCoordinate::parse(&line[start + 1..end]).ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coordinate() {
+        let coord = MavenCoordinate::parse("com.google.guava:guava:32.1.2-jre").unwrap();
+        assert_eq!(coord.name(), "com.google.guava:guava");
+        assert_eq!(coord.version.as_deref(), Some("32.1.2-jre"));
+        assert_eq!(coord.artifact_path(), "com/google/guava/guava");
+
+        assert!(MavenCoordinate::parse("guava").is_err());
+        assert!(MavenCoordinate::parse("com.google.guava::1.0").is_err());
+    }
+
+    #[test]
+    fn test_parse_pom_dependencies() {
+        let pom = r#"
+            <project>
+              <properties>
+                <jackson.version>2.15.2</jackson.version>
+              </properties>
+              <dependencies>
+                <dependency>
+                  <groupId>com.fasterxml.jackson.core</groupId>
+                  <artifactId>jackson-databind</artifactId>
+                  <version>${jackson.version}</version>
+                </dependency>
+                <dependency>
+                  <groupId>junit</groupId>
+                  <artifactId>junit</artifactId>
+                  <scope>test</scope>
+                </dependency>
+              </dependencies>
+            </project>
+        "#;
+
+        let deps = parse_pom_dependencies(pom);
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[0].name(), "com.fasterxml.jackson.core:jackson-databind");
+        assert_eq!(deps[0].version.as_deref(), Some("2.15.2"));
+        assert_eq!(deps[1].name(), "junit:junit");
+        assert_eq!(deps[1].version, None);
+    }
+
+    #[test]
+    fn test_parse_pom_skips_dependency_management() {
+        let pom = r#"
+            <project>
+              <dependencyManagement>
+                <dependencies>
+                  <dependency>
+                    <groupId>org.slf4j</groupId>
+                    <artifactId>slf4j-api</artifactId>
+                    <version>2.0.7</version>
+                  </dependency>
+                  <dependency>
+                    <groupId>com.google.guava</groupId>
+                    <artifactId>guava</artifactId>
+                    <version>32.1.2-jre</version>
+                  </dependency>
+                </dependencies>
+              </dependencyManagement>
+              <dependencies>
+                <dependency>
+                  <groupId>org.slf4j</groupId>
+                  <artifactId>slf4j-api</artifactId>
+                </dependency>
+              </dependencies>
+              <build>
+                <plugins>
+                  <plugin>
+                    <dependencies>
+                      <dependency>
+                        <groupId>org.ow2.asm</groupId>
+                        <artifactId>asm</artifactId>
+                        <version>9.5</version>
+                      </dependency>
+                    </dependencies>
+                  </plugin>
+                </plugins>
+              </build>
+            </project>
+        "#;
+
+        let deps = parse_pom_dependencies(pom);
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].name(), "org.slf4j:slf4j-api");
+        assert_eq!(deps[0].version.as_deref(), Some("2.0.7"));
+    }
+
+    #[test]
+    fn test_parse_gradle_dependencies() {
+        let script = r#"
+            dependencies {
+                implementation 'org.slf4j:slf4j-api:2.0.7'
+                testImplementation("org.junit.jupiter:junit-jupiter:5.10.0")
+                implementation project(':core')
+                apiVersion = "1.0"
+            }
+        "#;
+
+        let deps = parse_gradle_dependencies(script);
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[0].name(), "org.slf4j:slf4j-api");
+        assert_eq!(deps[1].version.as_deref(), Some("5.10.0"));
+    }
+}
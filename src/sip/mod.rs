@@ -4,3 +4,26 @@ pub mod verify;
 pub mod prompt;
 pub mod fetch;
 pub mod package;
+pub mod maven;
+pub mod manifest;
+pub mod detect;
+pub mod normalize;
+pub mod version;
+pub mod paths;
+pub mod cache;
+pub mod config;
+pub mod audit;
+pub mod review;
+pub mod registry_diff;
+pub mod registry_lint;
+pub mod upstream;
+pub mod registry_audit;
+pub mod scan;
+pub mod package_diff;
+pub mod provenance;
+pub mod source_check;
+pub mod policy;
+pub mod license;
+pub mod sbom;
+#[cfg(test)]
+pub mod testing;
@@ -0,0 +1,186 @@
+// ecosystem-specific package name normalization, so lookups match the way each
+// upstream registry decides whether two names refer to the same package
+
+use crate::cli::Language;
+use crate::sip::package::PackageRecord;
+use std::collections::{HashMap, HashSet};
+
+pub fn normalize_name(name: &str, lang: &Language) -> String {
+    let name = name.trim();
+    match lang {
+        Language::Python => normalize_pep503(name),
+        // crates.io treats `-` and `_` as the same character and ignores case
+        Language::Rust => name.to_lowercase().replace('_', "-"),
+        Language::Go => escape_go_module_path(name),
+        // npm, rubygems and packagist reject names that only differ by case
+        Language::Node | Language::Ruby | Language::Php => name.to_lowercase(),
+        // maven coordinates are case sensitive
+        Language::Java => name.to_string(),
+    }
+}
+
+// PEP 503: lowercase and collapse runs of `-`, `_` and `.` into a single `-`
+fn normalize_pep503(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut in_separator = false;
+
+    for c in name.chars() {
+        if c == '-' || c == '_' || c == '.' {
+            if !in_separator {
+                normalized.push('-');
+            }
+            in_separator = true;
+        } else {
+            normalized.extend(c.to_lowercase());
+            in_separator = false;
+        }
+    }
+
+    normalized
+}
+
+// go module paths are case sensitive; the proxy protocol encodes each uppercase letter as
+// `!` + lowercase, which gives a canonical form that is safe to compare (and already-escaped
+// input maps to itself)
+fn escape_go_module_path(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '!' {
+            // already escaped, keep the marker and the lowercase letter that follows
+            escaped.push('!');
+            if let Some(next) = chars.next() {
+                escaped.push(next);
+            }
+        } else if c.is_ascii_uppercase() {
+            escaped.push('!');
+            escaped.push(c.to_ascii_lowercase());
+        } else {
+            escaped.push(c);
+        }
+    }
+
+    escaped
+}
+
+// two records clash when they normalize to the same name but are spelled differently,
+// or when the same name and version is listed twice; one pass, grouped by normalized name
+pub fn find_duplicates(packages: &[PackageRecord], lang: &Language) -> Vec<String> {
+    let mut groups: HashMap<String, Vec<&PackageRecord>> = HashMap::new();
+    let mut order = Vec::new();
+    for package in packages {
+        let key = normalize_name(&package.name, lang);
+        let group = groups.entry(key.clone()).or_default();
+        if group.is_empty() {
+            order.push(key);
+        }
+        group.push(package);
+    }
+
+    let mut duplicates = Vec::new();
+    for key in &order {
+        let group = &groups[key];
+        let mut spellings: Vec<&str> = Vec::new();
+        let mut versions = HashSet::new();
+        let mut repeated = HashSet::new();
+        for package in group {
+            if !spellings.contains(&package.name.as_str()) {
+                spellings.push(&package.name);
+            }
+            if !versions.insert((&package.name, &package.version)) && repeated.insert((&package.name, &package.version)) {
+                duplicates.push(format!("'{}' {} is listed more than once", package.name, package.version));
+            }
+        }
+        for other in spellings.iter().skip(1) {
+            duplicates.push(format!("'{}' and '{}' are the same package", spellings[0], other));
+        }
+    }
+    duplicates
+}
+
+// edit distance counting adjacent transpositions as one edit, so `reqeusts` is one away
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+// trusted names a couple of edits away from `name`, the shape of a typosquat; short names
+// only tolerate one edit or every three-letter package would look alike
+pub fn similar_names<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>, lang: &Language) -> Vec<String> {
+    let normalized = normalize_name(name, lang);
+    let max_distance = if normalized.chars().count() <= 5 { 1 } else { 2 };
+    let mut similar: Vec<(usize, String)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&normalized, &normalize_name(candidate, lang));
+            (distance > 0 && distance <= max_distance).then(|| (distance, candidate.to_string()))
+        })
+        .collect();
+    similar.sort();
+    similar.dedup_by(|a, b| a.1 == b.1);
+    similar.into_iter().map(|(_, name)| name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sip::testing::record;
+
+    #[test]
+    fn test_python_normalization() {
+        assert_eq!(normalize_name("Flask", &Language::Python), "flask");
+        assert_eq!(normalize_name("typing_extensions", &Language::Python), "typing-extensions");
+        assert_eq!(normalize_name("zope.interface", &Language::Python), "zope-interface");
+        assert_eq!(normalize_name("Foo__-.Bar", &Language::Python), "foo-bar");
+    }
+
+    #[test]
+    fn test_rust_and_go_normalization() {
+        assert_eq!(normalize_name("serde_json", &Language::Rust), normalize_name("serde-json", &Language::Rust));
+        assert_eq!(
+            normalize_name("github.com/BurntSushi/toml", &Language::Go),
+            "github.com/!burnt!sushi/toml"
+        );
+        assert_eq!(normalize_name("github.com/!burnt!sushi/toml", &Language::Go), normalize_name("github.com/BurntSushi/toml", &Language::Go));
+        assert_ne!(normalize_name("github.com/Sirupsen/logrus", &Language::Go), normalize_name("github.com/sirupsen/logrus", &Language::Go));
+    }
+
+    #[test]
+    fn test_similar_names() {
+        let trusted = ["requests", "flask", "numpy", "django"];
+        assert_eq!(similar_names("reqeusts", trusted, &Language::Python), vec!["requests"]);
+        assert_eq!(similar_names("request", trusted, &Language::Python), vec!["requests"]);
+        assert_eq!(similar_names("Requests", trusted, &Language::Python), Vec::<String>::new());
+        assert_eq!(similar_names("flsk", trusted, &Language::Python), vec!["flask"]);
+        assert_eq!(similar_names("numba", trusted, &Language::Python), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let packages = vec![record("flask", "2.3.2"), record("Flask", "2.3.3"), record("requests", "2.31.0")];
+        assert_eq!(find_duplicates(&packages, &Language::Python).len(), 1);
+
+        let packages = vec![record("requests", "2.31.0"), record("requests", "2.32.4")];
+        assert!(find_duplicates(&packages, &Language::Python).is_empty());
+
+        let packages = vec![record("requests", "2.32.4"), record("requests", "2.32.4"), record("requests", "2.32.4")];
+        assert_eq!(find_duplicates(&packages, &Language::Python), vec!["'requests' 2.32.4 is listed more than once"]);
+    }
+}
@@ -1,6 +1,7 @@
+use crate::sip::scan::InstallScan;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct PackageRecord {
     pub name: String,
     pub version: String,
@@ -9,4 +10,41 @@ pub struct PackageRecord {
     pub endorsed_by: Vec<String>,
     pub last_reviewed: String,  // ISO date
     pub source: String,         // must be a valid URI
+    // spdx license expression, e.g. "MIT OR Apache-2.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    // withdrawn upstream: crates.io yank, pypi pep 592 yank, go retract or npm deprecation
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub yanked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yank_reason: Option<String>,
+    // upstream owners when the record was reviewed: pypi roles, crates.io owners, npm
+    // maintainers, or the repository host and owner a go module resolves to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maintainers: Vec<String>,
+    // install-time risk scan of the reviewed artifact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_scan: Option<InstallScan>,
+}
+
+// a known-malicious or banned package; blocks win over any trust entry
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockedRecord {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,  // empty means every version
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    pub blocked_on: String,     // ISO date
+}
+
+impl BlockedRecord {
+    // an unpinned request cannot rule out a blocked version, so it counts as affected
+    pub fn affects(&self, version: Option<&str>) -> bool {
+        match version {
+            Some(v) if !self.versions.is_empty() => self.versions.iter().any(|blocked| blocked == v),
+            _ => true,
+        }
+    }
 }
@@ -0,0 +1,135 @@
+// source diff between two published versions of a package: compares the artifacts upstream
+// actually serves, which is what gets installed, rather than the git history behind them
+
+use crate::cli::Language;
+use crate::sip::scan::{self, ScanFinding};
+use serde::Serialize;
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet};
+
+// files that run, or decide what runs, when the package is installed or built
+const INSTALL_TIME_FILES: &[(Language, &[&str])] = &[
+    (Language::Python, &["setup.py", "setup.cfg", "pyproject.toml"]),
+    (Language::Rust, &["build.rs", "Cargo.toml"]),
+    (Language::Node, &["package.json", "binding.gyp"]),
+    (Language::Go, &["go.mod"]),
+];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FileDiff {
+    pub path: String,
+    pub change: FileChange,
+    pub install_time: bool,
+    pub binary: bool,
+}
+
+impl std::fmt::Display for FileDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let marker = match self.change {
+            FileChange::Added => "A",
+            FileChange::Removed => "D",
+            FileChange::Modified => "M",
+        };
+        write!(f, "{}  {}", marker, self.path)?;
+        if self.install_time {
+            write!(f, "  ⚠ install-time code")?;
+        }
+        if self.binary && self.change == FileChange::Added {
+            write!(f, "  ⚠ new binary")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn is_install_time(path: &str, lang: &Language) -> bool {
+    INSTALL_TIME_FILES
+        .iter()
+        .find(|(l, _)| l == lang)
+        .is_some_and(|(_, files)| files.contains(&path))
+}
+
+// git's heuristic: a nul byte early on, or content that is not utf-8
+pub fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|b| *b == 0) || std::str::from_utf8(content).is_err()
+}
+
+pub fn diff_files(old: &BTreeMap<String, Vec<u8>>, new: &BTreeMap<String, Vec<u8>>, lang: &Language) -> Vec<FileDiff> {
+    let paths: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (change, content) = match (old.get(path), new.get(path)) {
+                (None, Some(content)) => (FileChange::Added, content),
+                (Some(content), None) => (FileChange::Removed, content),
+                (Some(before), Some(after)) if before != after => (FileChange::Modified, after),
+                _ => return None,
+            };
+            Some(FileDiff {
+                path: path.clone(),
+                change,
+                install_time: is_install_time(path, lang),
+                binary: is_binary(content),
+            })
+        })
+        .collect()
+}
+
+// unified diff of one text file, None for binaries
+pub fn unified_diff(path: &str, old: Option<&[u8]>, new: Option<&[u8]>) -> Option<String> {
+    let old = std::str::from_utf8(old.unwrap_or_default()).ok()?;
+    let new = std::str::from_utf8(new.unwrap_or_default()).ok()?;
+    let diff = TextDiff::from_lines(old, new);
+    Some(diff.unified_diff().context_radius(3).header(&format!("a/{}", path), &format!("b/{}", path)).to_string())
+}
+
+// risky install-time patterns in the new version that the old one did not have
+pub fn new_findings(old: &BTreeMap<String, Vec<u8>>, new: &BTreeMap<String, Vec<u8>>, lang: &Language) -> Vec<ScanFinding> {
+    if !scan::supports(lang) {
+        return Vec::new();
+    }
+    let before = scan::scan_files(&scan::text_files(old), lang);
+    // line numbers shift between versions, the pattern and its line are what matter
+    scan::scan_files(&scan::text_files(new), lang)
+        .into_iter()
+        .filter(|finding| !before.iter().any(|b| b.file == finding.file && b.risk == finding.risk && b.excerpt == finding.excerpt))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(entries: &[(&str, &[u8])]) -> BTreeMap<String, Vec<u8>> {
+        entries.iter().map(|(path, content)| (path.to_string(), content.to_vec())).collect()
+    }
+
+    #[test]
+    fn test_diff_files_flags_install_time_code_and_binaries() {
+        let old = tree(&[("setup.py", b"setup()\n"), ("lib/a.py", b"a = 1\n"), ("README", b"old\n")]);
+        let new = tree(&[
+            ("setup.py", b"import os\nos.system('id')\nsetup()\n"),
+            ("lib/a.py", b"a = 1\n"),
+            ("lib/_speedups.so", b"\x7fELF\x00\x01"),
+        ]);
+
+        let diffs = diff_files(&old, &new, &Language::Python);
+        let summary: Vec<String> = diffs.iter().map(|d| d.to_string()).collect();
+        assert_eq!(summary, vec!["D  README", "A  lib/_speedups.so  ⚠ new binary", "M  setup.py  ⚠ install-time code"]);
+
+        let findings = new_findings(&old, &new, &Language::Python);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 2);
+
+        let patch = unified_diff("setup.py", old.get("setup.py").map(Vec::as_slice), new.get("setup.py").map(Vec::as_slice)).unwrap();
+        assert!(patch.starts_with("--- a/setup.py\n+++ b/setup.py\n"));
+        assert!(patch.contains("+os.system('id')\n"));
+    }
+}
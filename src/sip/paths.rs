@@ -0,0 +1,22 @@
+// per-user sip state (cache, logs) lives under ~/.sip unless SIP_HOME points elsewhere
+
+use std::path::PathBuf;
+
+pub fn sip_home() -> PathBuf {
+    if let Ok(home) = std::env::var("SIP_HOME") {
+        return PathBuf::from(home);
+    }
+
+    let user_home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(user_home).join(".sip")
+}
+
+pub fn cache_dir() -> PathBuf {
+    sip_home().join("cache")
+}
+
+pub fn log_dir() -> PathBuf {
+    sip_home().join("logs")
+}
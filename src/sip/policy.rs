@@ -0,0 +1,401 @@
+// organization policy: declarative allow/deny/warn rules from a toml file, evaluated by verify,
+// install and vet on top of the registry's trust scores
+//
+// rules are checked in file order. every matching warn rule is reported, and the first
+// matching allow or deny rule decides. the `[licenses]` lists are checked before any rule, so
+// a denied license cannot be allowed back in by name. blocklist entries are not subject to policy.
+
+use crate::cli::Language;
+use crate::sip::config::{Config, LicenseConfig};
+use crate::sip::license::LicenseCheck;
+use crate::sip::normalize::normalize_name;
+use crate::sip::package::PackageRecord;
+use crate::sip::scan::{Risk, ScanFinding};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    // trusted without review, whatever the registry says
+    Allow,
+    // never trusted
+    Deny,
+    // reported, does not change the verdict
+    Warn,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    pub action: PolicyAction,
+    #[serde(default)]
+    pub message: Option<String>,
+    // name globs, e.g. "acme-*"
+    #[serde(default)]
+    pub packages: Vec<String>,
+    #[serde(default)]
+    pub languages: Vec<String>,
+    // project environments the rule applies in, from `[policy] environment`
+    #[serde(default)]
+    pub environments: Vec<String>,
+    // trust score strictly below this; a package missing from the registry scores 0
+    #[serde(default)]
+    pub score_below: Option<f64>,
+    // install-time scan findings of any of these kinds
+    #[serde(default)]
+    pub risks: Vec<Risk>,
+    // license globs matched against each identifier of the spdx expression, e.g. "GPL-*"
+    #[serde(default)]
+    pub licenses: Vec<String>,
+    // the rule does not apply to packages endorsed by any of these
+    #[serde(default)]
+    pub unless_endorsed_by: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    pub rules: Vec<Rule>,
+    pub environment: Option<String>,
+    pub licenses: LicenseConfig,
+}
+
+// what a package is judged on
+pub struct Subject<'a> {
+    pub name: &'a str,
+    pub language: &'a Language,
+    pub record: Option<&'a PackageRecord>,
+    // findings of a scan of the artifact being installed, in place of the one saved with the review
+    pub scan: Option<&'a [ScanFinding]>,
+    // the license upstream publishes, for a package the registry has no record of
+    pub license: Option<&'a str>,
+}
+
+impl Subject<'_> {
+    fn license(&self) -> Option<&str> {
+        self.record.and_then(|r| r.license.as_deref()).or(self.license)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub action: PolicyAction,
+    pub message: Option<String>,
+}
+
+impl RuleMatch {
+    pub fn marker(&self) -> &'static str {
+        match self.action {
+            PolicyAction::Allow => "✓",
+            PolicyAction::Deny => "✘",
+            PolicyAction::Warn => "⚠",
+        }
+    }
+}
+
+impl std::fmt::Display for RuleMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = match self.action {
+            PolicyAction::Allow => "allowed by",
+            PolicyAction::Deny => "denied by",
+            PolicyAction::Warn => "flagged by",
+        };
+        write!(f, "{} policy rule '{}'", verb, self.rule)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct PolicyVerdict {
+    // the deciding allow or deny rule, if any matched
+    pub decision: Option<RuleMatch>,
+    pub warnings: Vec<RuleMatch>,
+}
+
+impl PolicyVerdict {
+    pub fn allowed(&self) -> bool {
+        self.decision.as_ref().is_some_and(|m| m.action == PolicyAction::Allow)
+    }
+
+    pub fn denied(&self) -> bool {
+        self.decision.as_ref().is_some_and(|m| m.action == PolicyAction::Deny)
+    }
+
+    // every matched rule, warnings first, for the verdict explanation
+    pub fn matches(&self) -> impl Iterator<Item = &RuleMatch> {
+        self.warnings.iter().chain(self.decision.as_ref())
+    }
+}
+
+// `*` matches any run of characters, everything else literally
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+// the license identifiers of an spdx expression, without operators or exceptions
+pub fn license_ids(expression: &str) -> Vec<&str> {
+    expression
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|token| !token.is_empty() && !matches!(*token, "AND" | "OR" | "WITH" | "and" | "or" | "with"))
+        .collect()
+}
+
+impl Rule {
+    fn matches(&self, subject: &Subject, environment: Option<&str>) -> bool {
+        let record = subject.record;
+        let name = normalize_name(subject.name, subject.language);
+        if !self.packages.is_empty() && !self.packages.iter().any(|p| glob_match(&normalize_name(p, subject.language), &name)) {
+            return false;
+        }
+        if !self.languages.is_empty() && !self.languages.contains(&subject.language.to_string()) {
+            return false;
+        }
+        if !self.environments.is_empty() && !environment.is_some_and(|env| self.environments.iter().any(|e| e == env)) {
+            return false;
+        }
+        if let Some(threshold) = self.score_below
+            && record.map_or(0.0, |r| r.trust_score) >= threshold
+        {
+            return false;
+        }
+        if !self.risks.is_empty() {
+            let saved = record.and_then(|r| r.install_scan.as_ref()).map(|scan| scan.findings.as_slice());
+            let findings = subject.scan.or(saved).unwrap_or_default();
+            if !findings.iter().any(|finding| self.risks.contains(&finding.risk)) {
+                return false;
+            }
+        }
+        if !self.licenses.is_empty() {
+            let ids = subject.license().map(license_ids).unwrap_or_default();
+            if !ids.iter().any(|id| self.licenses.iter().any(|pattern| glob_match(pattern, id))) {
+                return false;
+            }
+        }
+        if record.is_some_and(|r| r.endorsed_by.iter().any(|e| self.unless_endorsed_by.contains(e))) {
+            return false;
+        }
+        true
+    }
+}
+
+impl Policy {
+    pub fn load(config: &Config) -> Result<Policy, Box<dyn std::error::Error>> {
+        let policy = config.policy.clone().unwrap_or_default();
+        let rules = match &policy.file {
+            Some(file) => Self::load_rules(Path::new(file))?,
+            None => Vec::new(),
+        };
+        Ok(Policy {
+            rules,
+            environment: policy.environment,
+            licenses: config.licenses.clone().unwrap_or_default(),
+        })
+    }
+
+    fn load_rules(path: &Path) -> Result<Vec<Rule>, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("cannot read policy {}: {}", path.display(), e))?;
+        let file: PolicyFile = toml::from_str(&content).map_err(|e| format!("invalid policy {}: {}", path.display(), e))?;
+        for rule in &file.rules {
+            if let Some(language) = rule.languages.iter().find(|l| !Language::ALL.iter().any(|known| known.to_string() == **l)) {
+                return Err(format!("invalid policy {}: rule '{}' names unknown language '{}'", path.display(), rule.name, language).into());
+            }
+        }
+        Ok(file.rules)
+    }
+
+    // the `[licenses]` lists as a rule named "licenses"; a package without a known license is
+    // only flagged, since most registries leave the field empty for older releases
+    fn license_match(&self, subject: &Subject) -> Option<RuleMatch> {
+        if !self.licenses.is_configured() {
+            return None;
+        }
+        let license = subject.license();
+        let (action, message) = match license.map(|license| (license, self.licenses.check(license))) {
+            None => (PolicyAction::Warn, "no license is recorded".to_string()),
+            Some((_, LicenseCheck::Allowed)) => return None,
+            Some((license, LicenseCheck::Unparsed)) => (PolicyAction::Warn, format!("license '{}' is not a valid spdx expression", license)),
+            Some((license, check)) => (PolicyAction::Deny, format!("license '{}': {}", license, check)),
+        };
+        Some(RuleMatch { rule: "licenses".to_string(), action, message: Some(message) })
+    }
+
+    pub fn evaluate(&self, subject: &Subject) -> PolicyVerdict {
+        let mut verdict = PolicyVerdict::default();
+        match self.license_match(subject) {
+            Some(matched) if matched.action == PolicyAction::Warn => verdict.warnings.push(matched),
+            matched => verdict.decision = matched,
+        }
+        for rule in self.rules.iter().filter(|rule| rule.matches(subject, self.environment.as_deref())) {
+            let matched = RuleMatch {
+                rule: rule.name.clone(),
+                action: rule.action,
+                message: rule.message.clone(),
+            };
+            if rule.action == PolicyAction::Warn {
+                verdict.warnings.push(matched);
+            } else if verdict.decision.is_none() {
+                verdict.decision = Some(matched);
+            }
+        }
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sip::testing::record;
+    use crate::sip::scan::InstallScan;
+
+    const POLICY: &str = r#"
+        [[rules]]
+        name = "internal"
+        action = "allow"
+        packages = ["acme-*"]
+
+        [[rules]]
+        name = "install-hooks"
+        action = "deny"
+        risks = ["install-hook"]
+        unless_endorsed_by = ["security-team"]
+        message = "install hooks need a security-team review"
+
+        [[rules]]
+        name = "copyleft"
+        action = "deny"
+        licenses = ["GPL-*", "AGPL-*"]
+
+        [[rules]]
+        name = "production-score"
+        action = "deny"
+        environments = ["production"]
+        score_below = 8.0
+
+        [[rules]]
+        name = "young-node"
+        action = "warn"
+        languages = ["node"]
+        score_below = 9.0
+    "#;
+
+    fn policy(environment: Option<&str>) -> Policy {
+        let file: PolicyFile = toml::from_str(POLICY).unwrap();
+        Policy { rules: file.rules, environment: environment.map(str::to_string), licenses: LicenseConfig::default() }
+    }
+
+    fn decision(verdict: &PolicyVerdict) -> Option<(&str, PolicyAction)> {
+        verdict.decision.as_ref().map(|m| (m.rule.as_str(), m.action))
+    }
+
+    #[test]
+    fn test_glob_and_license_ids() {
+        assert!(glob_match("acme-*", "acme-billing"));
+        assert!(glob_match("*-internal", "acme-internal"));
+        assert!(glob_match("GPL-*", "GPL-3.0-only"));
+        assert!(!glob_match("GPL-*", "LGPL-2.1"));
+        assert!(glob_match("left-pad", "left-pad"));
+        assert_eq!(license_ids("(MIT OR Apache-2.0) AND GPL-2.0 WITH Classpath-exception-2.0"), vec!["MIT", "Apache-2.0", "GPL-2.0", "Classpath-exception-2.0"]);
+    }
+
+    #[test]
+    fn test_rules_decide_in_order() {
+        let node = Language::Node;
+        let policy = policy(Some("production"));
+
+        let unknown = Subject { name: "acme-billing", language: &node, record: None, scan: None, license: None };
+        assert_eq!(decision(&policy.evaluate(&unknown)), Some(("internal", PolicyAction::Allow)));
+
+        let mut hooked = PackageRecord { trust_score: 9.5, ..record("left-pad", "1.3.0") };
+        hooked.install_scan = Some(InstallScan {
+            scanned_on: "2026-01-01".to_string(),
+            findings: vec![ScanFinding { file: "package.json".to_string(), line: 5, risk: Risk::InstallHook, excerpt: "node install.js".to_string() }],
+        });
+        let subject = Subject { name: "left-pad", language: &node, record: Some(&hooked), scan: None, license: None };
+        assert_eq!(decision(&policy.evaluate(&subject)), Some(("install-hooks", PolicyAction::Deny)));
+
+        hooked.endorsed_by.push("security-team".to_string());
+        let subject = Subject { name: "left-pad", language: &node, record: Some(&hooked), scan: None, license: None };
+        assert_eq!(policy.evaluate(&subject), PolicyVerdict::default());
+
+        let gpl = PackageRecord { license: Some("GPL-3.0-or-later".to_string()), trust_score: 9.5, ..record("left-pad", "1.3.0") };
+        let subject = Subject { name: "left-pad", language: &node, record: Some(&gpl), scan: None, license: None };
+        assert_eq!(decision(&policy.evaluate(&subject)), Some(("copyleft", PolicyAction::Deny)));
+
+        let low = PackageRecord { trust_score: 7.0, ..record("left-pad", "1.3.0") };
+        let subject = Subject { name: "left-pad", language: &node, record: Some(&low), scan: None, license: None };
+        let verdict = policy.evaluate(&subject);
+        assert_eq!(decision(&verdict), Some(("production-score", PolicyAction::Deny)));
+        assert_eq!(verdict.warnings.len(), 1);
+        assert_eq!(verdict.matches().count(), 2);
+
+        // outside production only the warning is left
+        let verdict = self::policy(Some("staging")).evaluate(&subject);
+        assert!(!verdict.denied());
+        assert_eq!(verdict.warnings[0].to_string(), "flagged by policy rule 'young-node'");
+    }
+
+    #[test]
+    fn test_live_scan_replaces_saved_scan() {
+        let node = Language::Node;
+        let policy = policy(None);
+        let reviewed = PackageRecord { trust_score: 9.5, ..record("left-pad", "1.3.0") };
+        let hook = [ScanFinding { file: "package.json".to_string(), line: 5, risk: Risk::InstallHook, excerpt: "node install.js".to_string() }];
+
+        let subject = Subject { name: "left-pad", language: &node, record: Some(&reviewed), scan: None, license: None };
+        assert!(!policy.evaluate(&subject).denied());
+        let subject = Subject { scan: Some(&hook), ..subject };
+        assert_eq!(decision(&policy.evaluate(&subject)), Some(("install-hooks", PolicyAction::Deny)));
+        let unknown = Subject { name: "left-pad", language: &node, record: None, scan: Some(&hook), license: None };
+        assert!(policy.evaluate(&unknown).denied());
+    }
+
+    #[test]
+    fn test_license_lists_come_before_rules() {
+        let node = Language::Node;
+        let mut policy = policy(None);
+        policy.licenses = LicenseConfig { allow: Vec::new(), deny: vec!["SSPL-*".to_string()] };
+
+        let sspl = PackageRecord { license: Some("SSPL-1.0".to_string()), trust_score: 9.5, ..record("left-pad", "1.3.0") };
+        let subject = Subject { name: "acme-db", language: &node, record: Some(&sspl), scan: None, license: None };
+        let verdict = policy.evaluate(&subject);
+        assert_eq!(decision(&verdict), Some(("licenses", PolicyAction::Deny)));
+        assert_eq!(verdict.decision.unwrap().to_string(), "denied by policy rule 'licenses': license 'SSPL-1.0': SSPL-1.0 is denied");
+
+        let unknown = Subject { name: "acme-db", language: &node, record: None, scan: None, license: None };
+        let verdict = policy.evaluate(&unknown);
+        assert_eq!(decision(&verdict), Some(("internal", PolicyAction::Allow)));
+        assert_eq!(verdict.warnings[0].message.as_deref(), Some("no license is recorded"));
+
+        // an unregistered package is judged on the license upstream publishes
+        let fetched = Subject { name: "mongo-tools", license: Some("SSPL-1.0"), ..unknown };
+        assert_eq!(decision(&policy.evaluate(&fetched)), Some(("licenses", PolicyAction::Deny)));
+    }
+}
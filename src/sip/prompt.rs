@@ -1,20 +1,232 @@
-// placeholder implementation for prompt module
-// todo: implement interactive user prompt logic
-
-use dialoguer::Confirm;
-
-pub fn prompt_user_confirmation(
-    package: &str,
-    trust_score: f32,
-    _endorsed_by: &[String],
-) -> Result<bool, Box<dyn std::error::Error>> {
-    println!("package: {}", package);
-    println!("trust score: {:.1}", trust_score);
-    
-    let confirmation = Confirm::new()
-        .with_prompt("do you want to proceed with installation?")
-        .default(false)
-        .interact()?;
-    
-    Ok(confirmation)
+// interactive review of a package sip cannot vouch for: everything known about it is laid out
+// and the user decides what happens next
+
+use crate::sip::package::PackageRecord;
+use dialoguer::{Input, Select};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::IsTerminal;
+use std::process::{Command, Stdio};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "choice", rename_all = "kebab-case")]
+pub enum ReviewChoice {
+    Abort,
+    InstallOnce,
+    // install, then record a local trust entry with this score
+    InstallAndTrust { score: f64 },
+}
+
+impl std::fmt::Display for ReviewChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewChoice::Abort => write!(f, "abort"),
+            ReviewChoice::InstallOnce => write!(f, "install once"),
+            ReviewChoice::InstallAndTrust { score } => write!(f, "install and trust with score {:.1}", score),
+        }
+    }
+}
+
+pub struct ReviewSubject<'a> {
+    pub package: &'a str,
+    // the registry record, when the package is trusted but scores below the threshold
+    pub record: Option<&'a PackageRecord>,
+    // what upstream currently publishes for the package
+    pub upstream: Option<&'a PackageRecord>,
+    // trusted packages whose names are a typo away
+    pub similar: &'a [String],
+    // whether there is a record of the requested version to trust
+    pub can_trust: bool,
+}
+
+impl ReviewSubject<'_> {
+    fn source(&self) -> Option<&str> {
+        self.upstream
+            .into_iter()
+            .chain(self.record)
+            .map(|record| record.source.as_str())
+            .find(|source| source.starts_with("https://") || source.starts_with("http://"))
+    }
+
+    fn maintainers(&self) -> &[String] {
+        self.upstream
+            .into_iter()
+            .chain(self.record)
+            .map(|record| record.maintainers.as_slice())
+            .find(|maintainers| !maintainers.is_empty())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    Abort,
+    InstallOnce,
+    InstallAndTrust,
+    OpenSource,
+    ShowMaintainers,
+}
+
+impl Action {
+    fn label(self) -> &'static str {
+        match self {
+            Action::Abort => "abort",
+            Action::InstallOnce => "install once",
+            Action::InstallAndTrust => "install and trust locally",
+            Action::OpenSource => "open source url",
+            Action::ShowMaintainers => "show maintainers",
+        }
+    }
+}
+
+// one `field: value` line per record field, lists joined and nested values inline
+fn record_lines(record: &PackageRecord) -> Vec<String> {
+    let Ok(Value::Object(fields)) = serde_json::to_value(record) else {
+        return Vec::new();
+    };
+    fields
+        .into_iter()
+        .map(|(field, value)| {
+            let value = match value {
+                Value::String(s) => s,
+                Value::Array(items) => items.iter().map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string)).collect::<Vec<_>>().join(", "),
+                other => other.to_string(),
+            };
+            format!("{}: {}", field, value)
+        })
+        .collect()
+}
+
+fn show_record(title: &str, record: &PackageRecord) {
+    println!("{}:", title);
+    for line in record_lines(record) {
+        println!("  {}", line);
+    }
+}
+
+fn show_subject(subject: &ReviewSubject) {
+    if let Some(record) = subject.record {
+        show_record("registry record", record);
+    }
+    match subject.upstream {
+        Some(upstream) => show_record("upstream metadata", upstream),
+        None => println!("⚠ no upstream metadata could be fetched for {}", subject.package),
+    }
+    if !subject.similar.is_empty() {
+        println!("⚠ '{}' is similar to trusted package(s): {}", subject.package, subject.similar.join(", "));
+    }
+}
+
+// only web urls are handed to the desktop; the url comes from package metadata, so anything
+// else (a file path, a custom scheme handler) is not opened
+fn openable(url: &str) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|parsed| matches!(parsed.scheme(), "https" | "http"))
+}
+
+fn open_url(url: &str) -> std::io::Result<()> {
+    if !openable(url) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "not a web url"));
+    }
+    // explorer takes the url as a single argument, `cmd /C start` would run it through the shell
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        Command::new("explorer")
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(url).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+    Ok(())
+}
+
+// piped stdin keeps the plain y/N question so scripted installs behave as before
+fn confirm_line() -> Result<ReviewChoice, Box<dyn std::error::Error>> {
+    println!("do you want to proceed with installation anyway? [y/N]");
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let answer = input.trim().to_lowercase();
+    Ok(if answer == "y" || answer == "yes" { ReviewChoice::InstallOnce } else { ReviewChoice::Abort })
+}
+
+pub fn review_package(subject: &ReviewSubject) -> Result<ReviewChoice, Box<dyn std::error::Error>> {
+    show_subject(subject);
+    if !std::io::stdin().is_terminal() {
+        return confirm_line();
+    }
+
+    let mut actions = vec![Action::Abort, Action::InstallOnce];
+    if subject.can_trust {
+        actions.push(Action::InstallAndTrust);
+    }
+    actions.extend([Action::OpenSource, Action::ShowMaintainers]);
+    let labels: Vec<&str> = actions.iter().map(|action| action.label()).collect();
+
+    loop {
+        let selected = Select::new().with_prompt("what do you want to do?").items(&labels).default(0).interact()?;
+        match actions[selected] {
+            Action::Abort => return Ok(ReviewChoice::Abort),
+            Action::InstallOnce => return Ok(ReviewChoice::InstallOnce),
+            Action::InstallAndTrust => {
+                let score = Input::<f64>::new()
+                    .with_prompt("trust score (0.0 - 10.0)")
+                    .default(5.0)
+                    .validate_with(|score: &f64| if (0.0..=10.0).contains(score) { Ok(()) } else { Err("score must be between 0.0 and 10.0") })
+                    .interact_text()?;
+                return Ok(ReviewChoice::InstallAndTrust { score });
+            }
+            Action::OpenSource => match subject.source() {
+                Some(url) => match open_url(url) {
+                    Ok(()) => println!("opened {}", url),
+                    Err(e) => println!("⚠ could not open {}: {}", url, e),
+                },
+                None => println!("⚠ no source url is known for {}", subject.package),
+            },
+            Action::ShowMaintainers => match subject.maintainers() {
+                [] => println!("⚠ no maintainers are known for {}", subject.package),
+                maintainers => println!("maintainers: {}", maintainers.join(", ")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_web_urls_are_opened() {
+        assert!(openable("https://github.com/psf/requests"));
+        assert!(!openable("https://x\" & calc & \""));
+        assert!(!openable("file:///etc/passwd"));
+        assert!(!openable("ms-settings:"));
+        assert!(!openable("not a url"));
+    }
+
+    #[test]
+    fn test_review_subject_details() {
+        let record = PackageRecord {
+            name: "reqeusts".to_string(),
+            version: "2.31.0".to_string(),
+            endorsed_by: vec!["alice".to_string(), "bob".to_string()],
+            source: "https://pypi.org/project/reqeusts".to_string(),
+            ..Default::default()
+        };
+        let upstream = PackageRecord {
+            source: "https://github.com/example/reqeusts".to_string(),
+            maintainers: vec!["mallory".to_string()],
+            ..record.clone()
+        };
+
+        let lines = record_lines(&record);
+        assert!(lines.contains(&"endorsed_by: alice, bob".to_string()));
+        assert!(lines.contains(&"version: 2.31.0".to_string()));
+
+        let subject = ReviewSubject { package: "reqeusts", record: Some(&record), upstream: Some(&upstream), similar: &[], can_trust: true };
+        assert_eq!(subject.source(), Some("https://github.com/example/reqeusts"));
+        assert_eq!(subject.maintainers(), ["mallory".to_string()]);
+
+        let subject = ReviewSubject { upstream: None, ..subject };
+        assert_eq!(subject.source(), Some("https://pypi.org/project/reqeusts"));
+        assert!(subject.maintainers().is_empty());
+    }
 }
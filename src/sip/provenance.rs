@@ -0,0 +1,613 @@
+// provenance checks: fetch the sigstore attestation published for an artifact (pep 740 on pypi,
+// npm provenance bundles) and verify it offline against a pinned sigstore trusted root, so a
+// trust decision can rest on "built from this repository by this ci workflow"
+//
+// the trusted root is sigstore's trusted_root.json; sip does not ship one, it is pinned by the
+// user (see README) and every check fails closed without it
+
+use crate::cli::Language;
+use crate::sip::fetch::Artifact;
+use crate::sip::source_check::repository_key;
+use crate::sip::upstream::get_json;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::DateTime;
+use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use std::fs;
+use std::path::Path;
+use x509_parser::pem::parse_x509_pem;
+use x509_parser::prelude::*;
+
+const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+// fulcio certificate extensions describing the ci identity that signed
+const OID_ISSUER: &str = "1.3.6.1.4.1.57264.1.8";
+const OID_BUILD_SIGNER_URI: &str = "1.3.6.1.4.1.57264.1.9";
+const OID_SOURCE_REPOSITORY_URI: &str = "1.3.6.1.4.1.57264.1.12";
+const OID_SOURCE_REPOSITORY_REF: &str = "1.3.6.1.4.1.57264.1.14";
+// pre-v2 github-specific extensions hold raw strings
+const OID_LEGACY_ISSUER: &str = "1.3.6.1.4.1.57264.1.1";
+const OID_LEGACY_REPOSITORY: &str = "1.3.6.1.4.1.57264.1.5";
+const OID_LEGACY_REF: &str = "1.3.6.1.4.1.57264.1.6";
+
+const OID_CURVE_P256: &str = "1.2.840.10045.3.1.7";
+const OID_CURVE_P384: &str = "1.3.132.0.34";
+const OID_ECDSA_SHA256: &str = "1.2.840.10045.4.3.2";
+const OID_ECDSA_SHA384: &str = "1.2.840.10045.4.3.3";
+
+// ecosystems that publish sigstore attestations sip can read
+pub fn supports(lang: &Language) -> bool {
+    matches!(lang, Language::Python | Language::Node)
+}
+
+// what a verified attestation says about how the artifact was built
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Provenance {
+    pub repository: String,
+    pub workflow: Option<String>,
+    pub git_ref: Option<String>,
+    pub issuer: Option<String>,
+    pub log_index: i64,
+    pub integrated_time: i64,
+}
+
+#[derive(Debug)]
+pub enum ProvenanceStatus {
+    Verified(Provenance),
+    // upstream has no attestation for the artifact
+    Missing,
+    Invalid(String),
+}
+
+// protobuf json encodes int64 as a string
+fn int64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Int64 {
+        Number(i64),
+        Text(String),
+    }
+    match Int64::deserialize(deserializer)? {
+        Int64::Number(n) => Ok(n),
+        Int64::Text(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RawBytes {
+    raw_bytes: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct LogId {
+    key_id: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct ValidFor {
+    start: Option<String>,
+    end: Option<String>,
+}
+
+impl ValidFor {
+    fn contains(&self, time: i64) -> bool {
+        let at = |s: &Option<String>| s.as_deref().and_then(|s| DateTime::parse_from_rfc3339(s).ok()).map(|t| t.timestamp());
+        at(&self.start).is_none_or(|start| start <= time) && at(&self.end).is_none_or(|end| time <= end)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PublicKeyMaterial {
+    raw_bytes: String,
+    #[serde(default)]
+    valid_for: ValidFor,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TransparencyLog {
+    public_key: PublicKeyMaterial,
+    log_id: LogId,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct CertChain {
+    certificates: Vec<RawBytes>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CertificateAuthority {
+    cert_chain: CertChain,
+    #[serde(default)]
+    valid_for: ValidFor,
+}
+
+// the parts of sigstore's trusted_root.json needed to verify keyless signatures offline
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedRoot {
+    #[serde(default)]
+    tlogs: Vec<TransparencyLog>,
+    #[serde(default)]
+    certificate_authorities: Vec<CertificateAuthority>,
+}
+
+impl TrustedRoot {
+    pub fn load(path: &Path) -> Result<TrustedRoot, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            format!("no sigstore trusted root pinned at {} ({}) - see 'Provenance' in the README", path.display(), e)
+        })?;
+        let root: TrustedRoot = serde_json::from_str(&content).map_err(|e| format!("invalid trusted root {}: {}", path.display(), e))?;
+        if root.tlogs.is_empty() || root.certificate_authorities.is_empty() {
+            return Err(format!("trusted root {} has no transparency logs or certificate authorities", path.display()).into());
+        }
+        Ok(root)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct InclusionPromise {
+    signed_entry_timestamp: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TlogEntry {
+    #[serde(deserialize_with = "int64")]
+    log_index: i64,
+    log_id: LogId,
+    #[serde(deserialize_with = "int64")]
+    integrated_time: i64,
+    inclusion_promise: Option<InclusionPromise>,
+    canonicalized_body: String,
+}
+
+// one dsse-signed in-toto statement with the certificate and log entry that vouch for it
+#[derive(Debug, Clone)]
+pub struct Attestation {
+    certificate: Vec<u8>,
+    payload_type: String,
+    payload: Vec<u8>,
+    signature: Vec<u8>,
+    entry: Option<TlogEntry>,
+}
+
+// pep 740 provenance objects from pypi's integrity api
+#[derive(Deserialize)]
+struct PyPiEnvelope {
+    statement: String,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct PyPiMaterial {
+    certificate: String,
+    #[serde(default)]
+    transparency_entries: Vec<TlogEntry>,
+}
+
+#[derive(Deserialize)]
+struct PyPiAttestation {
+    verification_material: PyPiMaterial,
+    envelope: PyPiEnvelope,
+}
+
+#[derive(Deserialize)]
+struct PyPiBundle {
+    attestations: Vec<PyPiAttestation>,
+}
+
+#[derive(Deserialize)]
+struct PyPiProvenance {
+    attestation_bundles: Vec<PyPiBundle>,
+}
+
+// sigstore bundles as served by npm's attestations endpoint
+#[derive(Deserialize)]
+struct DsseSignature {
+    sig: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DsseEnvelope {
+    payload: String,
+    payload_type: String,
+    signatures: Vec<DsseSignature>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleMaterial {
+    x509_certificate_chain: Option<CertChain>,
+    certificate: Option<RawBytes>,
+    #[serde(default)]
+    tlog_entries: Vec<TlogEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SigstoreBundle {
+    verification_material: BundleMaterial,
+    dsse_envelope: Option<DsseEnvelope>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NpmAttestation {
+    predicate_type: String,
+    bundle: SigstoreBundle,
+}
+
+#[derive(Deserialize)]
+struct NpmAttestations {
+    attestations: Vec<NpmAttestation>,
+}
+
+fn decode(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    BASE64.decode(value).map_err(|e| format!("{} is not valid base64: {}", field, e))
+}
+
+// every attestation upstream publishes for the artifact; empty when there are none
+pub async fn fetch_attestations(client: &reqwest::Client, name: &str, artifact: &Artifact, lang: &Language) -> Result<Vec<Attestation>, Box<dyn std::error::Error>> {
+    match lang {
+        Language::Python => {
+            let filename = artifact.url.rsplit('/').next().unwrap_or_default();
+            let url = format!("https://pypi.org/integrity/{}/{}/{}/provenance", name, artifact.version, filename);
+            let Some(provenance) = get_json::<PyPiProvenance>(client, &url).await? else {
+                return Ok(Vec::new());
+            };
+            let mut attestations = Vec::new();
+            for attestation in provenance.attestation_bundles.into_iter().flat_map(|b| b.attestations) {
+                attestations.push(Attestation {
+                    certificate: decode("certificate", &attestation.verification_material.certificate)?,
+                    payload_type: IN_TOTO_PAYLOAD_TYPE.to_string(),
+                    payload: decode("statement", &attestation.envelope.statement)?,
+                    signature: decode("signature", &attestation.envelope.signature)?,
+                    entry: attestation.verification_material.transparency_entries.into_iter().next(),
+                });
+            }
+            Ok(attestations)
+        }
+        Language::Node => {
+            let url = format!("https://registry.npmjs.org/-/npm/v1/attestations/{}@{}", name.replace('/', "%2f"), artifact.version);
+            let Some(resp) = get_json::<NpmAttestations>(client, &url).await? else {
+                return Ok(Vec::new());
+            };
+            let mut attestations = Vec::new();
+            // npm's own publish attestation is signed with a registry key, not a ci identity
+            for attestation in resp.attestations.into_iter().filter(|a| a.predicate_type.starts_with("https://slsa.dev/provenance/")) {
+                let material = attestation.bundle.verification_material;
+                let certificate = material
+                    .certificate
+                    .or_else(|| material.x509_certificate_chain.and_then(|chain| chain.certificates.into_iter().next()))
+                    .ok_or("provenance bundle has no signing certificate")?;
+                let envelope = attestation.bundle.dsse_envelope.ok_or("provenance bundle has no dsse envelope")?;
+                let signature = envelope.signatures.first().ok_or("provenance bundle is unsigned")?;
+                attestations.push(Attestation {
+                    certificate: decode("certificate", &certificate.raw_bytes)?,
+                    payload_type: envelope.payload_type,
+                    payload: decode("payload", &envelope.payload)?,
+                    signature: decode("signature", &signature.sig)?,
+                    entry: material.tlog_entries.into_iter().next(),
+                });
+            }
+            Ok(attestations)
+        }
+        _ => Err(format!("provenance checks are not supported for {} yet", lang).into()),
+    }
+}
+
+// dsse pre-authentication encoding, what the signature actually covers
+fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut message = format!("DSSEv1 {} {} {} ", payload_type.len(), payload_type, payload.len()).into_bytes();
+    message.extend_from_slice(payload);
+    message
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Clone, Copy)]
+enum Hash {
+    Sha256,
+    Sha384,
+}
+
+fn curve(spki: &SubjectPublicKeyInfo) -> Option<String> {
+    spki.algorithm.parameters.as_ref().and_then(|p| p.as_oid().ok()).map(|oid| oid.to_id_string())
+}
+
+fn verify_signature(spki: &SubjectPublicKeyInfo, hash: Option<Hash>, message: &[u8], signature: &[u8]) -> Result<(), String> {
+    let algorithm: &dyn VerificationAlgorithm = match (curve(spki).as_deref(), hash) {
+        (Some(OID_CURVE_P256), None | Some(Hash::Sha256)) => &signature::ECDSA_P256_SHA256_ASN1,
+        (Some(OID_CURVE_P256), Some(Hash::Sha384)) => &signature::ECDSA_P256_SHA384_ASN1,
+        (Some(OID_CURVE_P384), Some(Hash::Sha256)) => &signature::ECDSA_P384_SHA256_ASN1,
+        (Some(OID_CURVE_P384), None | Some(Hash::Sha384)) => &signature::ECDSA_P384_SHA384_ASN1,
+        (curve, _) => return Err(format!("unsupported key type {}", curve.unwrap_or("(not ecdsa)"))),
+    };
+    UnparsedPublicKey::new(algorithm, spki.subject_public_key.data.as_ref())
+        .verify(message, signature)
+        .map_err(|_| "signature does not verify".to_string())
+}
+
+// a certificate's signature checked against its issuer's key
+fn verify_issued_by(cert: &X509Certificate, issuer: &X509Certificate) -> Result<(), String> {
+    let hash = match cert.signature_algorithm.algorithm.to_id_string().as_str() {
+        OID_ECDSA_SHA256 => Hash::Sha256,
+        OID_ECDSA_SHA384 => Hash::Sha384,
+        other => return Err(format!("unsupported certificate signature algorithm {}", other)),
+    };
+    verify_signature(issuer.public_key(), Some(hash), cert.tbs_certificate.as_ref(), cert.signature_value.data.as_ref())
+}
+
+fn chains_to(leaf: &X509Certificate, authority: &CertificateAuthority, time: i64) -> Result<(), String> {
+    if !authority.valid_for.contains(time) {
+        return Err("certificate authority was not valid when the entry was logged".to_string());
+    }
+    let ders = authority
+        .cert_chain
+        .certificates
+        .iter()
+        .map(|c| decode("trusted root certificate", &c.raw_bytes))
+        .collect::<Result<Vec<_>, _>>()?;
+    let chain = ders
+        .iter()
+        .map(|der| X509Certificate::from_der(der).map(|(_, cert)| cert).map_err(|e| format!("invalid trusted root certificate: {}", e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut child = leaf;
+    for issuer in &chain {
+        verify_issued_by(child, issuer)?;
+        child = issuer;
+    }
+    Ok(())
+}
+
+// der utf8string extension values, or the raw strings of the legacy extensions
+fn extension_string(value: &[u8]) -> Option<String> {
+    let content = match value {
+        [0x0c, len, rest @ ..] if (*len as usize) < 0x80 => rest.get(..*len as usize)?,
+        [0x0c, 0x81, len, rest @ ..] => rest.get(..*len as usize)?,
+        [0x0c, 0x82, hi, lo, rest @ ..] => rest.get(..((*hi as usize) << 8 | *lo as usize))?,
+        raw => raw,
+    };
+    String::from_utf8(content.to_vec()).ok()
+}
+
+fn identity(cert: &X509Certificate) -> Option<Provenance> {
+    let extension = |oid: &str| {
+        cert.extensions()
+            .iter()
+            .find(|ext| ext.oid.to_id_string() == oid)
+            .and_then(|ext| extension_string(ext.value))
+    };
+    let repository = extension(OID_SOURCE_REPOSITORY_URI)
+        .or_else(|| extension(OID_LEGACY_REPOSITORY).map(|repo| format!("https://github.com/{}", repo)))?;
+    // the workflow identity lives in the san uri for every fulcio version
+    let san = cert.subject_alternative_name().ok().flatten().and_then(|san| {
+        san.value.general_names.iter().find_map(|name| match name {
+            GeneralName::URI(uri) => Some(uri.to_string()),
+            _ => None,
+        })
+    });
+    Some(Provenance {
+        repository,
+        workflow: extension(OID_BUILD_SIGNER_URI).or(san),
+        git_ref: extension(OID_SOURCE_REPOSITORY_REF).or_else(|| extension(OID_LEGACY_REF)),
+        issuer: extension(OID_ISSUER).or_else(|| extension(OID_LEGACY_ISSUER)),
+        log_index: 0,
+        integrated_time: 0,
+    })
+}
+
+// the log entry has to be for this very payload, or it vouches for nothing
+fn entry_payload_hash(body: &serde_json::Value) -> Option<&str> {
+    let spec = body.get("spec")?;
+    spec.pointer("/payloadHash/value")
+        .or_else(|| spec.pointer("/content/payloadHash/value"))
+        .and_then(serde_json::Value::as_str)
+}
+
+// the (certificate, signature) pairs the log entry was made with: dsse entries log the pem
+// certificate and signature directly, intoto entries log the whole envelope whose signature is
+// itself base64 text
+fn entry_signatures(body: &serde_json::Value) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let spec = body.get("spec");
+    let decoded = |value: Option<&serde_json::Value>| value.and_then(serde_json::Value::as_str).and_then(|v| BASE64.decode(v).ok());
+    let certificate = |value: Option<&serde_json::Value>| {
+        decoded(value).and_then(|pem| parse_x509_pem(&pem).ok().map(|(_, pem)| pem.contents))
+    };
+    let dsse = spec.and_then(|s| s.get("signatures")).and_then(serde_json::Value::as_array).into_iter().flatten().filter_map(|sig| {
+        Some((certificate(sig.get("verifier"))?, decoded(sig.get("signature"))?))
+    });
+    let intoto = spec
+        .and_then(|s| s.pointer("/content/envelope/signatures"))
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|sig| {
+            let text = decoded(sig.get("sig"))?;
+            Some((certificate(sig.get("publicKey"))?, BASE64.decode(text).ok()?))
+        });
+    dsse.chain(intoto).collect()
+}
+
+// the statement must name the artifact by a digest we can recompute
+fn subject_matches(statement: &serde_json::Value, artifact: &[u8]) -> bool {
+    let subjects = statement.get("subject").and_then(serde_json::Value::as_array).cloned().unwrap_or_default();
+    subjects.iter().any(|subject| {
+        let digest = |algorithm: &str| subject.pointer(&format!("/digest/{}", algorithm)).and_then(serde_json::Value::as_str).map(str::to_lowercase);
+        digest("sha256").is_some_and(|d| d == hex(&Sha256::digest(artifact)))
+            || digest("sha512").is_some_and(|d| d == hex(&Sha512::digest(artifact)))
+    })
+}
+
+pub fn verify_attestation(attestation: &Attestation, artifact: &[u8], root: &TrustedRoot) -> Result<Provenance, String> {
+    let (_, leaf) = X509Certificate::from_der(&attestation.certificate).map_err(|e| format!("invalid signing certificate: {}", e))?;
+    let entry = attestation.entry.as_ref().ok_or("attestation has no transparency log entry")?;
+    let time = entry.integrated_time;
+
+    // 1. the certificate was issued by a pinned fulcio and valid when the signature was logged
+    let chain_errors: Vec<String> = root.certificate_authorities.iter().filter_map(|ca| chains_to(&leaf, ca, time).err()).collect();
+    if chain_errors.len() == root.certificate_authorities.len() {
+        return Err(format!("signing certificate does not chain to a pinned root: {}", chain_errors.join("; ")));
+    }
+    if leaf.basic_constraints().ok().flatten().is_some_and(|bc| bc.value.ca) {
+        return Err("signing certificate is a certificate authority".to_string());
+    }
+    if !leaf.extended_key_usage().ok().flatten().is_some_and(|eku| eku.value.code_signing) {
+        return Err("signing certificate is not for code signing".to_string());
+    }
+    let validity = leaf.validity();
+    if time < validity.not_before.timestamp() || time > validity.not_after.timestamp() {
+        return Err("signing certificate had expired when the entry was logged".to_string());
+    }
+
+    // 2. a pinned transparency log promised to include the entry at that time
+    let log_id = decode("log id", &entry.log_id.key_id)?;
+    let log = root
+        .tlogs
+        .iter()
+        .find(|log| decode("log id", &log.log_id.key_id).is_ok_and(|id| id == log_id))
+        .ok_or("entry was logged by a transparency log that is not pinned")?;
+    if !log.public_key.valid_for.contains(time) {
+        return Err("transparency log key was not valid when the entry was logged".to_string());
+    }
+    let promise = entry.inclusion_promise.as_ref().ok_or("transparency log entry has no signed entry timestamp")?;
+    let signed = format!(
+        r#"{{"body":"{}","integratedTime":{},"logID":"{}","logIndex":{}}}"#,
+        entry.canonicalized_body,
+        time,
+        hex(&log_id),
+        entry.log_index
+    );
+    let log_key_der = decode("transparency log key", &log.public_key.raw_bytes)?;
+    let (_, log_key) = SubjectPublicKeyInfo::from_der(&log_key_der).map_err(|e| format!("invalid transparency log key: {}", e))?;
+    verify_signature(&log_key, None, signed.as_bytes(), &decode("signed entry timestamp", &promise.signed_entry_timestamp)?)
+        .map_err(|e| format!("signed entry timestamp: {}", e))?;
+
+    let body: serde_json::Value = serde_json::from_slice(&decode("log entry body", &entry.canonicalized_body)?)
+        .map_err(|e| format!("invalid log entry body: {}", e))?;
+    let payload_hash = hex(&Sha256::digest(&attestation.payload));
+    if entry_payload_hash(&body) != Some(payload_hash.as_str()) {
+        return Err("transparency log entry is for a different statement".to_string());
+    }
+    if !entry_signatures(&body).iter().any(|(cert, sig)| *cert == attestation.certificate && *sig == attestation.signature) {
+        return Err("transparency log entry was made with a different certificate or signature".to_string());
+    }
+
+    // 3. the certificate's key signed the statement, and the statement is about this artifact
+    verify_signature(leaf.public_key(), None, &pae(&attestation.payload_type, &attestation.payload), &attestation.signature)
+        .map_err(|e| format!("statement: {}", e))?;
+    let statement: serde_json::Value = serde_json::from_slice(&attestation.payload).map_err(|e| format!("invalid in-toto statement: {}", e))?;
+    if !subject_matches(&statement, artifact) {
+        return Err("attestation is for a different artifact".to_string());
+    }
+
+    let provenance = identity(&leaf).ok_or("signing certificate does not name a source repository")?;
+    Ok(Provenance {
+        log_index: entry.log_index,
+        integrated_time: time,
+        ..provenance
+    })
+}
+
+pub async fn check_provenance(
+    client: &reqwest::Client,
+    name: &str,
+    artifact: &Artifact,
+    lang: &Language,
+    root: &TrustedRoot,
+) -> Result<ProvenanceStatus, Box<dyn std::error::Error>> {
+    let attestations = fetch_attestations(client, name, artifact, lang).await?;
+    if attestations.is_empty() {
+        return Ok(ProvenanceStatus::Missing);
+    }
+    let mut errors = Vec::new();
+    for attestation in &attestations {
+        match verify_attestation(attestation, &artifact.bytes, root) {
+            Ok(provenance) => return Ok(ProvenanceStatus::Verified(provenance)),
+            Err(e) => errors.push(e),
+        }
+    }
+    Ok(ProvenanceStatus::Invalid(errors.join("; ")))
+}
+
+pub fn source_matches(provenance: &Provenance, source: &str) -> bool {
+    repository_key(&provenance.repository) == repository_key(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    #[test]
+    fn test_pae_signature_round_trip() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+
+        let payload = br#"{"_type":"https://in-toto.io/Statement/v1"}"#;
+        let message = pae(IN_TOTO_PAYLOAD_TYPE, payload);
+        assert!(message.starts_with(b"DSSEv1 28 application/vnd.in-toto+json 43 {"));
+
+        let sig = key.sign(&rng, &message).unwrap();
+        let public = UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, key.public_key().as_ref());
+        assert!(public.verify(&message, sig.as_ref()).is_ok());
+        assert!(public.verify(&pae(IN_TOTO_PAYLOAD_TYPE, b"{}"), sig.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_subject_and_entry_binding() {
+        let artifact = b"hello";
+        let statement = serde_json::json!({
+            "subject": [{"name": "x-1.0.tar.gz", "digest": {"sha256": "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"}}]
+        });
+        assert!(subject_matches(&statement, artifact));
+        assert!(!subject_matches(&statement, b"hello!"));
+
+        let dsse = serde_json::json!({"kind": "dsse", "spec": {"payloadHash": {"algorithm": "sha256", "value": "abc"}}});
+        let intoto = serde_json::json!({"kind": "intoto", "spec": {"content": {"payloadHash": {"algorithm": "sha256", "value": "def"}}}});
+        assert_eq!(entry_payload_hash(&dsse), Some("abc"));
+        assert_eq!(entry_payload_hash(&intoto), Some("def"));
+    }
+
+    #[test]
+    fn test_entry_signatures() {
+        let pem = "-----BEGIN CERTIFICATE-----\nAQID\n-----END CERTIFICATE-----\n";
+        let dsse = serde_json::json!({"kind": "dsse", "spec": {"signatures": [
+            {"signature": BASE64.encode([9, 9]), "verifier": BASE64.encode(pem)}
+        ]}});
+        assert_eq!(entry_signatures(&dsse), vec![(vec![1, 2, 3], vec![9, 9])]);
+
+        let intoto = serde_json::json!({"kind": "intoto", "spec": {"content": {"envelope": {"signatures": [
+            {"sig": BASE64.encode(BASE64.encode([7])), "publicKey": BASE64.encode(pem)}
+        ]}}}});
+        assert_eq!(entry_signatures(&intoto), vec![(vec![1, 2, 3], vec![7])]);
+        assert!(entry_signatures(&serde_json::json!({"spec": {}})).is_empty());
+    }
+
+    #[test]
+    fn test_extension_strings() {
+        assert_eq!(extension_string(&[0x0c, 0x03, b'a', b'b', b'c']).as_deref(), Some("abc"));
+        assert_eq!(extension_string(b"psf/requests").as_deref(), Some("psf/requests"));
+    }
+
+    #[test]
+    fn test_valid_for_window() {
+        let window = ValidFor { start: Some("2022-04-13T20:06:15.000Z".to_string()), end: None };
+        assert!(window.contains(1_700_000_000));
+        assert!(!window.contains(1_600_000_000));
+    }
+}
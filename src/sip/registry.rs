@@ -1,214 +1,864 @@
 use jsonschema::JSONSchema;
 use crate::cli::Language;
-use crate::sip::package::PackageRecord;
+use crate::sip::cache;
+use crate::sip::config::{Config, personal_registry_dir, project_registry_dir};
+use crate::sip::normalize::{find_duplicates, normalize_name};
+use crate::sip::package::{BlockedRecord, PackageRecord};
+use crate::sip::paths::cache_dir;
+use crate::sip::version::compare_versions;
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // embed registry data at compile time - makes binary portable
-const SCHEMA_JSON: &str = include_str!("../../registry/schema/sip-package.json");
+pub const SCHEMA_JSON: &str = include_str!("../../registry/schema/sip-package.json");
 const PYTHON_PACKAGES_JSON: &str = include_str!("../../registry/data/python/trusted-packages.json");
 const RUST_CRATES_JSON: &str = include_str!("../../registry/data/rust/trusted-crates.json");
 const GO_MODULES_JSON: &str = include_str!("../../registry/data/go/trusted-modules.json");
+const NODE_PACKAGES_JSON: &str = include_str!("../../registry/data/node/trusted-packages.json");
+const JAVA_ARTIFACTS_JSON: &str = include_str!("../../registry/data/java/trusted-artifacts.json");
+const RUBY_GEMS_JSON: &str = include_str!("../../registry/data/ruby/trusted-gems.json");
+const PHP_PACKAGES_JSON: &str = include_str!("../../registry/data/php/trusted-packages.json");
+pub const BLOCKED_SCHEMA_JSON: &str = include_str!("../../registry/schema/sip-blocked.json");
+
+// on-disk location and embedded copy of each language's registry
+pub fn registry_file(lang: &Language) -> (&'static str, &'static str) {
+    match lang {
+        Language::Python => ("registry/data/python/trusted-packages.json", PYTHON_PACKAGES_JSON),
+        Language::Rust => ("registry/data/rust/trusted-crates.json", RUST_CRATES_JSON),
+        Language::Go => ("registry/data/go/trusted-modules.json", GO_MODULES_JSON),
+        Language::Node => ("registry/data/node/trusted-packages.json", NODE_PACKAGES_JSON),
+        Language::Java => ("registry/data/java/trusted-artifacts.json", JAVA_ARTIFACTS_JSON),
+        Language::Ruby => ("registry/data/ruby/trusted-gems.json", RUBY_GEMS_JSON),
+        Language::Php => ("registry/data/php/trusted-packages.json", PHP_PACKAGES_JSON),
+    }
+}
+
+// on-disk location and embedded copy of each language's blocklist
+pub fn blocklist_file(lang: &Language) -> (&'static str, &'static str) {
+    match lang {
+        Language::Python => ("registry/data/python/blocked-packages.json", include_str!("../../registry/data/python/blocked-packages.json")),
+        Language::Rust => ("registry/data/rust/blocked-packages.json", include_str!("../../registry/data/rust/blocked-packages.json")),
+        Language::Go => ("registry/data/go/blocked-packages.json", include_str!("../../registry/data/go/blocked-packages.json")),
+        Language::Node => ("registry/data/node/blocked-packages.json", include_str!("../../registry/data/node/blocked-packages.json")),
+        Language::Java => ("registry/data/java/blocked-packages.json", include_str!("../../registry/data/java/blocked-packages.json")),
+        Language::Ruby => ("registry/data/ruby/blocked-packages.json", include_str!("../../registry/data/ruby/blocked-packages.json")),
+        Language::Php => ("registry/data/php/blocked-packages.json", include_str!("../../registry/data/php/blocked-packages.json")),
+    }
+}
+
+// all recorded versions of one package, oldest first
+#[derive(Debug, Default)]
+struct PackageVersions {
+    versions: Vec<PackageRecord>,
+}
+
+impl PackageVersions {
+    fn latest(&self) -> Option<&PackageRecord> {
+        self.versions.last()
+    }
+
+    fn get(&self, version: &str) -> Option<&PackageRecord> {
+        self.versions.iter().find(|pkg| pkg.version == version)
+    }
+
+    // replace the record for the same version, or insert keeping version order;
+    // the spelling already on record wins so one package never ends up under two names
+    fn upsert(&mut self, mut package: PackageRecord) {
+        if let Some(existing) = self.versions.first() {
+            package.name = existing.name.clone();
+        }
+        self.versions.retain(|pkg| pkg.version != package.version);
+        let pos = self
+            .versions
+            .iter()
+            .position(|pkg| compare_versions(&pkg.version, &package.version).is_gt())
+            .unwrap_or(self.versions.len());
+        self.versions.insert(pos, package);
+    }
+}
+
+// a compiled cache still mapped from disk, each package decoded on its first lookup
+#[derive(Debug)]
+struct MappedIndex {
+    cache: cache::CacheFile,
+    decoded: Vec<OnceCell<PackageVersions>>,
+}
+
+impl MappedIndex {
+    fn versions(&self, i: usize) -> Result<&PackageVersions, Box<dyn std::error::Error>> {
+        let cell = &self.decoded[i];
+        if cell.get().is_none() {
+            let records = self.cache.versions(i).ok_or("registry cache is corrupt - delete it to rebuild")?;
+            let mut versions = PackageVersions::default();
+            for record in records {
+                versions.upsert(record);
+            }
+            let _ = cell.set(versions);
+        }
+        Ok(cell.get().expect("entry was just decoded"))
+    }
+
+    fn name(&self, i: usize) -> Result<&str, Box<dyn std::error::Error>> {
+        Ok(self.cache.name(i).ok_or("registry cache is corrupt - delete it to rebuild")?)
+    }
+}
+
+// one language's registry, keyed by normalized package name
+#[derive(Debug, Default)]
+struct LanguageIndex {
+    packages: HashMap<String, PackageVersions>,
+    // set while the index is served straight from a compiled cache
+    mapped: Option<MappedIndex>,
+    dirty: bool,
+}
+
+impl LanguageIndex {
+    fn build(records: Vec<PackageRecord>, lang: &Language) -> Self {
+        let mut index = LanguageIndex::default();
+        for record in records {
+            index
+                .packages
+                .entry(normalize_name(&record.name, lang))
+                .or_default()
+                .upsert(record);
+        }
+        index
+    }
+
+    fn mapped(cache: cache::CacheFile) -> Self {
+        let decoded = (0..cache.package_count()).map(|_| OnceCell::new()).collect();
+        LanguageIndex {
+            mapped: Some(MappedIndex { cache, decoded }),
+            ..Default::default()
+        }
+    }
+
+    fn package_count(&self) -> usize {
+        match &self.mapped {
+            Some(mapped) => mapped.cache.package_count(),
+            None => self.packages.len(),
+        }
+    }
+
+    fn get(&self, key: &str) -> Result<Option<&PackageVersions>, Box<dyn std::error::Error>> {
+        match &self.mapped {
+            Some(mapped) => mapped.cache.position(key).map(|i| mapped.versions(i)).transpose(),
+            None => Ok(self.packages.get(key)),
+        }
+    }
+
+    // every package under its normalized name, in no particular order
+    fn entries(&self) -> Result<Vec<(&str, &PackageVersions)>, Box<dyn std::error::Error>> {
+        match &self.mapped {
+            Some(mapped) => (0..mapped.cache.package_count())
+                .map(|i| Ok((mapped.name(i)?, mapped.versions(i)?)))
+                .collect(),
+            None => Ok(self.packages.iter().map(|(key, versions)| (key.as_str(), versions)).collect()),
+        }
+    }
+
+    // decode the whole cache so the index can be changed
+    fn materialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(mapped) = &self.mapped else {
+            return Ok(());
+        };
+        let mut packages = HashMap::new();
+        for i in 0..mapped.cache.package_count() {
+            let versions = mapped.versions(i)?.versions.clone();
+            packages.insert(mapped.name(i)?.to_string(), PackageVersions { versions });
+        }
+        self.packages = packages;
+        self.mapped = None;
+        Ok(())
+    }
+
+    // records sorted by name then version, the order they are listed and saved in
+    fn records(&self) -> Result<Vec<&PackageRecord>, Box<dyn std::error::Error>> {
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|(key, _)| *key);
+        Ok(entries
+            .into_iter()
+            .flat_map(|(_, versions)| versions.versions.iter())
+            .collect())
+    }
+}
+
+// one language's blocked packages, keyed by normalized package name
+#[derive(Debug, Default)]
+struct Blocklist {
+    entries: HashMap<String, BlockedRecord>,
+    dirty: bool,
+}
+
+impl Blocklist {
+    fn records(&self) -> Vec<&BlockedRecord> {
+        let mut records: Vec<&BlockedRecord> = self.entries.values().collect();
+        records.sort_by(|a, b| a.name.cmp(&b.name));
+        records
+    }
+}
+
+// built-in layer names, lowest to highest default precedence
+pub const COMMUNITY_LAYER: &str = "community";
+pub const PROJECT_LAYER: &str = "project";
+pub const PERSONAL_LAYER: &str = "personal";
+
+// where a layer's json comes from
+#[derive(Debug, Clone)]
+enum LayerSource {
+    // the registry compiled into the binary, or registry/data in the current directory when preferred
+    Community { prefer_files: bool },
+    // a directory laid out like registry/data (<lang>/trusted-*.json), missing files are empty
+    Directory(PathBuf),
+}
+
+#[derive(Debug)]
+struct RegistryLayer {
+    name: String,
+    priority: i32,
+    source: LayerSource,
+    // per-language indexes, each parsed and validated on first access
+    indexes: HashMap<Language, OnceCell<LanguageIndex>>,
+    blocklists: HashMap<Language, OnceCell<Blocklist>>,
+}
+
+impl RegistryLayer {
+    fn new(name: &str, priority: i32, source: LayerSource) -> Self {
+        RegistryLayer {
+            name: name.to_string(),
+            priority,
+            source,
+            indexes: Language::ALL.iter().map(|lang| (lang.clone(), OnceCell::new())).collect(),
+            blocklists: Language::ALL.iter().map(|lang| (lang.clone(), OnceCell::new())).collect(),
+        }
+    }
+
+    fn file_path(&self, lang: &Language) -> PathBuf {
+        self.resolve(registry_file(lang).0)
+    }
+
+    fn blocklist_path(&self, lang: &Language) -> PathBuf {
+        self.resolve(blocklist_file(lang).0)
+    }
+
+    fn resolve(&self, file_path: &str) -> PathBuf {
+        match &self.source {
+            LayerSource::Community { .. } => PathBuf::from(file_path),
+            LayerSource::Directory(dir) => in_data_dir(dir, file_path),
+        }
+    }
+}
+
+// a registry/data path relocated into another directory with the same layout
+fn in_data_dir(dir: &Path, file_path: &str) -> PathBuf {
+    dir.join(file_path.trim_start_matches("registry/data/"))
+}
+
+// where a language's trusted records live in a directory laid out like registry/data
+pub fn data_file(dir: &Path, lang: &Language) -> PathBuf {
+    in_data_dir(dir, registry_file(lang).0)
+}
+
+pub fn blocked_file(dir: &Path, lang: &Language) -> PathBuf {
+    in_data_dir(dir, blocklist_file(lang).0)
+}
+
+// a record together with the layer that supplied it
+#[derive(Debug)]
+pub struct Resolved<'a, T = PackageRecord> {
+    pub record: &'a T,
+    pub layer: &'a str,
+}
+
+impl<T> Clone for Resolved<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Resolved<'_, T> {}
 
 #[derive(Debug)]
 pub struct Registry {
-    pub python_packages: Vec<PackageRecord>,
-    pub rust_crates: Vec<PackageRecord>,
-    pub go_modules: Vec<PackageRecord>,
-    schema: JSONSchema,
+    // ordered by ascending priority, the last layer holding a package wins
+    layers: Vec<RegistryLayer>,
+    schema: OnceCell<JSONSchema>,
+    blocked_schema: OnceCell<JSONSchema>,
+    // where compiled caches live, None disables caching
+    cache_dir: Option<PathBuf>,
 }
 
 impl Registry {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let schema = Self::load_embedded_schema()?;
-        
-        let python_packages = Self::load_embedded_packages(PYTHON_PACKAGES_JSON, &schema)?;
-        let rust_crates = Self::load_embedded_packages(RUST_CRATES_JSON, &schema)?;
-        let go_modules = Self::load_embedded_packages(GO_MODULES_JSON, &schema)?;
-        
-        println!("loaded {} python packages", python_packages.len());
-        println!("loaded {} rust crates", rust_crates.len());
-        println!("loaded {} go modules", go_modules.len());
-        
-        Ok(Registry {
-            python_packages,
-            rust_crates,
-            go_modules,
-            schema,
-        })
-    }
-    
+        Self::layered(false)
+    }
+
     // load registry with ability to modify and save back to disk
     pub fn load_mutable() -> Result<Self, Box<dyn std::error::Error>> {
-        let schema = Self::load_embedded_schema()?;
-        
-        // try to load from local files first, fall back to embedded
-        let python_packages = Self::load_from_file_or_embedded(
-            "registry/data/python/trusted-packages.json",
-            PYTHON_PACKAGES_JSON,
-            &schema
-        )?;
-        
-        let rust_crates = Self::load_from_file_or_embedded(
-            "registry/data/rust/trusted-crates.json", 
-            RUST_CRATES_JSON,
-            &schema
-        )?;
-        
-        let go_modules = Self::load_from_file_or_embedded(
-            "registry/data/go/trusted-modules.json",
-            GO_MODULES_JSON, 
-            &schema
-        )?;
-        
-        println!("loaded {} python packages", python_packages.len());
-        println!("loaded {} rust crates", rust_crates.len());
-        println!("loaded {} go modules", go_modules.len());
-        
-        Ok(Registry {
-            python_packages,
-            rust_crates,
-            go_modules,
-            schema,
-        })
-    }
-    
-    // kept for backwards compatibility but now just calls new()
-    pub fn load_from_directory(_registry_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        Self::new()
-    }
-    
-    fn load_embedded_schema() -> Result<JSONSchema, Box<dyn std::error::Error>> {
-        let schema_json: serde_json::Value = serde_json::from_str(SCHEMA_JSON)?;
+        Self::layered(true)
+    }
+
+    // community registry, then configured organization layers, then the project and personal overlays
+    fn layered(prefer_files: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let config = Config::load()?;
+
+        let mut layers = vec![RegistryLayer::new(COMMUNITY_LAYER, 0, LayerSource::Community { prefer_files })];
+        for (i, layer) in config.registry.layers.iter().enumerate() {
+            if [COMMUNITY_LAYER, PROJECT_LAYER, PERSONAL_LAYER].contains(&layer.name.as_str()) {
+                return Err(format!("registry layer name '{}' is reserved", layer.name).into());
+            }
+            let priority = layer.priority.unwrap_or(100 + i as i32);
+            layers.push(RegistryLayer::new(&layer.name, priority, LayerSource::Directory(PathBuf::from(&layer.path))));
+        }
+        if let Some(dir) = project_registry_dir() {
+            layers.push(RegistryLayer::new(PROJECT_LAYER, 200, LayerSource::Directory(dir)));
+        }
+        layers.push(RegistryLayer::new(PERSONAL_LAYER, 300, LayerSource::Directory(personal_registry_dir())));
+
+        Ok(Self::from_layers(layers, Some(cache_dir())))
+    }
+
+    fn from_layers(mut layers: Vec<RegistryLayer>, cache_dir: Option<PathBuf>) -> Self {
+        layers.sort_by_key(|layer| layer.priority);
+        Registry {
+            layers,
+            schema: OnceCell::new(),
+            blocked_schema: OnceCell::new(),
+            cache_dir,
+        }
+    }
+
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.layers.iter().map(|layer| layer.name.as_str()).collect()
+    }
+
+    fn layer_position(&self, name: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        self.layers
+            .iter()
+            .position(|layer| layer.name == name)
+            .ok_or_else(|| format!("unknown registry layer '{}' (available: {})", name, self.layer_names().join(", ")).into())
+    }
+
+    fn schema(&self) -> Result<&JSONSchema, Box<dyn std::error::Error>> {
+        Self::compiled(&self.schema, SCHEMA_JSON)
+    }
+
+    fn compiled<'a>(cell: &'a OnceCell<JSONSchema>, source: &str) -> Result<&'a JSONSchema, Box<dyn std::error::Error>> {
+        if let Some(schema) = cell.get() {
+            return Ok(schema);
+        }
+        let schema_json: serde_json::Value = serde_json::from_str(source)?;
         let compiled = JSONSchema::compile(&schema_json)
             .map_err(|e| format!("failed to compile embedded json schema: {}", e))?;
-        Ok(compiled)
+        Ok(cell.get_or_init(|| compiled))
+    }
+
+    fn index<'a>(&'a self, layer: &'a RegistryLayer, lang: &Language) -> Result<&'a LanguageIndex, Box<dyn std::error::Error>> {
+        let cell = &layer.indexes[lang];
+        if let Some(index) = cell.get() {
+            return Ok(index);
+        }
+
+        let index = self.load_index(layer, lang)?;
+        if index.package_count() > 0 {
+            eprintln!("loaded {} {} packages from {} registry", index.package_count(), lang, layer.name);
+        }
+        Ok(cell.get_or_init(|| index))
+    }
+
+    fn index_mut(&mut self, layer: &str, lang: &Language) -> Result<&mut LanguageIndex, Box<dyn std::error::Error>> {
+        let pos = self.layer_position(layer)?;
+        self.index(&self.layers[pos], lang)?;
+        let index = self.layers[pos]
+            .indexes
+            .get_mut(lang)
+            .and_then(|cell| cell.get_mut())
+            .expect("index was just loaded");
+        index.materialize()?;
+        Ok(index)
+    }
+
+    fn load_index(&self, layer: &RegistryLayer, lang: &Language) -> Result<LanguageIndex, Box<dyn std::error::Error>> {
+        let (_, embedded_data) = registry_file(lang);
+        let file_path = layer.file_path(lang);
+        match &layer.source {
+            LayerSource::Community { prefer_files: true } if file_path.exists() => {
+                println!("loading from file: {}", file_path.display());
+                let content = fs::read_to_string(&file_path)?;
+                self.load_cached(&content, lang, &file_path.to_string_lossy(), "local")
+            }
+            LayerSource::Community { prefer_files } => {
+                if *prefer_files {
+                    println!("file {} not found, using embedded data", file_path.display());
+                }
+                self.load_cached(embedded_data, lang, "embedded", "embedded")
+            }
+            LayerSource::Directory(_) if file_path.exists() => {
+                let content = fs::read_to_string(&file_path)?;
+                self.load_cached(&content, lang, &file_path.to_string_lossy(), &format!("layer-{}", layer.name))
+            }
+            LayerSource::Directory(_) => Ok(LanguageIndex::default()),
+        }
+    }
+
+    // the schema is part of the hash so a stricter schema invalidates caches validated by an older one
+    fn source_hash(json_str: &str) -> [u8; 32] {
+        cache::content_hash(&[SCHEMA_JSON, json_str].concat())
     }
-    
-    fn load_embedded_packages(json_str: &str, schema: &JSONSchema) -> Result<Vec<PackageRecord>, Box<dyn std::error::Error>> {
+
+    // hashing the json is far cheaper than parsing and validating it, so a cache built
+    // from identical json is trusted as is and mapped rather than decoded; anything else
+    // goes through full validation
+    fn load_cached(&self, json_str: &str, lang: &Language, origin: &str, cache_name: &str) -> Result<LanguageIndex, Box<dyn std::error::Error>> {
+        let Some(dir) = &self.cache_dir else {
+            return Ok(LanguageIndex::build(self.parse_records(json_str, lang, origin)?, lang));
+        };
+
+        let source_hash = Self::source_hash(json_str);
+        let path = cache::cache_path(dir, lang, cache_name);
+        if let Some(cache) = cache::load(&path, &source_hash) {
+            return Ok(LanguageIndex::mapped(cache));
+        }
+
+        let records = self.parse_records(json_str, lang, origin)?;
+        // a read-only home directory only costs us the speedup, not the command
+        let _ = cache::store(&path, &records, lang, &source_hash);
+        Ok(LanguageIndex::build(records, lang))
+    }
+
+    fn parse_records(&self, json_str: &str, lang: &Language, origin: &str) -> Result<Vec<PackageRecord>, Box<dyn std::error::Error>> {
         let packages_json: serde_json::Value = serde_json::from_str(json_str)?;
-        
+
         // validate against schema
-        if let Err(errors) = schema.validate(&packages_json) {
-            let error_msgs: Vec<String> = errors.map(|e| e.to_string()).collect();
-            return Err(format!("embedded registry schema validation failed: {}", error_msgs.join(", ")).into());
+        if let Err(errors) = self.schema()?.validate(&packages_json) {
+            let error_msgs: Vec<String> = errors.map(|e| format!("{}: {}", e.instance_path, e)).collect();
+            return Err(format!("{} registry schema validation failed: {}", origin, error_msgs.join(", ")).into());
         }
-        
-        let packages: Vec<PackageRecord> = serde_json::from_str(json_str)?;
+
+        let packages: Vec<PackageRecord> = serde_json::from_value(packages_json)?;
+        Self::check_duplicates(&packages, lang, origin)?;
         Ok(packages)
     }
-    
-    fn load_from_file_or_embedded(
-        file_path: &str,
-        embedded_data: &str,
-        schema: &JSONSchema
-    ) -> Result<Vec<PackageRecord>, Box<dyn std::error::Error>> {
-        if Path::new(file_path).exists() {
-            println!("loading from file: {}", file_path);
-            let content = fs::read_to_string(file_path)?;
-            let packages_json: serde_json::Value = serde_json::from_str(&content)?;
-            
-            // validate against schema
-            if let Err(errors) = schema.validate(&packages_json) {
-                let error_msgs: Vec<String> = errors.map(|e| e.to_string()).collect();
-                return Err(format!("file registry schema validation failed: {}", error_msgs.join(", ")).into());
-            }
-            
-            let packages: Vec<PackageRecord> = serde_json::from_str(&content)?;
-            Ok(packages)
+
+    // upstream metadata does not always fit the schema (rubygems `1.0.pre`, packagist `2.1`),
+    // a record that would fail the next load must not be written in the first place
+    pub fn validate_record(&self, record: &PackageRecord) -> Result<(), Box<dyn std::error::Error>> {
+        let record_json = serde_json::to_value([record])?;
+        if let Err(errors) = self.schema()?.validate(&record_json) {
+            let error_msgs: Vec<String> = errors.map(|e| format!("{}: {}", e.instance_path, e)).collect();
+            return Err(format!("{} {} does not match the registry schema: {}", record.name, record.version, error_msgs.join(", ")).into());
+        }
+        Ok(())
+    }
+
+    // registry files must not list the same package under two spellings
+    fn check_duplicates(packages: &[PackageRecord], lang: &Language, origin: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let duplicates = find_duplicates(packages, lang);
+        if duplicates.is_empty() {
+            Ok(())
         } else {
-            println!("file {} not found, using embedded data", file_path);
-            Self::load_embedded_packages(embedded_data, schema)
+            Err(format!("{} {} registry has duplicate packages: {}", origin, lang, duplicates.join(", ")).into())
         }
     }
-    
-    pub fn lookup_package(&self, name: &str, lang: &Language) -> Option<&PackageRecord> {
-        match lang {
-            Language::Python => {
-                self.python_packages.iter().find(|pkg| pkg.name == name)
-            }
-            Language::Rust => {
-                self.rust_crates.iter().find(|pkg| pkg.name == name)
-            }
-            Language::Go => {
-                self.go_modules.iter().find(|pkg| pkg.name == name)
+
+    // versions of a package from the highest-precedence layer that records it
+    fn effective_versions(&self, name: &str, lang: &Language) -> Result<Option<(&PackageVersions, &str)>, Box<dyn std::error::Error>> {
+        let key = normalize_name(name, lang);
+        for layer in self.layers.iter().rev() {
+            if let Some(versions) = self.index(layer, lang)?.get(&key)? {
+                return Ok(Some((versions, layer.name.as_str())));
             }
         }
+        Ok(None)
+    }
+
+    // latest recorded version of a package in the effective layer
+    pub fn lookup_package(&self, name: &str, lang: &Language) -> Result<Option<Resolved<'_>>, Box<dyn std::error::Error>> {
+        Ok(self
+            .effective_versions(name, lang)?
+            .and_then(|(versions, layer)| versions.latest().map(|record| Resolved { record, layer })))
+    }
+
+    // the record for one exact version, if that version was reviewed in the effective layer
+    pub fn lookup_version(&self, name: &str, version: &str, lang: &Language) -> Result<Option<Resolved<'_>>, Box<dyn std::error::Error>> {
+        Ok(self
+            .effective_versions(name, lang)?
+            .and_then(|(versions, layer)| versions.get(version).map(|record| Resolved { record, layer })))
     }
-    
-    pub fn add_package(&mut self, package: PackageRecord, lang: &Language) {
-        // remove existing entry if present
-        self.remove_package(&package.name, lang);
-        
-        // add new entry
-        match lang {
-            Language::Python => self.python_packages.push(package),
-            Language::Rust => self.rust_crates.push(package),
-            Language::Go => self.go_modules.push(package),
-        }
-    }
-    
-    pub fn remove_package(&mut self, name: &str, lang: &Language) -> bool {
-        match lang {
-            Language::Python => {
-                if let Some(pos) = self.python_packages.iter().position(|pkg| pkg.name == name) {
-                    self.python_packages.remove(pos);
-                    true
-                } else {
-                    false
+
+    pub fn add_package(&mut self, package: PackageRecord, lang: &Language, layer: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.validate_record(&package)?;
+        let index = self.index_mut(layer, lang)?;
+        index
+            .packages
+            .entry(normalize_name(&package.name, lang))
+            .or_default()
+            .upsert(package);
+        index.dirty = true;
+        Ok(())
+    }
+
+    // drop every version recorded under any spelling of the name from one layer
+    pub fn remove_package(&mut self, name: &str, lang: &Language, layer: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let index = self.index_mut(layer, lang)?;
+        let removed = index.packages.remove(&normalize_name(name, lang)).is_some();
+        index.dirty |= removed;
+        Ok(removed)
+    }
+
+    // only layers and languages that were changed are written back
+    pub fn save_to_disk(&self) -> Result<(), Box<dyn std::error::Error>> {
+        for layer in &self.layers {
+            for (lang, cell) in &layer.indexes {
+                let Some(index) = cell.get().filter(|index| index.dirty) else {
+                    continue;
+                };
+
+                let file_path = layer.file_path(lang);
+                if let Some(parent) = file_path.parent() {
+                    fs::create_dir_all(parent)?;
                 }
-            }
-            Language::Rust => {
-                if let Some(pos) = self.rust_crates.iter().position(|pkg| pkg.name == name) {
-                    self.rust_crates.remove(pos);
-                    true
-                } else {
-                    false
+
+                // the json and the cache are both trusted on the next load; records were checked
+                // against the schema as they were added, clashing spellings are checked here
+                let records: Vec<PackageRecord> = index.records()?.into_iter().cloned().collect();
+                Self::check_duplicates(&records, lang, &file_path.to_string_lossy())?;
+                let json = serde_json::to_string_pretty(&records)?;
+                fs::write(&file_path, &json)?;
+                println!("saved {} {} packages to {}", records.len(), lang, file_path.display());
+
+                // refresh the compiled cache so the next command skips validation
+                if let Some(dir) = &self.cache_dir {
+                    let cache_name = match layer.source {
+                        LayerSource::Community { .. } => "local".to_string(),
+                        LayerSource::Directory(_) => format!("layer-{}", layer.name),
+                    };
+                    let _ = cache::store(&cache::cache_path(dir, lang, &cache_name), &records, lang, &Self::source_hash(&json));
                 }
             }
-            Language::Go => {
-                if let Some(pos) = self.go_modules.iter().position(|pkg| pkg.name == name) {
-                    self.go_modules.remove(pos);
-                    true
-                } else {
-                    false
+        }
+
+        for layer in &self.layers {
+            for (lang, cell) in &layer.blocklists {
+                let Some(blocklist) = cell.get().filter(|blocklist| blocklist.dirty) else {
+                    continue;
+                };
+
+                let file_path = layer.blocklist_path(lang);
+                if let Some(parent) = file_path.parent() {
+                    fs::create_dir_all(parent)?;
                 }
+                let records = blocklist.records();
+                fs::write(&file_path, serde_json::to_string_pretty(&records)?)?;
+                println!("saved {} blocked {} packages to {}", records.len(), lang, file_path.display());
             }
         }
-    }
-    
-    pub fn save_to_disk(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // ensure registry directory exists
-        fs::create_dir_all("registry/data/python")?;
-        fs::create_dir_all("registry/data/rust")?;
-        fs::create_dir_all("registry/data/go")?;
-        
-        // save each language registry
-        let python_json = serde_json::to_string_pretty(&self.python_packages)?;
-        fs::write("registry/data/python/trusted-packages.json", python_json)?;
-        
-        let rust_json = serde_json::to_string_pretty(&self.rust_crates)?;
-        fs::write("registry/data/rust/trusted-crates.json", rust_json)?;
-        
-        let go_json = serde_json::to_string_pretty(&self.go_modules)?;
-        fs::write("registry/data/go/trusted-modules.json", go_json)?;
-        
+
         println!("registry saved to disk");
         Ok(())
     }
-    
-    pub fn list_packages(&self, lang: Option<&Language>) -> Vec<&PackageRecord> {
-        match lang {
-            Some(Language::Python) => self.python_packages.iter().collect(),
-            Some(Language::Rust) => self.rust_crates.iter().collect(),
-            Some(Language::Go) => self.go_modules.iter().collect(),
-            None => {
-                let mut all_packages = Vec::new();
-                all_packages.extend(self.python_packages.iter());
-                all_packages.extend(self.rust_crates.iter());
-                all_packages.extend(self.go_modules.iter());
-                all_packages
+
+    fn blocklist<'a>(&'a self, layer: &'a RegistryLayer, lang: &Language) -> Result<&'a Blocklist, Box<dyn std::error::Error>> {
+        let cell = &layer.blocklists[lang];
+        if let Some(blocklist) = cell.get() {
+            return Ok(blocklist);
+        }
+
+        let file_path = layer.blocklist_path(lang);
+        let json = match &layer.source {
+            LayerSource::Community { prefer_files: true } | LayerSource::Directory(_) if file_path.exists() => {
+                fs::read_to_string(&file_path)?
+            }
+            LayerSource::Community { .. } => blocklist_file(lang).1.to_string(),
+            LayerSource::Directory(_) => "[]".to_string(),
+        };
+
+        let blocked_json: serde_json::Value = serde_json::from_str(&json)?;
+        if let Err(errors) = Self::compiled(&self.blocked_schema, BLOCKED_SCHEMA_JSON)?.validate(&blocked_json) {
+            let error_msgs: Vec<String> = errors.map(|e| format!("{}: {}", e.instance_path, e)).collect();
+            return Err(format!("{} blocklist schema validation failed: {}", file_path.display(), error_msgs.join(", ")).into());
+        }
+
+        let mut blocklist = Blocklist::default();
+        for record in serde_json::from_value::<Vec<BlockedRecord>>(blocked_json)? {
+            let key = normalize_name(&record.name, lang);
+            if let Some(existing) = blocklist.entries.insert(key, record) {
+                return Err(format!("{} {} blocklist lists '{}' more than once", file_path.display(), lang, existing.name).into());
+            }
+        }
+        Ok(cell.get_or_init(|| blocklist))
+    }
+
+    fn blocklist_mut(&mut self, layer: &str, lang: &Language) -> Result<&mut Blocklist, Box<dyn std::error::Error>> {
+        let pos = self.layer_position(layer)?;
+        self.blocklist(&self.layers[pos], lang)?;
+        Ok(self.layers[pos]
+            .blocklists
+            .get_mut(lang)
+            .and_then(|cell| cell.get_mut())
+            .expect("blocklist was just loaded"))
+    }
+
+    // blocks are not overridden by higher layers: a ban recorded anywhere applies
+    pub fn lookup_block(&self, name: &str, version: Option<&str>, lang: &Language) -> Result<Option<Resolved<'_, BlockedRecord>>, Box<dyn std::error::Error>> {
+        let key = normalize_name(name, lang);
+        for layer in self.layers.iter().rev() {
+            if let Some(record) = self.blocklist(layer, lang)?.entries.get(&key)
+                && record.affects(version)
+            {
+                return Ok(Some(Resolved { record, layer: layer.name.as_str() }));
+            }
+        }
+        Ok(None)
+    }
+
+    // recording a block again replaces the previous reason and versions
+    pub fn block_package(&mut self, record: BlockedRecord, lang: &Language, layer: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let blocklist = self.blocklist_mut(layer, lang)?;
+        blocklist.entries.insert(normalize_name(&record.name, lang), record);
+        blocklist.dirty = true;
+        Ok(())
+    }
+
+    pub fn unblock_package(&mut self, name: &str, lang: &Language, layer: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let blocklist = self.blocklist_mut(layer, lang)?;
+        let removed = blocklist.entries.remove(&normalize_name(name, lang)).is_some();
+        blocklist.dirty |= removed;
+        Ok(removed)
+    }
+
+    // every block from every layer (or just one layer), sorted by name
+    pub fn list_blocked(&self, lang: Option<&Language>, layer: Option<&str>) -> Result<Vec<Resolved<'_, BlockedRecord>>, Box<dyn std::error::Error>> {
+        let languages: Vec<&Language> = match lang {
+            Some(lang) => vec![lang],
+            None => Language::ALL.iter().collect(),
+        };
+        let layers: Vec<&RegistryLayer> = match layer {
+            Some(name) => vec![&self.layers[self.layer_position(name)?]],
+            None => self.layers.iter().rev().collect(),
+        };
+
+        let mut blocked = Vec::new();
+        for lang in languages {
+            for layer in &layers {
+                blocked.extend(
+                    self.blocklist(layer, lang)?
+                        .records()
+                        .into_iter()
+                        .map(|record| Resolved { record, layer: layer.name.as_str() }),
+                );
             }
         }
+        Ok(blocked)
+    }
+
+    // one layer's own records, or the merged view where each package comes from its effective layer
+    pub fn list_packages(&self, lang: Option<&Language>, layer: Option<&str>) -> Result<Vec<Resolved<'_>>, Box<dyn std::error::Error>> {
+        let languages: Vec<&Language> = match lang {
+            Some(lang) => vec![lang],
+            None => Language::ALL.iter().collect(),
+        };
+
+        let mut all_packages = Vec::new();
+        for lang in languages {
+            if let Some(name) = layer {
+                let layer = &self.layers[self.layer_position(name)?];
+                all_packages.extend(
+                    self.index(layer, lang)?
+                        .records()?
+                        .into_iter()
+                        .map(|record| Resolved { record, layer: layer.name.as_str() }),
+                );
+                continue;
+            }
+
+            // keys are unique within a layer, so the first layer to claim a key owns all its versions
+            let mut seen: HashSet<&str> = HashSet::new();
+            let mut merged: Vec<(&str, Resolved<'_>)> = Vec::new();
+            for layer in self.layers.iter().rev() {
+                for (key, versions) in self.index(layer, lang)?.entries()? {
+                    if !seen.insert(key) {
+                        continue;
+                    }
+                    merged.extend(
+                        versions
+                            .versions
+                            .iter()
+                            .map(|record| (key, Resolved { record, layer: layer.name.as_str() })),
+                    );
+                }
+            }
+            // stable sort keeps each package's versions in order
+            merged.sort_by(|a, b| a.0.cmp(b.0));
+            all_packages.extend(merged.into_iter().map(|(_, resolved)| resolved));
+        }
+        Ok(all_packages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sip::testing::record;
+
+    fn community_only() -> Registry {
+        Registry::from_layers(vec![RegistryLayer::new(COMMUNITY_LAYER, 0, LayerSource::Community { prefer_files: false })], None)
+    }
+
+    #[test]
+    fn test_lookup_uses_latest_version_and_normalized_name() {
+        let registry = community_only();
+        let requests = registry.lookup_package("Requests", &Language::Python).unwrap().unwrap();
+        assert_eq!(requests.record.version, "2.32.4");
+        assert_eq!(requests.layer, COMMUNITY_LAYER);
+        assert!(registry.lookup_version("requests", "2.31.0", &Language::Python).unwrap().is_some());
+        assert!(registry.lookup_version("requests", "0.0.1", &Language::Python).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_validate_record_rejects_upstream_versions_outside_the_schema() {
+        let registry = community_only();
+        assert!(registry.validate_record(&record("monolog/monolog", "3.5.0")).is_ok());
+        assert!(registry.validate_record(&record("laravel/framework", "2.1")).is_err());
+        assert!(registry.validate_record(&record("rails", "7.1.pre")).is_err());
+    }
+
+    #[test]
+    fn test_languages_load_lazily() {
+        let registry = community_only();
+        registry.lookup_package("requests", &Language::Python).unwrap();
+        assert!(registry.layers[0].indexes[&Language::Python].get().is_some());
+        assert!(registry.layers[0].indexes[&Language::Rust].get().is_none());
+    }
+
+    #[test]
+    fn test_large_registry_file_parses_quickly() {
+        // a 20k-entry file is the size the index is built for; the duplicate check must stay linear
+        let records: Vec<PackageRecord> = (0..20_000)
+            .map(|i| PackageRecord { hash: format!("sha256:{:064x}", i), ..record(&format!("package-{}", i), "1.0.0") })
+            .collect();
+        let json = serde_json::to_string(&records).unwrap();
+        let started = std::time::Instant::now();
+        let parsed = community_only().parse_records(&json, &Language::Python, "generated").unwrap();
+        assert_eq!(parsed.len(), 20_000);
+        assert!(started.elapsed() < std::time::Duration::from_secs(10), "parsing took {:?}", started.elapsed());
+
+        let mut clashing = records;
+        clashing.push(record("Package_7", "2.0.0"));
+        let json = serde_json::to_string(&clashing).unwrap();
+        let error = community_only().parse_records(&json, &Language::Python, "generated").unwrap_err().to_string();
+        assert!(error.contains("'package-7' and 'Package_7' are the same package"));
+    }
+
+    #[test]
+    fn test_add_and_remove_package() {
+        let mut registry = community_only();
+        registry.add_package(record("left_pad", "1.3.0"), &Language::Rust, COMMUNITY_LAYER).unwrap();
+        registry.add_package(record("left-pad", "1.10.0"), &Language::Rust, COMMUNITY_LAYER).unwrap();
+        assert_eq!(registry.lookup_package("left-pad", &Language::Rust).unwrap().unwrap().record.version, "1.10.0");
+
+        assert!(registry.remove_package("LEFT-PAD", &Language::Rust, COMMUNITY_LAYER).unwrap());
+        assert!(registry.lookup_package("left_pad", &Language::Rust).unwrap().is_none());
+        assert!(!registry.remove_package("left_pad", &Language::Rust, COMMUNITY_LAYER).unwrap());
+        assert!(registry.add_package(record("serde", "1.0.0"), &Language::Rust, "acme").is_err());
+        assert!(registry.add_package(record("left-pad", "1.3"), &Language::Node, COMMUNITY_LAYER).is_err());
+    }
+
+    #[test]
+    fn test_cached_index_is_looked_up_without_decoding() {
+        let dir = std::env::temp_dir().join(format!("sip-mapped-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let layers = || vec![RegistryLayer::new(COMMUNITY_LAYER, 0, LayerSource::Community { prefer_files: false })];
+
+        // the first load compiles the cache, the second maps it
+        Registry::from_layers(layers(), Some(dir.clone())).lookup_package("requests", &Language::Python).unwrap();
+        let registry = Registry::from_layers(layers(), Some(dir.clone()));
+        let requests = registry.lookup_package("Requests", &Language::Python).unwrap().unwrap();
+        assert_eq!(requests.record.version, "2.32.4");
+
+        let index = registry.layers[0].indexes[&Language::Python].get().unwrap();
+        let mapped = index.mapped.as_ref().unwrap();
+        assert_eq!(mapped.decoded.iter().filter(|cell| cell.get().is_some()).count(), 1);
+        assert_eq!(index.records().unwrap().len(), community_only().list_packages(Some(&Language::Python), None).unwrap().len());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_higher_layer_overrides_lower() {
+        let dir = std::env::temp_dir().join(format!("sip-layers-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("python")).unwrap();
+        let mut pinned = record("Requests", "2.0.0");
+        pinned.trust_score = 3.0;
+        fs::write(dir.join("python/trusted-packages.json"), serde_json::to_string(&vec![pinned]).unwrap()).unwrap();
+
+        let registry = Registry::from_layers(
+            vec![
+                RegistryLayer::new(PERSONAL_LAYER, 300, LayerSource::Directory(dir.clone())),
+                RegistryLayer::new(COMMUNITY_LAYER, 0, LayerSource::Community { prefer_files: false }),
+            ],
+            None,
+        );
+
+        // the whole package comes from the overriding layer, versions are not mixed across layers
+        let requests = registry.lookup_package("requests", &Language::Python).unwrap().unwrap();
+        assert_eq!((requests.record.version.as_str(), requests.layer), ("2.0.0", PERSONAL_LAYER));
+        assert!(registry.lookup_version("requests", "2.31.0", &Language::Python).unwrap().is_none());
+
+        let listed = registry.list_packages(Some(&Language::Python), None).unwrap();
+        assert_eq!(listed.iter().filter(|r| r.record.name == "Requests").count(), 1);
+        assert!(listed.iter().filter(|r| r.record.name != "Requests").all(|r| r.layer == COMMUNITY_LAYER));
+        assert_eq!(registry.list_packages(Some(&Language::Python), Some(PERSONAL_LAYER)).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_writes_file_and_cache_from_memory() {
+        let dir = std::env::temp_dir().join(format!("sip-save-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let layers = || vec![RegistryLayer::new(PERSONAL_LAYER, 300, LayerSource::Directory(dir.join("personal")))];
+
+        let mut registry = Registry::from_layers(layers(), Some(dir.join("cache")));
+        registry.add_package(record("requests", "2.31.0"), &Language::Python, PERSONAL_LAYER).unwrap();
+        registry.save_to_disk().unwrap();
+        assert!(dir.join("personal/python/trusted-packages.json").exists());
+        assert!(fs::read_dir(dir.join("cache")).unwrap().next().is_some());
+
+        let reloaded = Registry::from_layers(layers(), Some(dir.join("cache")));
+        assert_eq!(reloaded.lookup_package("requests", &Language::Python).unwrap().unwrap().record.version, "2.31.0");
+
+        // a second spelling is saved under the first, so the written file never clashes
+        let mut registry = reloaded;
+        registry.add_package(record("Requests", "2.32.0"), &Language::Python, PERSONAL_LAYER).unwrap();
+        registry.save_to_disk().unwrap();
+        let saved: Vec<PackageRecord> = serde_json::from_str(&fs::read_to_string(dir.join("personal/python/trusted-packages.json")).unwrap()).unwrap();
+        assert_eq!(saved.iter().map(|r| (r.name.as_str(), r.version.as_str())).collect::<Vec<_>>(), vec![("requests", "2.31.0"), ("requests", "2.32.0")]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_blocks_apply_to_affected_versions() {
+        let mut registry = community_only();
+        let block = BlockedRecord {
+            name: "Requests".to_string(),
+            versions: vec!["2.31.0".to_string()],
+            reason: "compromised release".to_string(),
+            reference: None,
+            blocked_on: "2024-01-01".to_string(),
+        };
+        registry.block_package(block, &Language::Python, COMMUNITY_LAYER).unwrap();
+
+        assert!(registry.lookup_block("requests", Some("2.31.0"), &Language::Python).unwrap().is_some());
+        assert!(registry.lookup_block("requests", Some("2.32.4"), &Language::Python).unwrap().is_none());
+        // unpinned requests cannot rule the blocked version out
+        assert!(registry.lookup_block("requests", None, &Language::Python).unwrap().is_some());
+
+        assert!(registry.unblock_package("REQUESTS", &Language::Python, COMMUNITY_LAYER).unwrap());
+        assert!(registry.lookup_block("requests", None, &Language::Python).unwrap().is_none());
     }
 }
@@ -0,0 +1,263 @@
+// registry audit: re-fetches the upstream checksum for every recorded version and reports
+// records that no longer match what upstream serves
+
+use crate::cli::Language;
+use crate::sip::normalize::normalize_name;
+use crate::sip::fetch::{download_artifact, unpack_artifact};
+use crate::sip::package::PackageRecord;
+use crate::sip::source_check;
+use crate::sip::upstream::{self, OwnershipChange, UpstreamRelease};
+use crate::sip::version::compare_versions;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Issue {
+    HashMismatch { recorded: String, upstream: String },
+    Yanked { reason: Option<String> },
+    Missing,
+    // the package changed hands since it was reviewed
+    OwnershipChanged { added: Vec<String>, removed: Vec<String> },
+    // the artifact's own metadata names a different repository than the record
+    SourceMismatch { source: String, declared: Vec<String> },
+    Behind { latest: String, newer_versions: usize },
+    Failed { message: String },
+}
+
+impl Issue {
+    // stale records are worth a look, the rest mean the record cannot be relied on
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Issue::Behind { .. })
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AuditFinding {
+    pub language: String,
+    pub package: String,
+    pub version: String,
+    #[serde(flatten)]
+    pub issue: Issue,
+    // whether --fix rewrote the record
+    pub fixed: bool,
+}
+
+impl std::fmt::Display for AuditFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let marker = if self.fixed { "✓" } else if self.issue.is_error() { "✘" } else { "⚠" };
+        write!(f, "{} {} {} ", marker, self.package, self.version)?;
+        match &self.issue {
+            Issue::HashMismatch { recorded, upstream } => write!(f, "hash mismatch: recorded {}, upstream {}", recorded, upstream)?,
+            Issue::Yanked { reason: None } => write!(f, "was yanked upstream")?,
+            Issue::Yanked { reason: Some(reason) } => write!(f, "was yanked upstream: {}", reason)?,
+            Issue::Missing => write!(f, "no longer exists upstream")?,
+            Issue::OwnershipChanged { added, removed } => {
+                let change = OwnershipChange { added: added.clone(), removed: removed.clone() };
+                write!(f, "changed owners since it was reviewed: {}", change)?
+            }
+            Issue::SourceMismatch { source, declared } => {
+                write!(f, "records source {} but its metadata declares {}", source, declared.join(", "))?
+            }
+            Issue::Behind { latest, newer_versions } => {
+                write!(f, "is {} releases behind upstream (latest {})", newer_versions, latest)?
+            }
+            Issue::Failed { message } => write!(f, "could not be checked: {}", message)?,
+        }
+        if self.fixed {
+            write!(f, " (fixed)")?;
+        }
+        Ok(())
+    }
+}
+
+// pre-releases do not count towards how far behind a record is
+fn is_release(version: &str) -> bool {
+    !version.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+// compare one record with what upstream serves for its version
+pub fn check_release(record: &PackageRecord, release: Option<&UpstreamRelease>) -> Option<Issue> {
+    let release = release?;
+    // a record that already carries the yank is not news
+    if release.yanked && !record.yanked {
+        return Some(Issue::Yanked { reason: release.yank_reason.clone() });
+    }
+    match release.hashes.first() {
+        Some(upstream) if !release.hashes.contains(&record.hash) => Some(Issue::HashMismatch {
+            recorded: record.hash.clone(),
+            upstream: upstream.clone(),
+        }),
+        _ => None,
+    }
+}
+
+pub fn check_ownership(record: &PackageRecord, current: &[String]) -> Option<Issue> {
+    OwnershipChange::between(&record.maintainers, current).map(|change| Issue::OwnershipChanged {
+        added: change.added,
+        removed: change.removed,
+    })
+}
+
+pub fn check_source(record: &PackageRecord, declared: &[String]) -> Option<Issue> {
+    (!source_check::source_agrees(&record.source, declared)).then(|| Issue::SourceMismatch {
+        source: record.source.clone(),
+        declared: declared.to_vec(),
+    })
+}
+
+pub fn check_behind(record: &PackageRecord, upstream_versions: &[String], threshold: usize) -> Option<Issue> {
+    let newer: Vec<&String> = upstream_versions
+        .iter()
+        .filter(|v| is_release(v) && compare_versions(v, &record.version).is_gt())
+        .collect();
+    let latest = newer.iter().max_by(|a, b| compare_versions(a, b))?;
+    (newer.len() >= threshold).then(|| Issue::Behind {
+        latest: latest.to_string(),
+        newer_versions: newer.len(),
+    })
+}
+
+// audit every record of one language; with `fix`, mismatched hashes are replaced and yanks
+// recorded in place while scores, endorsements and review dates are left alone. `sources`
+// downloads each artifact to compare the repository its metadata declares with the record
+pub async fn audit_records(
+    client: &reqwest::Client,
+    records: &mut [PackageRecord],
+    lang: &Language,
+    fix: bool,
+    behind_threshold: usize,
+    sources: bool,
+) -> Vec<AuditFinding> {
+    // only the newest recorded version of a package is expected to keep up with upstream
+    let mut latest: HashMap<String, String> = HashMap::new();
+    for record in records.iter() {
+        let entry = latest.entry(normalize_name(&record.name, lang)).or_insert_with(|| record.version.clone());
+        if compare_versions(&record.version, entry).is_gt() {
+            *entry = record.version.clone();
+        }
+    }
+
+    let mut findings = Vec::new();
+    for record in records.iter_mut() {
+        let mut issues = Vec::new();
+        match upstream::fetch_release(client, &record.name, &record.version, lang).await {
+            Ok(None) => issues.push(Issue::Missing),
+            Ok(Some(release)) => issues.extend(check_release(record, Some(&release))),
+            Err(e) => issues.push(Issue::Failed { message: e.to_string() }),
+        }
+
+        // owners are not touched by --fix, a new owner needs a fresh review rather than a rewrite
+        if !record.maintainers.is_empty()
+            && let Ok(Some(current)) = upstream::fetch_maintainers(client, &record.name, lang).await
+        {
+            issues.extend(check_ownership(record, &current));
+        }
+
+        if sources && *lang == Language::Go {
+            match upstream::go_repository(client, &record.name).await {
+                Ok(repository) => issues.extend(check_source(record, &repository.into_iter().collect::<Vec<_>>())),
+                Err(e) => issues.push(Issue::Failed { message: e.to_string() }),
+            }
+        } else if sources {
+            match download_artifact(client, &record.name, Some(&record.version), lang).await.and_then(|artifact| unpack_artifact(&artifact.bytes)) {
+                Ok(files) => issues.extend(check_source(record, &source_check::declared_repositories(&files, lang))),
+                Err(e) => issues.push(Issue::Failed { message: e.to_string() }),
+            }
+        }
+
+        if latest.get(&normalize_name(&record.name, lang)) == Some(&record.version)
+            && let Ok(versions) = upstream::fetch_versions(client, &record.name, lang).await
+        {
+            issues.extend(check_behind(record, &versions, behind_threshold));
+        }
+
+        for issue in issues {
+            let fixed = match (&issue, fix) {
+                (Issue::HashMismatch { upstream, .. }, true) => {
+                    record.hash = upstream.clone();
+                    true
+                }
+                (Issue::Yanked { reason }, true) => {
+                    record.yanked = true;
+                    record.yank_reason = reason.clone();
+                    true
+                }
+                // a homepage or placeholder is replaced, a different repository needs a human
+                (Issue::SourceMismatch { source, declared }, true) if declared.len() == 1 && !source_check::is_repository_url(source) => {
+                    record.source = declared[0].clone();
+                    true
+                }
+                _ => false,
+            };
+            findings.push(AuditFinding {
+                language: lang.to_string(),
+                package: record.name.clone(),
+                version: record.version.clone(),
+                issue,
+                fixed,
+            });
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sip::testing::record;
+
+    #[test]
+    fn test_check_release() {
+        let release = UpstreamRelease {
+            hashes: vec!["sha256:aa".to_string(), "sha256:bb".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(check_release(&PackageRecord { hash: "sha256:bb".to_string(), ..record("serde", "1.0.0") }, Some(&release)), None);
+        assert_eq!(
+            check_release(&PackageRecord { hash: "sha256:manual".to_string(), ..record("serde", "1.0.0") }, Some(&release)),
+            Some(Issue::HashMismatch { recorded: "sha256:manual".to_string(), upstream: "sha256:aa".to_string() })
+        );
+        let yanked = UpstreamRelease { yanked: true, yank_reason: Some("broken build".to_string()), ..release };
+        assert_eq!(
+            check_release(&PackageRecord { hash: "sha256:aa".to_string(), ..record("serde", "1.0.0") }, Some(&yanked)),
+            Some(Issue::Yanked { reason: Some("broken build".to_string()) })
+        );
+        let recorded = PackageRecord { yanked: true, hash: "sha256:aa".to_string(), ..record("serde", "1.0.0") };
+        assert_eq!(check_release(&recorded, Some(&yanked)), None);
+    }
+
+    #[test]
+    fn test_check_ownership() {
+        let reviewed = PackageRecord { maintainers: vec!["dtolnay".to_string()], ..record("serde", "1.0.0") };
+        assert_eq!(check_ownership(&reviewed, &["dtolnay".to_string()]), None);
+        assert_eq!(
+            check_ownership(&reviewed, &["mallory".to_string()]),
+            Some(Issue::OwnershipChanged { added: vec!["mallory".to_string()], removed: vec!["dtolnay".to_string()] })
+        );
+        assert_eq!(check_ownership(&record("serde", "1.0.0"), &["mallory".to_string()]), None);
+    }
+
+    #[test]
+    fn test_check_source() {
+        let declared = vec!["https://github.com/serde-rs/serde".to_string()];
+        let reviewed = PackageRecord { source: "https://github.com/serde-rs/serde".to_string(), ..record("serde", "1.0.0") };
+        assert_eq!(check_source(&reviewed, &declared), None);
+        assert_eq!(check_source(&reviewed, &[]), None);
+        assert_eq!(
+            check_source(&record("serde", "1.0.0"), &declared),
+            Some(Issue::SourceMismatch { source: "https://example.com".to_string(), declared: declared.clone() })
+        );
+        assert!(check_source(&record("serde", "1.0.0"), &declared).is_some_and(|issue| issue.is_error()));
+    }
+
+    #[test]
+    fn test_check_behind_ignores_prereleases() {
+        let versions: Vec<String> = ["1.0.0", "1.0.1", "1.0.2", "1.1.0", "2.0.0-rc.1"].iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            check_behind(&record("serde", "1.0.0"), &versions, 3),
+            Some(Issue::Behind { latest: "1.1.0".to_string(), newer_versions: 3 })
+        );
+        assert_eq!(check_behind(&record("serde", "1.0.1"), &versions, 3), None);
+    }
+}
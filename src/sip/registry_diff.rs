@@ -0,0 +1,250 @@
+// semantic diff between two registry snapshots: what a reviewer needs to know about a change
+// to the data files, rather than a line diff of pretty-printed json
+
+use crate::cli::Language;
+use crate::sip::normalize::normalize_name;
+use crate::sip::package::PackageRecord;
+use crate::sip::registry::data_file;
+use crate::sip::version::compare_versions;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Change {
+    Added { versions: Vec<String> },
+    Removed { versions: Vec<String> },
+    VersionBump { from: String, to: String },
+    VersionAdded { version: String },
+    VersionRemoved { version: String },
+    ScoreChanged { version: String, from: f64, to: f64 },
+    // the same version now points at different content - almost never legitimate
+    HashChanged { version: String, from: String, to: String },
+    EndorsementsChanged { version: String, added: Vec<String>, removed: Vec<String> },
+}
+
+impl Change {
+    pub fn is_red_flag(&self) -> bool {
+        matches!(self, Change::HashChanged { .. })
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PackageChange {
+    pub language: String,
+    pub package: String,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct RegistryDiff {
+    pub changes: Vec<PackageChange>,
+    pub red_flags: usize,
+}
+
+// every language's records in one snapshot
+pub type Snapshot = Vec<(Language, Vec<PackageRecord>)>;
+
+// a snapshot is a directory laid out like registry/data (or a checkout containing one),
+// or a single data file whose language comes from --lang or its parent directory name
+pub fn load_snapshot(path: &Path, lang: Option<&Language>) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    if path.is_dir() {
+        let dir = if path.join("registry/data").is_dir() { path.join("registry/data") } else { path.to_path_buf() };
+        let mut snapshot = Vec::new();
+        for language in Language::ALL.iter().filter(|l| lang.is_none_or(|lang| lang == *l)) {
+            let file = data_file(&dir, language);
+            let records = if file.exists() { read_records(&file)? } else { Vec::new() };
+            snapshot.push((language.clone(), records));
+        }
+        return Ok(snapshot);
+    }
+
+    let language = match lang {
+        Some(lang) => lang.clone(),
+        None => path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|name| Language::ALL.iter().find(|l| l.to_string() == name.to_string_lossy()))
+            .cloned()
+            .ok_or_else(|| format!("cannot tell the language of {} - please specify with --lang flag", path.display()))?,
+    };
+    Ok(vec![(language, read_records(path)?)])
+}
+
+fn read_records(path: &Path) -> Result<Vec<PackageRecord>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("invalid registry file {}: {}", path.display(), e).into())
+}
+
+// records grouped by normalized name, versions oldest first
+fn group<'a>(records: &'a [PackageRecord], lang: &Language) -> BTreeMap<String, Vec<&'a PackageRecord>> {
+    let mut grouped: BTreeMap<String, Vec<&PackageRecord>> = BTreeMap::new();
+    for record in records {
+        grouped.entry(normalize_name(&record.name, lang)).or_default().push(record);
+    }
+    for versions in grouped.values_mut() {
+        versions.sort_by(|a, b| compare_versions(&a.version, &b.version));
+    }
+    grouped
+}
+
+fn versions_of(records: &[&PackageRecord]) -> Vec<String> {
+    records.iter().map(|r| r.version.clone()).collect()
+}
+
+pub fn diff_records(old: &[PackageRecord], new: &[PackageRecord], lang: &Language) -> Vec<PackageChange> {
+    let old = group(old, lang);
+    let new = group(new, lang);
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    let mut changes = Vec::new();
+    for name in names {
+        let display_name = new.get(name).or(old.get(name)).map(|r| r[0].name.clone()).unwrap_or_default();
+        let mut push = |change: Change| {
+            changes.push(PackageChange {
+                language: lang.to_string(),
+                package: display_name.clone(),
+                change,
+            })
+        };
+
+        let (old_versions, new_versions) = match (old.get(name), new.get(name)) {
+            (None, Some(new_versions)) => {
+                push(Change::Added { versions: versions_of(new_versions) });
+                continue;
+            }
+            (Some(old_versions), None) => {
+                push(Change::Removed { versions: versions_of(old_versions) });
+                continue;
+            }
+            (Some(old_versions), Some(new_versions)) => (old_versions, new_versions),
+            (None, None) => continue,
+        };
+
+        let mut added: Vec<&str> = new_versions
+            .iter()
+            .map(|r| r.version.as_str())
+            .filter(|v| !old_versions.iter().any(|r| r.version == *v))
+            .collect();
+        let mut removed: Vec<&str> = old_versions
+            .iter()
+            .map(|r| r.version.as_str())
+            .filter(|v| !new_versions.iter().any(|r| r.version == *v))
+            .collect();
+
+        // a replaced latest version reads as one bump rather than an add and a remove
+        let old_latest = old_versions.last().map(|r| r.version.as_str());
+        let new_latest = new_versions.last().map(|r| r.version.as_str());
+        if let (Some(from), Some(to)) = (old_latest, new_latest)
+            && removed.contains(&from)
+            && added.contains(&to)
+        {
+            push(Change::VersionBump { from: from.to_string(), to: to.to_string() });
+            added.retain(|v| *v != to);
+            removed.retain(|v| *v != from);
+        }
+        for version in added {
+            push(Change::VersionAdded { version: version.to_string() });
+        }
+        for version in removed {
+            push(Change::VersionRemoved { version: version.to_string() });
+        }
+
+        for old_record in old_versions.iter() {
+            let Some(new_record) = new_versions.iter().find(|r| r.version == old_record.version) else {
+                continue;
+            };
+            let version = old_record.version.clone();
+            if old_record.hash != new_record.hash {
+                push(Change::HashChanged { version: version.clone(), from: old_record.hash.clone(), to: new_record.hash.clone() });
+            }
+            if old_record.trust_score != new_record.trust_score {
+                push(Change::ScoreChanged { version: version.clone(), from: old_record.trust_score, to: new_record.trust_score });
+            }
+            let added: Vec<String> = new_record.endorsed_by.iter().filter(|e| !old_record.endorsed_by.contains(e)).cloned().collect();
+            let removed: Vec<String> = old_record.endorsed_by.iter().filter(|e| !new_record.endorsed_by.contains(e)).cloned().collect();
+            if !added.is_empty() || !removed.is_empty() {
+                push(Change::EndorsementsChanged { version, added, removed });
+            }
+        }
+    }
+    changes
+}
+
+pub fn diff_snapshots(old: &Path, new: &Path, lang: Option<&Language>) -> Result<RegistryDiff, Box<dyn std::error::Error>> {
+    let old = load_snapshot(old, lang)?;
+    let new = load_snapshot(new, lang)?;
+
+    let mut diff = RegistryDiff::default();
+    for (language, new_records) in &new {
+        let old_records = old
+            .iter()
+            .find(|(l, _)| l == language)
+            .map(|(_, records)| records.as_slice())
+            .unwrap_or_default();
+        diff.changes.extend(diff_records(old_records, new_records, language));
+    }
+    diff.red_flags = diff.changes.iter().filter(|c| c.change.is_red_flag()).count();
+    Ok(diff)
+}
+
+impl std::fmt::Display for PackageChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = &self.package;
+        match &self.change {
+            Change::Added { versions } => write!(f, "+ {} {} (new package)", name, versions.join(", ")),
+            Change::Removed { versions } => write!(f, "- {} {} (removed)", name, versions.join(", ")),
+            Change::VersionBump { from, to } => {
+                let direction = if compare_versions(to, from).is_lt() { "downgraded" } else { "bumped" };
+                write!(f, "~ {} {} -> {} ({})", name, from, to, direction)
+            }
+            Change::VersionAdded { version } => write!(f, "+ {} {} (additional version)", name, version),
+            Change::VersionRemoved { version } => write!(f, "- {} {} (version removed)", name, version),
+            Change::ScoreChanged { version, from, to } => write!(f, "~ {} {} trust score {:.1} -> {:.1}", name, version, from, to),
+            Change::HashChanged { version, from, to } => {
+                write!(f, "✘ {} {} hash changed for an unchanged version: {} -> {}", name, version, from, to)
+            }
+            Change::EndorsementsChanged { version, added, removed } => {
+                let mut parts: Vec<String> = added.iter().map(|e| format!("+{}", e)).collect();
+                parts.extend(removed.iter().map(|e| format!("-{}", e)));
+                write!(f, "~ {} {} endorsements {}", name, version, parts.join(" "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sip::testing::record;
+
+    #[test]
+    fn test_diff_reports_semantic_changes() {
+        let old = vec![
+            PackageRecord { hash: "sha256:aa".to_string(), trust_score: 9.5, ..record("requests", "2.31.0") },
+            PackageRecord { hash: "sha256:bb".to_string(), trust_score: 9.0, ..record("flask", "2.3.2") },
+            PackageRecord { hash: "sha256:cc".to_string(), trust_score: 5.0, ..record("leftpad", "1.0.0") },
+        ];
+        let mut endorsed = PackageRecord { hash: "sha256:b0".to_string(), trust_score: 8.0, ..record("Flask", "2.3.2") };
+        endorsed.endorsed_by.push("alice".to_string());
+        let new = vec![
+            PackageRecord { hash: "sha256:dd".to_string(), trust_score: 9.5, ..record("requests", "2.32.4") },
+            endorsed,
+            PackageRecord { hash: "sha256:ee".to_string(), trust_score: 9.8, ..record("numpy", "1.24.3") },
+        ];
+
+        let changes: Vec<Change> = diff_records(&old, &new, &Language::Python).into_iter().map(|c| c.change).collect();
+        assert_eq!(changes, vec![
+            Change::HashChanged { version: "2.3.2".to_string(), from: "sha256:bb".to_string(), to: "sha256:b0".to_string() },
+            Change::ScoreChanged { version: "2.3.2".to_string(), from: 9.0, to: 8.0 },
+            Change::EndorsementsChanged { version: "2.3.2".to_string(), added: vec!["alice".to_string()], removed: vec![] },
+            Change::Removed { versions: vec!["1.0.0".to_string()] },
+            Change::Added { versions: vec!["1.24.3".to_string()] },
+            Change::VersionBump { from: "2.31.0".to_string(), to: "2.32.4".to_string() },
+        ]);
+        assert_eq!(changes.iter().filter(|c| c.is_red_flag()).count(), 1);
+    }
+}
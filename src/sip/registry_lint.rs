@@ -0,0 +1,294 @@
+// registry linting: everything the load-time schema check does, reported per entry with a
+// json pointer, plus the mistakes hand edits tend to introduce that the schema cannot see
+
+use crate::cli::Language;
+use crate::sip::config::LintConfig;
+use crate::sip::normalize::normalize_name;
+use crate::sip::source_check::is_repository_url;
+use crate::sip::registry::{blocked_file, data_file, BLOCKED_SCHEMA_JSON, SCHEMA_JSON};
+use chrono::NaiveDate;
+use jsonschema::JSONSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// hashes written by manual and bulk trust entries that were never checked against an artifact
+const PLACEHOLDER_HASHES: &[&str] = &["sha256:manual", "manual", "sha256:", ""];
+const PLACEHOLDER_SOURCES: &[&str] = &["https://manual.entry", "manual"];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Finding {
+    pub file: String,
+    // json pointer to the offending value, e.g. /3/hash
+    pub path: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let marker = match self.severity {
+            Severity::Error => "✘",
+            Severity::Warning => "⚠",
+        };
+        let path = if self.path.is_empty() { "/" } else { &self.path };
+        write!(f, "{} {} {}: {}", marker, self.file, path, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileKind {
+    Trusted,
+    Blocked,
+}
+
+type LintTarget = (PathBuf, Language, FileKind);
+
+// which files to lint: a registry/data style directory (or a checkout holding one), or one file
+fn collect_files(path: &Path, lang: Option<&Language>) -> Result<Vec<LintTarget>, Box<dyn std::error::Error>> {
+    if path.is_dir() {
+        let dir = if path.join("registry/data").is_dir() { path.join("registry/data") } else { path.to_path_buf() };
+        let mut files = Vec::new();
+        for language in Language::ALL.iter().filter(|l| lang.is_none_or(|lang| lang == *l)) {
+            for (file, kind) in [(data_file(&dir, language), FileKind::Trusted), (blocked_file(&dir, language), FileKind::Blocked)] {
+                if file.exists() {
+                    files.push((file, language.clone(), kind));
+                }
+            }
+        }
+        return Ok(files);
+    }
+
+    let language = match lang {
+        Some(lang) => lang.clone(),
+        None => path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|name| Language::ALL.iter().find(|l| l.to_string() == name.to_string_lossy()))
+            .cloned()
+            .ok_or_else(|| format!("cannot tell the language of {} - please specify with --lang flag", path.display()))?,
+    };
+    let kind = if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("blocked")) {
+        FileKind::Blocked
+    } else {
+        FileKind::Trusted
+    };
+    Ok(vec![(path.to_path_buf(), language, kind)])
+}
+
+pub fn lint_path(path: &Path, lang: Option<&Language>, config: &LintConfig, today: NaiveDate) -> Result<Vec<Finding>, Box<dyn std::error::Error>> {
+    let files = collect_files(path, lang)?;
+    if files.is_empty() {
+        return Err(format!("no registry files found in {}", path.display()).into());
+    }
+
+    let mut findings = Vec::new();
+    for (file, language, kind) in files {
+        let content = fs::read_to_string(&file)?;
+        findings.extend(lint_json(&file.to_string_lossy(), &content, &language, kind == FileKind::Blocked, config, today)?);
+    }
+    Ok(findings)
+}
+
+fn lint_json(file: &str, content: &str, lang: &Language, blocked: bool, config: &LintConfig, today: NaiveDate) -> Result<Vec<Finding>, Box<dyn std::error::Error>> {
+    let mut findings = Vec::new();
+    let mut report = |path: String, severity: Severity, message: String| {
+        findings.push(Finding { file: file.to_string(), path, severity, message });
+    };
+
+    let json: Value = match serde_json::from_str(content) {
+        Ok(json) => json,
+        Err(e) => {
+            report(String::new(), Severity::Error, format!("invalid json: {}", e));
+            return Ok(findings);
+        }
+    };
+
+    let schema_source = if blocked { BLOCKED_SCHEMA_JSON } else { SCHEMA_JSON };
+    let schema = JSONSchema::compile(&serde_json::from_str(schema_source)?)
+        .map_err(|e| format!("failed to compile embedded json schema: {}", e))?;
+    if let Err(errors) = schema.validate(&json) {
+        for error in errors {
+            report(error.instance_path.to_string(), Severity::Error, error.to_string());
+        }
+    }
+
+    let Some(entries) = json.as_array() else {
+        return Ok(findings);
+    };
+
+    // normalized name -> (index, spelling, version) of every entry seen so far
+    let mut seen: HashMap<String, Vec<(usize, String, String)>> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let Some(name) = entry.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let version = entry.get("version").and_then(Value::as_str).unwrap_or_default();
+        let normalized = normalize_name(name, lang);
+
+        // go's normalized form is the proxy escaping of a case-sensitive path, not a spelling to write
+        if *lang != Language::Go && normalized != name {
+            report(format!("/{}/name", i), Severity::Warning, format!("'{}' is not in normalized form '{}'", name, normalized));
+        }
+
+        for (other, spelling, other_version) in seen.get(&normalized).into_iter().flatten() {
+            if spelling != name {
+                report(format!("/{}/name", i), Severity::Error, format!("'{}' duplicates '{}' at /{}", name, spelling, other));
+            } else if blocked || other_version == version {
+                report(format!("/{}", i), Severity::Error, format!("duplicate entry for {} {}, first at /{}", name, version, other));
+            }
+        }
+        seen.entry(normalized).or_default().push((i, name.to_string(), version.to_string()));
+
+        if blocked {
+            continue;
+        }
+
+        let hash = entry.get("hash").and_then(Value::as_str).unwrap_or_default();
+        if PLACEHOLDER_HASHES.contains(&hash) {
+            report(format!("/{}/hash", i), Severity::Error, format!("placeholder hash '{}' was never checked against the artifact", hash));
+        } else if !is_hash(hash) {
+            report(format!("/{}/hash", i), Severity::Error, format!("'{}' is not an <algorithm>:<hex> or sri hash", hash));
+        }
+
+        if let Some(source) = entry.get("source").and_then(Value::as_str) {
+            if PLACEHOLDER_SOURCES.contains(&source) {
+                report(format!("/{}/source", i), Severity::Warning, format!("placeholder source '{}'", source));
+            } else if !is_repository_url(source) {
+                report(format!("/{}/source", i), Severity::Warning, format!("'{}' is not a source repository url", source));
+            }
+        }
+
+        if let Some(reviewed) = entry.get("last_reviewed").and_then(Value::as_str)
+            && let Ok(date) = NaiveDate::parse_from_str(reviewed, "%Y-%m-%d")
+            && date > today
+        {
+            report(format!("/{}/last_reviewed", i), Severity::Error, format!("review date {} is in the future", reviewed));
+        }
+
+        if !version.is_empty() && !is_semver(version) {
+            // cargo and npm require semver, the other ecosystems only mostly follow it
+            let severity = match lang {
+                Language::Rust | Language::Node | Language::Go => Severity::Error,
+                _ => Severity::Warning,
+            };
+            report(format!("/{}/version", i), severity, format!("'{}' is not a valid semver version", version));
+        }
+
+        if let Some(score) = entry.get("trust_score").and_then(Value::as_f64)
+            && (score < config.min_score || score > config.max_score)
+        {
+            report(
+                format!("/{}/trust_score", i),
+                Severity::Error,
+                format!("score {:.1} is outside the policy band {:.1}-{:.1}", score, config.min_score, config.max_score),
+            );
+        }
+    }
+
+    Ok(findings)
+}
+
+// sha256:<hex>, sri style sha512-<base64>, go.sum's h1:<base64>, or composer's git:<reference>
+fn is_hash(hash: &str) -> bool {
+    if let Some(reference) = hash.strip_prefix("git:") {
+        return !reference.is_empty();
+    }
+    if let Some(digest) = hash.strip_prefix("h1:") {
+        return digest.len() == 44 && digest.chars().all(|c| c.is_ascii_alphanumeric() || "+/=".contains(c));
+    }
+    if let Some((algorithm, digest)) = hash.split_once(':') {
+        let expected_len = match algorithm {
+            "sha1" => 40,
+            "sha256" => 64,
+            "sha384" => 96,
+            "sha512" => 128,
+            _ => return false,
+        };
+        return digest.len() == expected_len && digest.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    if let Some((algorithm, digest)) = hash.split_once('-') {
+        return matches!(algorithm, "sha1" | "sha256" | "sha384" | "sha512")
+            && !digest.is_empty()
+            && digest.chars().all(|c| c.is_ascii_alphanumeric() || "+/=".contains(c));
+    }
+    false
+}
+
+// MAJOR.MINOR.PATCH[-prerelease][+build] as defined by semver 2.0
+fn is_semver(version: &str) -> bool {
+    let (rest, build) = match version.split_once('+') {
+        Some((rest, build)) => (rest, Some(build)),
+        None => (version, None),
+    };
+    let (core, pre) = match rest.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (rest, None),
+    };
+
+    let numeric = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()) && (part == "0" || !part.starts_with('0'));
+    let identifiers = |s: &str| s.split('.').all(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts.iter().all(|part| numeric(part))
+        && pre.is_none_or(identifiers)
+        && build.is_none_or(identifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semver_and_hash_shapes() {
+        assert!(is_semver("1.0.188"));
+        assert!(is_semver("32.1.2-jre"));
+        assert!(is_semver("1.0.0-rc.1+build.5"));
+        assert!(!is_semver("2.0.0rc1.post1.x"));
+        assert!(!is_semver("01.2.3"));
+        assert!(!is_semver("7.0.8.1"));
+
+        assert!(is_hash("sha256:942c5a758f98d790eaed1a29cb6eefc7ffb0d1cf7af05c3d2791656dbd6ad1e1"));
+        assert!(is_hash("sha512-v2kDEe57lecTulaDIuNTPy3Ry4gLGJ6Z1O3vE1krgXZNrsQ+LFTGHVxVjcXPs17LhbZVGedAJv8XZ1tvj5FvSg=="));
+        assert!(!is_hash("sha256:abc"));
+    }
+
+    #[test]
+    fn test_lint_reports_json_paths() {
+        let content = r#"[
+            {"name": "Flask", "version": "2.3.2", "hash": "sha256:manual", "trust_score": 9.0, "last_reviewed": "2030-01-01", "source": "https://manual.entry"},
+            {"name": "flask", "version": "2.3.2", "hash": "sha256:manual", "trust_score": 11.0, "last_reviewed": "2024-01-01", "source": "https://example.com"}
+        ]"#;
+        let today = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let findings = lint_json("trusted-packages.json", content, &Language::Python, false, &LintConfig::default(), today).unwrap();
+        let paths: Vec<(&str, Severity)> = findings.iter().map(|f| (f.path.as_str(), f.severity)).collect();
+
+        assert!(paths.contains(&("/1/trust_score", Severity::Error)));
+        assert!(paths.contains(&("/0/name", Severity::Warning)));
+        assert!(paths.contains(&("/1/name", Severity::Error)));
+        assert!(paths.contains(&("/0/hash", Severity::Error)));
+        assert!(paths.contains(&("/0/source", Severity::Warning)));
+        assert!(paths.contains(&("/1/source", Severity::Warning)));
+        assert!(paths.contains(&("/0/last_reviewed", Severity::Error)));
+    }
+
+    #[test]
+    fn test_go_paths_keep_their_case() {
+        let content = r#"[
+            {"name": "github.com/BurntSushi/toml", "version": "1.3.2", "hash": "h1:o7IhLm0Msx3BaB+n3Ag7L8EVlByGnpq14C4YWiu/gL8=", "trust_score": 9.0, "last_reviewed": "2025-06-01", "source": "https://github.com/BurntSushi/toml"}
+        ]"#;
+        let today = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let findings = lint_json("trusted-modules.json", content, &Language::Go, false, &LintConfig::default(), today).unwrap();
+        assert!(!findings.iter().any(|f| f.path == "/0/name"));
+    }
+}
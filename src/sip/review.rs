@@ -0,0 +1,94 @@
+// review expiry: a trust entry is only as good as its last review, so entries older than
+// the configured age for their language and score band are reported as stale
+
+use crate::cli::Language;
+use crate::sip::config::{ReviewConfig, ReviewRule};
+use crate::sip::package::PackageRecord;
+use chrono::{NaiveDate, Utc};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Staleness {
+    // None when the review date cannot be read
+    pub age_days: Option<i64>,
+    pub max_age_days: i64,
+}
+
+impl std::fmt::Display for Staleness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.age_days {
+            Some(days) => write!(f, "last reviewed {} days ago (max {})", days, self.max_age_days),
+            None => write!(f, "review date is not a valid date (max {} days)", self.max_age_days),
+        }
+    }
+}
+
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+// the most specific matching rule wins: a language rule beats a catch-all, then the highest score band
+pub fn max_age_days(config: &ReviewConfig, lang: &Language, trust_score: f64) -> i64 {
+    let lang = lang.to_string();
+    config
+        .rules
+        .iter()
+        .filter(|rule| rule.language.as_ref().is_none_or(|l| *l == lang) && trust_score >= rule.min_score)
+        .max_by(|a, b| specificity(a).partial_cmp(&specificity(b)).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|rule| rule.max_age_days)
+        .unwrap_or(config.max_age_days)
+}
+
+fn specificity(rule: &ReviewRule) -> (bool, f64) {
+    (rule.language.is_some(), rule.min_score)
+}
+
+// days since the record was last reviewed, None when the date cannot be read
+pub fn review_age(record: &PackageRecord, today: NaiveDate) -> Option<i64> {
+    NaiveDate::parse_from_str(&record.last_reviewed, "%Y-%m-%d")
+        .ok()
+        .map(|reviewed| (today - reviewed).num_days())
+}
+
+// an unreadable review date counts as never reviewed
+pub fn staleness(record: &PackageRecord, lang: &Language, config: &ReviewConfig, today: NaiveDate) -> Option<Staleness> {
+    let max_age_days = max_age_days(config, lang, record.trust_score);
+    let age_days = review_age(record, today);
+    age_days
+        .is_none_or(|days| days > max_age_days)
+        .then_some(Staleness { age_days, max_age_days })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sip::testing::record;
+
+    #[test]
+    fn test_most_specific_rule_sets_max_age() {
+        let config: ReviewConfig = toml::from_str(r#"
+            max_age_days = 365
+            [[rules]]
+            min_score = 9.0
+            max_age_days = 540
+            [[rules]]
+            language = "node"
+            max_age_days = 90
+        "#).unwrap();
+
+        assert_eq!(max_age_days(&config, &Language::Python, 8.0), 365);
+        assert_eq!(max_age_days(&config, &Language::Python, 9.5), 540);
+        assert_eq!(max_age_days(&config, &Language::Node, 9.5), 90);
+    }
+
+    #[test]
+    fn test_staleness() {
+        let config = ReviewConfig::default();
+        let today = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+
+        assert_eq!(staleness(&PackageRecord { last_reviewed: "2026-01-01".to_string(), ..record("left-pad", "1.3.0") }, &Language::Rust, &config, today), None);
+        let stale = staleness(&PackageRecord { last_reviewed: "2025-01-01".to_string(), ..record("left-pad", "1.3.0") }, &Language::Rust, &config, today).unwrap();
+        assert_eq!((stale.age_days, stale.max_age_days), (Some(516), 365));
+        let invalid = staleness(&PackageRecord { last_reviewed: "not a date".to_string(), ..record("left-pad", "1.3.0") }, &Language::Rust, &config, today).unwrap();
+        assert_eq!(invalid.to_string(), "review date is not a valid date (max 365 days)");
+    }
+}
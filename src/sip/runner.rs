@@ -1,10 +1,38 @@
-use crate::cli::{self, Commands, Language};
-use crate::sip::registry::Registry;
-use crate::sip::package::PackageRecord;
-use crate::sip::fetch::fetch_from_pypi;
-use std::path::Path;
+use crate::cli::{self, Commands, Language, OutputFormat, RegistryCommands, SbomFormat};
+use crate::sip::audit::{self, AuditEvent, Decision, LogFilter};
+use crate::sip::config::{Config, StaleAction};
+use crate::sip::paths::log_dir;
+use crate::sip::registry::data_file;
+use crate::sip::registry_audit;
+use crate::sip::registry_diff;
+use crate::sip::package_diff::{self, FileChange};
+use crate::sip::provenance::{self, ProvenanceStatus, TrustedRoot};
+use crate::sip::registry_lint::{self, Severity};
+use crate::sip::license::{self, LicenseCheck};
+use crate::sip::review;
+use crate::sip::sbom::{self, Component, Verdict};
+use crate::sip::scan::{self, InstallScan, ScanFinding};
+use crate::sip::source_check;
+use crate::sip::upstream::{self, OwnershipChange};
+use crate::sip::registry::{Registry, PERSONAL_LAYER};
+use crate::sip::normalize::{normalize_name, similar_names};
+use crate::sip::prompt::{self, ReviewChoice, ReviewSubject};
+use crate::sip::policy::{self, Policy, PolicyVerdict};
+use crate::sip::package::{BlockedRecord, PackageRecord};
+use crate::sip::verify::{verify_package, Yanked, DEFAULT_TRUST_THRESHOLD};
+use crate::sip::fetch::{
+    check_digest, download_artifact, unpack_artifact, Artifact, fetch_from_pypi, fetch_from_crates, fetch_from_npm, fetch_from_maven, fetch_from_rubygems, fetch_from_packagist,
+};
+use crate::sip::detect::{detect_project, find_project_root, resolve_language};
+use crate::sip::manifest::{collect_dependencies, collect_resolved_dependencies, Dependency};
+use crate::sip::maven::{self, MavenCoordinate};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::fs;
-use std::process::{Command, Stdio};
+use std::fmt;
+use std::io::IsTerminal;
+use std::time::Duration;
+use std::process::{Command, ExitStatus, Stdio};
 use chrono::Utc;
 
 pub fn run() {
@@ -16,28 +44,79 @@ pub fn run() {
             version,
             lang,
             yes,
+            provenance,
             extra_args,
         } => {
-            handle_install(package, version, lang, yes, extra_args)
+            handle_install(package, version, lang, yes, provenance, extra_args)
         }
         Commands::Verify {
             package,
             version,
             lang,
+            provenance,
         } => {
-            handle_verify(package, version, lang)
+            handle_verify(package, version, lang, provenance)
         }
-        Commands::Trust { package, version, lang, fetch, score } => {
-            handle_trust(package, version, lang, fetch, Some(score))
+        Commands::Scan { package, version, lang, format } => {
+            handle_scan(package, version, lang, format)
         }
-        Commands::Untrust { package, lang } => {
-            handle_untrust(package, lang)
+        Commands::Diff { package, old, new, lang, stat, format } => {
+            handle_diff(package, old, new, lang, stat, format)
         }
-        Commands::List { lang } => {
-            handle_list(lang)
+        Commands::Trust { package, version, lang, fetch, score, layer } => {
+            handle_trust(package, version, lang, fetch, Some(score), layer)
         }
-        Commands::BulkTrust { file, lang, score } => {
-            handle_bulk_trust(file, lang, Some(score))
+        Commands::Untrust { package, lang, layer } => {
+            handle_untrust(package, lang, layer)
+        }
+        Commands::Block { package, reason, versions, reference, lang, layer } => {
+            handle_block(package, reason, versions, reference, lang, layer)
+        }
+        Commands::Unblock { package, lang, layer } => {
+            handle_unblock(package, lang, layer)
+        }
+        Commands::List { lang, layer, blocked } => {
+            if blocked {
+                handle_list_blocked(lang, layer)
+            } else {
+                handle_list(lang, layer)
+            }
+        }
+        Commands::Vet { path, lang, sbom, locked } => {
+            handle_vet(path, lang, sbom, locked)
+        }
+        Commands::Licenses { path, lang, fetch, format } => {
+            handle_licenses(path, lang, fetch, format)
+        }
+        Commands::Sbom { path, lang, format, output } => {
+            handle_sbom(path, lang, format, output)
+        }
+        Commands::Review { stale, lang } => {
+            handle_review(stale, lang)
+        }
+        Commands::Log { package, lang, command, decision, since, limit, json } => {
+            let filter = LogFilter {
+                package,
+                language: lang.map(|l| l.to_string()),
+                command,
+                decision,
+                since,
+            };
+            handle_log(filter, limit, json)
+        }
+        Commands::Registry { command } => match command {
+            RegistryCommands::Diff { old, new, lang, format } => {
+                handle_registry_diff(old, new, lang, format)
+            }
+            RegistryCommands::Lint { path, lang, format } => {
+                handle_registry_lint(path, lang, format)
+            }
+            RegistryCommands::Audit { path, lang, fix, behind, sources, format } => {
+                handle_registry_audit(path, lang, fix, behind, sources, format)
+            }
+        },
+        Commands::BulkTrust { file, lang, score, layer } => {
+            handle_bulk_trust(file, lang, Some(score), layer)
         }
     };
 
@@ -48,269 +127,1500 @@ pub fn run() {
 }
 
 fn load_registry() -> Result<Registry, Box<dyn std::error::Error>> {
-    // registry data is now embedded in the binary - completely portable!
     Registry::new()
 }
 
-fn detect_language() -> Option<Language> {
-    // check for language-specific files in current directory
-    if Path::new("Cargo.toml").exists() {
-        return Some(Language::Rust);
-    }
+fn handle_install(
+    package: String,
+    version: Option<String>,
+    lang: Option<Language>,
+    yes: bool,
+    require_provenance: bool,
+    extra_args: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("installing package: {}", package);
+    if let Some(version) = &version {
+        println!("version: {}", version);
+    }
+    
+    // load registry and check if package is trusted
+    let registry = load_registry()?;
+    let language = resolve_language(lang, Some(&package), Some(&registry), !yes)?;
+    println!("language: {}", language);
+    
+    let event = AuditEvent {
+        version: version.clone(),
+        language: Some(language.to_string()),
+        ..AuditEvent::new("install", &package, Decision::Allowed)
+    };
+    
+    // blocked packages are never installed, not even with --yes
+    if let Some(blocked) = registry.lookup_block(&package, version.as_deref(), &language)? {
+        println!("✘ package '{}' is blocked ({} registry)", package, blocked.layer);
+        println!("  reason: {}", blocked.record.reason);
+        if let Some(reference) = &blocked.record.reference {
+            println!("  reference: {}", reference);
+        }
+        audit::record(AuditEvent {
+            layer: Some(blocked.layer.to_string()),
+            decision: Decision::Refused,
+            ..event
+        });
+        return Err(format!("refusing to install blocked package '{}'", package).into());
+    }
+    
+    // organization policy is checked before anything is downloaded or asked
+    let config = Config::load()?;
+    let policy = Policy::load(&config)?;
+    let trusted = registry.lookup_package(&package, &language)?;
+    // without a record there is no reviewed license, so the license lists judge what upstream says
+    let upstream_license = if trusted.is_none() && policy.licenses.is_configured() {
+        let license = upstream_license(&package, version.as_deref(), &language);
+        println!("license: {}", license.as_deref().unwrap_or("unknown"));
+        license
+    } else {
+        None
+    };
+    let verdict = policy.evaluate(&policy::Subject {
+        name: &package,
+        language: &language,
+        record: trusted.map(|trusted| trusted.record),
+        scan: None,
+        license: upstream_license.as_deref(),
+    });
+    for matched in verdict.matches() {
+        println!("{} {}", matched.marker(), matched);
+    }
+    if let Some(decision) = verdict.decision.as_ref().filter(|_| verdict.denied()) {
+        audit::record(AuditEvent {
+            record: trusted.map(|trusted| trusted.record.clone()),
+            layer: trusted.map(|trusted| trusted.layer.to_string()),
+            decision: Decision::Refused,
+            ..event
+        });
+        return Err(format!("refusing to install '{}': {}", package, decision).into());
+    }
+    
+    if let Some(trusted) = trusted {
+        let trusted_package = trusted.record;
+        println!("✓ package '{}' is trusted ({} registry)", package, trusted.layer);
+        println!("  version: {}", trusted_package.version);
+        println!("  trust score: {:.1}", trusted_package.trust_score);
+        println!("  endorsed by: {}", trusted_package.endorsed_by.join(", "));
+        println!("  last reviewed: {}", trusted_package.last_reviewed);
+        
+        let review_config = config.review.unwrap_or_default();
+        if let Some(stale) = review::staleness(trusted_package, &language, &review_config, review::today()) {
+            if review_config.on_stale == StaleAction::Block && !verdict.allowed() {
+                println!("✘ review expired: {}", stale);
+                audit::record(AuditEvent {
+                    record: Some(trusted_package.clone()),
+                    layer: Some(trusted.layer.to_string()),
+                    decision: Decision::Refused,
+                    ..event
+                });
+                return Err(format!("refusing to install '{}' until it is re-reviewed (see 'sip review --stale')", package).into());
+            }
+            println!("⚠ review is stale: {}", stale);
+        }
+        
+        let target = version.as_deref().unwrap_or(&trusted_package.version);
+        let yanked = Yanked::from_record(trusted_package)
+            .filter(|yanked| yanked.version == target)
+            .or_else(|| upstream_yank(&package, target, &language));
+        if let Some(yanked) = yanked {
+            println!("⚠ {}", yanked);
+        }
+        let checked = show_install_scan(&package, version.as_deref(), Some(trusted_package), &language).and_then(|scanned| {
+            let verdict = policy_after_scan(&policy, &package, &language, Some(trusted_package), None, scanned.as_deref(), &verdict)?;
+            if require_provenance {
+                show_provenance(&package, Some(target), Some(trusted_package), &language)?;
+            }
+            Ok(verdict)
+        });
+        let verdict = match checked {
+            Ok(verdict) => verdict,
+            Err(e) => {
+                audit::record(AuditEvent {
+                    record: Some(trusted_package.clone()),
+                    layer: Some(trusted.layer.to_string()),
+                    decision: Decision::Refused,
+                    ..event
+                });
+                return Err(format!("refusing to install '{}': {}", package, e).into());
+            }
+        };
+        
+        // a low score gets the same review as an unknown package; scripted installs of trusted
+        // packages are not interrupted
+        let mut review = None;
+        if trusted_package.trust_score < DEFAULT_TRUST_THRESHOLD && !verdict.allowed() && !yes && std::io::stdin().is_terminal() {
+            println!("⚠ trust score {:.1} is below the threshold of {:.1}", trusted_package.trust_score, DEFAULT_TRUST_THRESHOLD);
+            let (choice, trustable) = review_before_install(&registry, &package, Some(target), Some(trusted_package), &language)?;
+            if choice == ReviewChoice::Abort {
+                println!("installation cancelled");
+                audit::record(AuditEvent {
+                    record: Some(trusted_package.clone()),
+                    layer: Some(trusted.layer.to_string()),
+                    decision: Decision::Cancelled,
+                    review: Some(choice),
+                    ..event
+                });
+                return Ok(());
+            }
+            review = Some((choice, trustable));
+        }
+        
+        // proceed with installation
+        let result = install_package(&package, &version, &language, &extra_args);
+        audit::record(AuditEvent {
+            record: Some(trusted_package.clone()),
+            layer: Some(trusted.layer.to_string()),
+            overridden: review.is_some(),
+            review: review.as_ref().map(|(choice, _)| choice.clone()),
+            exit_status: installer_exit_status(&result),
+            ..event
+        });
+        result?;
+        if let Some((ReviewChoice::InstallAndTrust { score }, Some(record))) = review {
+            trust_locally(&package, record, score, &language)?;
+        }
+    } else {
+        println!("⚠ package '{}' is not in trusted registry", package);
+        println!("consider using 'sip trust {}' to add it to your trusted packages", package);
+        if let Some(yanked) = version.as_deref().and_then(|v| upstream_yank(&package, v, &language)) {
+            println!("⚠ {}", yanked);
+        }
+        let checked = show_install_scan(&package, version.as_deref(), None, &language).and_then(|scanned| {
+            let verdict = policy_after_scan(&policy, &package, &language, None, upstream_license.as_deref(), scanned.as_deref(), &verdict)?;
+            if require_provenance {
+                show_provenance(&package, version.as_deref(), None, &language)?;
+            }
+            Ok(verdict)
+        });
+        let verdict = match checked {
+            Ok(verdict) => verdict,
+            Err(e) => {
+                audit::record(AuditEvent { decision: Decision::Refused, ..event });
+                return Err(format!("refusing to install '{}': {}", package, e).into());
+            }
+        };
+        
+        let mut review = None;
+        if !yes && !verdict.allowed() {
+            let (choice, trustable) = review_before_install(&registry, &package, version.as_deref(), None, &language)?;
+            if choice == ReviewChoice::Abort {
+                println!("installation cancelled");
+                audit::record(AuditEvent { decision: Decision::Cancelled, review: Some(choice), ..event });
+                return Ok(());
+            }
+            review = Some((choice, trustable));
+        }
+        
+        println!("proceeding with untrusted installation...");
+        let result = install_package(&package, &version, &language, &extra_args);
+        audit::record(AuditEvent {
+            overridden: !verdict.allowed(),
+            review: review.as_ref().map(|(choice, _)| choice.clone()),
+            exit_status: installer_exit_status(&result),
+            ..event
+        });
+        result?;
+        if let Some((ReviewChoice::InstallAndTrust { score }, Some(record))) = review {
+            trust_locally(&package, record, score, &language)?;
+        }
+    }
+    
+    Ok(())
+}
+
+// the review shown before installing a package sip cannot vouch for; alongside the choice it
+// returns the record the user may trust, which has to describe the version being installed
+fn review_before_install(
+    registry: &Registry,
+    package: &str,
+    version: Option<&str>,
+    record: Option<&PackageRecord>,
+    language: &Language,
+) -> Result<(ReviewChoice, Option<PackageRecord>), Box<dyn std::error::Error>> {
+    let upstream = tokio::runtime::Runtime::new()
+        .ok()
+        .and_then(|runtime| fetch_package_metadata(&runtime, package, version, language).ok());
+    let trusted = registry.list_packages(Some(language), None)?;
+    let similar = similar_names(package, trusted.iter().map(|t| t.record.name.as_str()), language);
+    let trustable = upstream
+        .clone()
+        .filter(|upstream| version.is_none_or(|v| v == upstream.version))
+        .or_else(|| record.cloned());
+    
+    let choice = prompt::review_package(&ReviewSubject {
+        package,
+        record,
+        upstream: upstream.as_ref(),
+        similar: &similar,
+        can_trust: trustable.is_some(),
+    })?;
+    Ok((choice, trustable))
+}
+
+// "install and trust locally" records the package in the personal registry once it installed
+fn trust_locally(package: &str, mut record: PackageRecord, score: f64, language: &Language) -> Result<(), Box<dyn std::error::Error>> {
+    // the same refusal as `sip trust --fetch`: installing a withdrawn release once is the user's
+    // call, pinning it for every later install is not
+    if let Some(yanked) = Yanked::from_record(&record) {
+        println!("✘ {}", yanked);
+        return Err(format!("refusing to trust yanked release {} {}", package, record.version).into());
+    }
+    record.trust_score = score;
+    record.last_reviewed = Utc::now().date_naive().to_string();
+    if !record.endorsed_by.iter().any(|e| e == "user") {
+        record.endorsed_by.push("user".to_string());
+    }
+    
+    let mut registry = Registry::load_mutable()?;
+    registry.add_package(record.clone(), language, PERSONAL_LAYER)?;
+    registry.save_to_disk()?;
+    audit::record(AuditEvent {
+        version: Some(record.version.clone()),
+        language: Some(language.to_string()),
+        record: Some(record),
+        layer: Some(PERSONAL_LAYER.to_string()),
+        ..AuditEvent::new("trust", package, Decision::Added)
+    });
+    println!("✓ added '{}' to {} registry with trust score {:.1}", package, PERSONAL_LAYER, score);
+    Ok(())
+}
+
+fn handle_verify(
+    package: String, 
+    version: Option<String>, 
+    lang: Option<Language>,
+    check_provenance: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("verifying package: {}", package);
+    if let Some(version) = &version {
+        println!("version: {}", version);
+    }
+    
+    let registry = load_registry()?;
+    let language = resolve_language(lang, Some(&package), Some(&registry), true)?;
+    println!("language: {}", language);
+    let config = Config::load()?;
+    let policy = Policy::load(&config)?;
+    let review_config = config.review.unwrap_or_default();
+    // verify reports registry membership and whether the review is still current, install and
+    // vet are the ones holding scores to a threshold
+    let mut result = verify_package(&package, version.as_deref(), &language, &registry, 0.0, &review_config, &policy)?;
+    if result.yanked.is_none()
+        && let Some(target) = version.as_deref().or(result.package_entry.as_ref().map(|entry| entry.version.as_str()))
+    {
+        result.yanked = upstream_yank(&package, target, &language);
+    }
+    result.ownership = result.package_entry.as_ref().and_then(|entry| upstream_ownership_change(entry, &language));
+    result.display();
+    if check_provenance {
+        let target = version.as_deref().or(result.package_entry.as_ref().map(|entry| entry.version.as_str()));
+        // verify only reports, install --provenance is what refuses
+        let _ = show_provenance(&package, target, result.package_entry.as_ref(), &language);
+    }
+    
+    let decision = if result.blocked.is_some() {
+        Decision::Blocked
+    } else if result.is_trusted {
+        Decision::Trusted
+    } else {
+        Decision::Untrusted
+    };
+    audit::record(AuditEvent {
+        version,
+        language: Some(language.to_string()),
+        record: result.package_entry.clone(),
+        layer: result.layer.clone(),
+        ..AuditEvent::new("verify", &package, decision)
+    });
+    
+    Ok(())
+}
+
+fn handle_trust(
+    package: String, 
+    version: Option<String>, 
+    lang: Option<Language>, 
+    fetch: bool, 
+    score: Option<f64>,
+    layer: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut registry = Registry::load_mutable()?;
+    let language = resolve_language(lang, Some(&package), Some(&registry), true)?;
+    
+    let package_entry = if fetch && language != Language::Go {
+        // fetch metadata from the upstream registry
+        let runtime = tokio::runtime::Runtime::new()?;
+        let mut fetched_entry = fetch_package_metadata(&runtime, &package, version.as_deref(), &language)?;
+        registry.validate_record(&fetched_entry)?;
+        
+        // pinning a withdrawn release would trust exactly what upstream told people to avoid
+        if let Some(yanked) = Yanked::from_record(&fetched_entry) {
+            println!("✘ {}", yanked);
+            return Err(format!("refusing to trust yanked release {} {}", package, fetched_entry.version).into());
+        }
+        
+        // the scan is saved with the review so later installs show what the reviewer saw
+        if scan::supports(&language) {
+            println!("scanning install-time code...");
+            let client = upstream::client()?;
+            match runtime.block_on(scan::scan_package(&client, &package, Some(&fetched_entry.version), &language)) {
+                Ok((artifact, findings)) => {
+                    print_scan_findings(&findings);
+                    fetched_entry.install_scan = Some(InstallScan {
+                        scanned_on: Utc::now().date_naive().to_string(),
+                        findings,
+                    });
+                    check_fetched_source(&mut fetched_entry, &artifact, &language);
+                }
+                Err(e) => println!("⚠ could not scan install-time code: {}", e),
+            }
+        }
+        
+        // override trust score if provided
+        if let Some(trust_score) = score {
+            fetched_entry.trust_score = trust_score;
+        }
+        
+        // add user endorsement
+        fetched_entry.endorsed_by.push("user".to_string());
+        
+        fetched_entry
+    } else {
+        // manual entry
+        let (name, version) = if language == Language::Java {
+            let coord = MavenCoordinate::parse(&package)?;
+            (coord.name(), version.or(coord.version))
+        } else {
+            (package.clone(), version)
+        };
+        let version = version.unwrap_or_else(|| "1.0.0".to_string());
+        let trust_score = score.unwrap_or(5.0);
+        // go modules cannot be fetched, but their license can be read from the module zip
+        let license = if fetch && language == Language::Go {
+            upstream::client()
+                .ok()
+                .and_then(|client| upstream_best_effort(SCAN_TIMEOUT, upstream::fetch_license(&client, &name, Some(&version), &language)))
+        } else {
+            None
+        };
+        
+        PackageRecord {
+            name,
+            version,
+            hash: "sha256:manual".to_string(),
+            trust_score,
+            endorsed_by: vec!["user".to_string()],
+            last_reviewed: Utc::now().format("%Y-%m-%d").to_string(),
+            source: "https://manual.entry".to_string(),
+            // go modules are owned by their repository, which the module path names
+            maintainers: if language == Language::Go { upstream::repository_owner(&package).into_iter().collect() } else { Vec::new() },
+            license,
+            ..Default::default()
+        }
+    };
+    
+    let event = AuditEvent {
+        version: Some(package_entry.version.clone()),
+        language: Some(language.to_string()),
+        record: Some(package_entry.clone()),
+        layer: Some(layer.clone()),
+        ..AuditEvent::new("trust", &package, Decision::Added)
+    };
+    
+    registry.add_package(package_entry, &language, &layer)?;
+    registry.save_to_disk()?;
+    audit::record(event);
+    
+    println!("✓ added '{}' to {} registry of trusted {} packages", package, layer, match language {
+        Language::Python => "python",
+        Language::Rust => "rust", 
+        Language::Go => "go",
+        Language::Node => "node",
+        Language::Java => "java",
+        Language::Ruby => "ruby",
+        Language::Php => "php",
+    });
+    
+    Ok(())
+}
+
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(10);
+// the artifact's metadata is the best evidence of where a package lives: a fetched homepage gives
+// way to the one repository the package declares, a different repository is left to the reviewer
+fn check_fetched_source(record: &mut PackageRecord, artifact: &Artifact, language: &Language) {
+    let Ok(files) = unpack_artifact(&artifact.bytes) else {
+        return;
+    };
+    // registries without license metadata still ship the LICENSE file
+    if record.license.is_none() {
+        record.license = license::license_from_files(&files);
+    }
+    let declared = source_check::declared_repositories(&files, language);
+    if !source_check::is_repository_url(&record.source) && declared.len() == 1 {
+        println!("source: {} (declared by the package)", declared[0]);
+        record.source = declared[0].clone();
+    } else if !source_check::source_agrees(&record.source, &declared) {
+        println!("⚠ source {} is not the repository the package declares: {}", record.source, declared.join(", "));
+    } else if !source_check::is_repository_url(&record.source) {
+        println!("⚠ source {} is not a source repository url", record.source);
+    }
+}
+
+// downloading an artifact to scan takes longer than a metadata lookup
+const SCAN_TIMEOUT: Duration = Duration::from_secs(60);
+
+// upstream lookups made by install and verify are best-effort: unsupported ecosystems and
+// unreachable registries are skipped rather than holding up the command
+fn upstream_best_effort<T>(timeout: Duration, lookup: impl Future<Output = Result<Option<T>, Box<dyn std::error::Error>>>) -> Option<T> {
+    let runtime = tokio::runtime::Runtime::new().ok()?;
+    // the timer needs the runtime's reactor, so it is created inside block_on
+    runtime.block_on(async { tokio::time::timeout(timeout, lookup).await }).ok()?.ok()?
+}
+
+fn upstream_yank(package: &str, version: &str, language: &Language) -> Option<Yanked> {
+    if !upstream::supports(language) {
+        return None;
+    }
+    let client = upstream::client().ok()?;
+    let release = upstream_best_effort(UPSTREAM_TIMEOUT, upstream::fetch_release(&client, package, version, language))?;
+    release.yanked.then(|| Yanked {
+        version: version.to_string(),
+        reason: release.yank_reason,
+    })
+}
+
+fn upstream_license(package: &str, version: Option<&str>, language: &Language) -> Option<String> {
+    if !upstream::supports(language) {
+        return None;
+    }
+    let client = upstream::client().ok()?;
+    upstream_best_effort(UPSTREAM_TIMEOUT, upstream::fetch_license(&client, package, version, language))
+}
+
+fn upstream_ownership_change(record: &PackageRecord, language: &Language) -> Option<OwnershipChange> {
+    if record.maintainers.is_empty() || !upstream::supports(language) {
+        return None;
+    }
+    let client = upstream::client().ok()?;
+    let current = upstream_best_effort(UPSTREAM_TIMEOUT, upstream::fetch_maintainers(&client, &record.name, language))?;
+    OwnershipChange::between(&record.maintainers, &current)
+}
+
+fn print_scan_findings(findings: &[ScanFinding]) {
+    if findings.is_empty() {
+        println!("✓ no risky install-time patterns found");
+        return;
+    }
+    println!("⚠ {} risky install-time pattern(s):", findings.len());
+    for finding in findings {
+        println!("  {}", finding);
+    }
+}
+
+// shows the scan saved with the review when it covers the version being installed, otherwise
+// scans the artifact on the spot and returns those findings for policy to take again; only an
+// artifact whose digest differs from the reviewed one is an error, not being able to scan is not
+fn show_install_scan(package: &str, version: Option<&str>, record: Option<&PackageRecord>, language: &Language) -> Result<Option<Vec<ScanFinding>>, String> {
+    if let Some(record) = record
+        && let Some(saved) = &record.install_scan
+        && version.is_none_or(|v| v == record.version)
+    {
+        println!("install-time scan from review on {}:", saved.scanned_on);
+        print_scan_findings(&saved.findings);
+        return Ok(None);
+    }
+    if !scan::supports(language) {
+        return Ok(None);
+    }
+    let Ok(client) = upstream::client() else {
+        return Ok(None);
+    };
+
+    println!("scanning install-time code...");
+    let scanned = upstream_best_effort(SCAN_TIMEOUT, async { scan::scan_package(&client, package, version, language).await.map(Some) });
+    let Some((artifact, findings)) = scanned else {
+        println!("⚠ could not download {} to scan its install-time code", package);
+        return Ok(None);
+    };
+    if let Some(record) = record.filter(|r| r.version == artifact.version) {
+        check_digest(&artifact.bytes, &record.hash)?;
+    }
+    print_scan_findings(&findings);
+    Ok(Some(findings))
+}
+
+// the first verdict only saw the scan saved with the review, risk rules are taken again on what
+// the artifact served now contains
+fn policy_after_scan(
+    policy: &Policy,
+    package: &str,
+    language: &Language,
+    record: Option<&PackageRecord>,
+    license: Option<&str>,
+    findings: Option<&[ScanFinding]>,
+    before: &PolicyVerdict,
+) -> Result<PolicyVerdict, String> {
+    let Some(findings) = findings else {
+        return Ok(before.clone());
+    };
+    let verdict = policy.evaluate(&policy::Subject { name: package, language, record, scan: Some(findings), license });
+    for matched in verdict.matches().filter(|m| !before.matches().any(|b| b == *m)) {
+        println!("{} {}", matched.marker(), matched);
+    }
+    match verdict.decision.as_ref().filter(|_| verdict.denied()) {
+        Some(decision) => Err(decision.to_string()),
+        None => Ok(verdict),
+    }
+}
+
+// provenance of the artifact upstream serves for the version, checked against the pinned
+// sigstore trusted root; a missing trusted root is an error rather than a skipped check
+fn check_provenance(package: &str, version: Option<&str>, language: &Language) -> Result<(String, ProvenanceStatus), Box<dyn std::error::Error>> {
+    if !provenance::supports(language) {
+        return Err(format!("provenance checks are not supported for {} yet", language).into());
+    }
+    let config = Config::load()?.provenance.unwrap_or_default();
+    let root = TrustedRoot::load(&config.trusted_root_path())?;
+    let client = upstream::client()?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let artifact = download_artifact(&client, package, version, language).await?;
+        let status = provenance::check_provenance(&client, package, &artifact, language, &root).await?;
+        Ok((artifact.version, status))
+    })
+}
+
+// prints what the attestation says; the error explains why it cannot be relied on
+fn show_provenance(package: &str, version: Option<&str>, record: Option<&PackageRecord>, language: &Language) -> Result<(), String> {
+    println!("checking provenance...");
+    let (version, status) = check_provenance(package, version, language).map_err(|e| {
+        println!("✘ {}", e);
+        e.to_string()
+    })?;
+    match status {
+        ProvenanceStatus::Verified(attested) => {
+            println!("✓ {} {} built from {}", package, version, attested.repository);
+            if let Some(workflow) = &attested.workflow {
+                println!("  workflow: {}", workflow);
+            }
+            if let Some(git_ref) = &attested.git_ref {
+                println!("  ref: {}", git_ref);
+            }
+            println!("  transparency log index: {}", attested.log_index);
+            match record.filter(|r| r.version == version) {
+                Some(record) if !provenance::source_matches(&attested, &record.source) => {
+                    println!("✘ attested repository does not match the trusted source {}", record.source);
+                    Err(format!("{} was built from {}, not {}", version, attested.repository, record.source))
+                }
+                _ => Ok(()),
+            }
+        }
+        ProvenanceStatus::Missing => {
+            println!("⚠ no provenance attestation is published for {} {}", package, version);
+            Err(format!("{} has no provenance attestation", version))
+        }
+        ProvenanceStatus::Invalid(reason) => {
+            println!("✘ provenance attestation does not verify: {}", reason);
+            Err(format!("provenance attestation for {} does not verify", version))
+        }
+    }
+}
+
+fn handle_scan(package: String, version: Option<String>, lang: Option<Language>, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = load_registry()?;
+    let language = resolve_language(lang, Some(&package), Some(&registry), true)?;
+    if !scan::supports(&language) {
+        return Err(format!("install-time scanning is not supported for {} yet", language).into());
+    }
+    
+    let client = upstream::client()?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let (artifact, findings) = runtime.block_on(scan::scan_package(&client, &package, version.as_deref(), &language))?;
+    
+    // an artifact that differs from the reviewed one makes the findings moot
+    if let Some(record) = registry.lookup_version(&package, &artifact.version, &language)? {
+        check_digest(&artifact.bytes, &record.record.hash)?;
+    }
+    
+    match format {
+        OutputFormat::Json => {
+            let report = serde_json::json!({
+                "package": package,
+                "version": artifact.version,
+                "language": language.to_string(),
+                "url": artifact.url,
+                "findings": findings,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::Text => {
+            println!("scanned {} {} ({})", package, artifact.version, artifact.url);
+            print_scan_findings(&findings);
+        }
+    }
+    Ok(())
+}
+
+// the artifact must match the reviewed record when there is one, and otherwise one of the
+// hashes upstream publishes for the version
+fn verify_artifact(
+    runtime: &tokio::runtime::Runtime,
+    client: &reqwest::Client,
+    registry: &Registry,
+    package: &str,
+    artifact: &Artifact,
+    language: &Language,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    if let Some(record) = registry.lookup_version(package, &artifact.version, language)?
+        && check_digest(&artifact.bytes, &record.record.hash).map_err(|e| format!("{} {} does not match the reviewed record: {}", package, artifact.version, e))?
+    {
+        return Ok(Some(record.record.hash.clone()));
+    }
+
+    let release = runtime.block_on(upstream::fetch_release(client, package, &artifact.version, language))?;
+    let hashes = release.map(|r| r.hashes).unwrap_or_default();
+    let mut mismatch = None;
+    for hash in &hashes {
+        match check_digest(&artifact.bytes, hash) {
+            Ok(true) => return Ok(Some(hash.clone())),
+            Ok(false) => {}
+            // pypi lists a hash per file, only one of them is the sdist
+            Err(e) => mismatch = Some(e),
+        }
+    }
+    match mismatch {
+        Some(e) => Err(format!("{} {} does not match any hash upstream publishes: {}", package, artifact.version, e).into()),
+        None => Ok(None),
+    }
+}
+
+fn handle_diff(
+    package: String,
+    old: String,
+    new: String,
+    lang: Option<Language>,
+    stat: bool,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = load_registry()?;
+    let language = resolve_language(lang, Some(&package), Some(&registry), true)?;
+    if !upstream::supports(&language) {
+        return Err(format!("artifact diffs are not supported for {} yet", language).into());
+    }
+    let json = matches!(format, OutputFormat::Json);
+    
+    let client = upstream::client()?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let mut sides = Vec::new();
+    for version in [&old, &new] {
+        if !json {
+            println!("downloading {} {}...", package, version);
+        }
+        let artifact = runtime.block_on(download_artifact(&client, &package, Some(version), &language))?;
+        let verified = verify_artifact(&runtime, &client, &registry, &package, &artifact, &language)?;
+        if !json {
+            match &verified {
+                Some(hash) => println!("✓ {} {} matches {}", package, artifact.version, hash),
+                None => println!("⚠ {} {} could not be checked against a published hash", package, artifact.version),
+            }
+        }
+        let files = unpack_artifact(&artifact.bytes)?;
+        sides.push((artifact, verified, files));
+    }
+    let (old_artifact, old_hash, old_files) = &sides[0];
+    let (new_artifact, new_hash, new_files) = &sides[1];
+    
+    let diffs = package_diff::diff_files(old_files, new_files, &language);
+    let findings = package_diff::new_findings(old_files, new_files, &language);
+    
+    if json {
+        let side = |artifact: &Artifact, hash: &Option<String>| serde_json::json!({
+            "version": artifact.version,
+            "url": artifact.url,
+            "verified_hash": hash,
+        });
+        let report = serde_json::json!({
+            "package": package,
+            "language": language.to_string(),
+            "old": side(old_artifact, old_hash),
+            "new": side(new_artifact, new_hash),
+            "files": diffs,
+            "new_findings": findings,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    
+    let count = |change: FileChange| diffs.iter().filter(|d| d.change == change).count();
+    println!();
+    println!(
+        "{} -> {}: {} added, {} removed, {} modified",
+        old_artifact.version,
+        new_artifact.version,
+        count(FileChange::Added),
+        count(FileChange::Removed),
+        count(FileChange::Modified)
+    );
+    for diff in &diffs {
+        println!("  {}", diff);
+    }
+    
+    if !findings.is_empty() {
+        println!();
+        println!("⚠ {} new risky install-time pattern(s):", findings.len());
+        for finding in &findings {
+            println!("  {}", finding);
+        }
+    }
+    
+    if !stat {
+        for diff in diffs.iter().filter(|d| !d.binary) {
+            let before = old_files.get(&diff.path).map(Vec::as_slice);
+            let after = new_files.get(&diff.path).map(Vec::as_slice);
+            if let Some(patch) = package_diff::unified_diff(&diff.path, before, after) {
+                println!();
+                print!("{}", patch);
+            }
+        }
+    }
+    Ok(())
+}
+
+// fetch current metadata for a package from its upstream registry
+fn fetch_package_metadata(
+    runtime: &tokio::runtime::Runtime,
+    package: &str,
+    version: Option<&str>,
+    language: &Language,
+) -> Result<PackageRecord, Box<dyn std::error::Error>> {
+    let record = match language {
+        Language::Python => {
+            println!("fetching metadata for '{}' from pypi...", package);
+            runtime.block_on(fetch_from_pypi(package))?
+        }
+        Language::Rust => {
+            println!("fetching metadata for '{}' from crates.io...", package);
+            runtime.block_on(fetch_from_crates(package))?
+        }
+        Language::Node => {
+            println!("fetching metadata for '{}' from npm...", package);
+            runtime.block_on(fetch_from_npm(package))?
+        }
+        Language::Java => {
+            let repository = maven::default_repository();
+            let spec = match version {
+                Some(v) => format!("{}:{}", MavenCoordinate::parse(package)?.name(), v),
+                None => package.to_string(),
+            };
+            println!("fetching metadata for '{}' from {}...", spec, repository);
+            runtime.block_on(fetch_from_maven(&spec, &repository))?
+        }
+        Language::Ruby => {
+            println!("fetching metadata for '{}' from rubygems.org...", package);
+            runtime.block_on(fetch_from_rubygems(package))?
+        }
+        Language::Php => {
+            println!("fetching metadata for '{}' from packagist...", package);
+            runtime.block_on(fetch_from_packagist(package))?
+        }
+        Language::Go => return Err("go modules cannot be fetched yet - trust them manually without --fetch".into()),
+    };
+    
+    Ok(record)
+}
+
+fn handle_untrust(package: String, lang: Option<Language>, layer: String) -> Result<(), Box<dyn std::error::Error>> {
+    let mut registry = Registry::load_mutable()?;
+    let language = resolve_language(lang, Some(&package), Some(&registry), true)?;
+    
+    if registry.remove_package(&package, &language, &layer)? {
+        registry.save_to_disk()?;
+        audit::record(AuditEvent {
+            language: Some(language.to_string()),
+            layer: Some(layer.clone()),
+            ..AuditEvent::new("untrust", &package, Decision::Removed)
+        });
+        println!("✓ removed '{}' from {} registry of trusted {} packages", package, layer, match language {
+            Language::Python => "python",
+            Language::Rust => "rust",
+            Language::Go => "go",
+            Language::Node => "node",
+            Language::Java => "java",
+            Language::Ruby => "ruby",
+            Language::Php => "php",
+        });
+    } else {
+        println!("⚠ package '{}' was not found in {} registry of trusted {} packages", package, layer, match language {
+            Language::Python => "python", 
+            Language::Rust => "rust",
+            Language::Go => "go",
+            Language::Node => "node",
+            Language::Java => "java",
+            Language::Ruby => "ruby",
+            Language::Php => "php",
+        });
+    }
+    
+    Ok(())
+}
+
+fn handle_block(
+    package: String,
+    reason: String,
+    versions: Vec<String>,
+    reference: Option<String>,
+    lang: Option<Language>,
+    layer: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut registry = Registry::load_mutable()?;
+    let language = resolve_language(lang, Some(&package), Some(&registry), true)?;
+    
+    let name = if language == Language::Java {
+        MavenCoordinate::parse(&package)?.name()
+    } else {
+        package.clone()
+    };
+    
+    registry.block_package(BlockedRecord {
+        name,
+        versions,
+        reason,
+        reference,
+        blocked_on: Utc::now().format("%Y-%m-%d").to_string(),
+    }, &language, &layer)?;
+    registry.save_to_disk()?;
+    audit::record(AuditEvent {
+        language: Some(language.to_string()),
+        layer: Some(layer.clone()),
+        ..AuditEvent::new("block", &package, Decision::Added)
+    });
+    
+    println!("✓ blocked '{}' in {} registry of {} packages", package, layer, language);
+    Ok(())
+}
+
+fn handle_unblock(package: String, lang: Option<Language>, layer: String) -> Result<(), Box<dyn std::error::Error>> {
+    let mut registry = Registry::load_mutable()?;
+    let language = resolve_language(lang, Some(&package), Some(&registry), true)?;
+    
+    if registry.unblock_package(&package, &language, &layer)? {
+        registry.save_to_disk()?;
+        audit::record(AuditEvent {
+            language: Some(language.to_string()),
+            layer: Some(layer.clone()),
+            ..AuditEvent::new("unblock", &package, Decision::Removed)
+        });
+        println!("✓ removed '{}' from {} blocklist of {} packages", package, layer, language);
+    } else {
+        println!("⚠ package '{}' was not found in {} blocklist of {} packages", package, layer, language);
+    }
+    
+    // a block in another layer still applies
+    if let Some(blocked) = registry.lookup_block(&package, None, &language)? {
+        println!("⚠ '{}' is still blocked by the {} registry", package, blocked.layer);
+    }
+    
+    Ok(())
+}
+
+fn handle_list_blocked(lang: Option<Language>, layer: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = Registry::load_mutable()?;
+    let blocked = registry.list_blocked(lang.as_ref(), layer.as_deref())?;
+    
+    if blocked.is_empty() {
+        println!("no blocked packages found");
+        return Ok(());
+    }
+    
+    println!("blocked packages:");
+    for entry in blocked {
+        let versions = if entry.record.versions.is_empty() {
+            "all versions".to_string()
+        } else {
+            entry.record.versions.join(", ")
+        };
+        println!("  {} ({}) - {} [{}]", entry.record.name, versions, entry.record.reason, entry.layer);
+    }
+    
+    Ok(())
+}
+
+fn handle_list(lang: Option<Language>, layer: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = Registry::load_mutable()?;
+    
+    let packages = registry.list_packages(lang.as_ref(), layer.as_deref())?;
+    
+    if packages.is_empty() {
+        println!("no trusted packages found");
+        return Ok(());
+    }
+    
+    match lang {
+        Some(l) => println!("trusted {} packages:", match l {
+            Language::Python => "python",
+            Language::Rust => "rust", 
+            Language::Go => "go",
+            Language::Node => "node",
+            Language::Java => "java",
+            Language::Ruby => "ruby",
+            Language::Php => "php",
+        }),
+        None => println!("all trusted packages:"),
+    }
+    
+    // the merged view names the layer each package comes from
+    for package in packages {
+        println!("  {} {} (score: {:.1}) - {}{}", 
+            package.record.name, 
+            package.record.version, 
+            package.record.trust_score,
+            package.record.last_reviewed,
+            if layer.is_some() { String::new() } else { format!(" [{}]", package.layer) }
+        );
+    }
+    
+    Ok(())
+}
+
+fn handle_vet(path: String, lang: Option<Language>, sbom: Option<String>, locked: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let (dependencies, origin) = match sbom {
+        Some(file) => {
+            let content = fs::read_to_string(&file).map_err(|e| format!("cannot read {}: {}", file, e))?;
+            let (mut components, unsupported) = sbom::read_components(&content, &file)?;
+            components.retain(|c| lang.as_ref().is_none_or(|l| *l == c.language));
+            // components outside sip's ecosystems cannot be vetted, but should not go unnoticed
+            if !unsupported.is_empty() {
+                println!("⚠ {} component(s) of {} have no ecosystem sip can check: {}", unsupported.len(), file, unsupported.join(", "));
+            }
+            (components, file)
+        }
+        None => {
+            // from inside a project, vet the whole project rather than just the current directory
+            let dir = if path == "." {
+                let cwd = std::env::current_dir()?;
+                detect_project(&cwd).root.unwrap_or(cwd)
+            } else {
+                PathBuf::from(&path)
+            };
+            let dependencies = if locked {
+                collect_resolved_dependencies(&dir, lang.as_ref())?
+            } else {
+                collect_dependencies(&dir, lang.as_ref())?
+            };
+            (dependencies, dir.display().to_string())
+        }
+    };
+    
+    if dependencies.is_empty() {
+        println!("no dependencies found in {}", origin);
+        return Ok(());
+    }
+    
+    println!("vetting {} dependencies in {}", dependencies.len(), origin);
+    
+    let registry = load_registry()?;
+    let config = Config::load()?;
+    let policy = Policy::load(&config)?;
+    let review_config = config.review.unwrap_or_default();
+    let mut untrusted_count = 0;
+    let mut blocked_count = 0;
+    
+    for dep in &dependencies {
+        let requested = dep.version.as_deref().unwrap_or("*");
+        let result = verify_package(&dep.name, dep.pinned_version(), &dep.language, &registry, DEFAULT_TRUST_THRESHOLD, &review_config, &policy)?;
+        
+        if let Some(blocked) = &result.blocked {
+            blocked_count += 1;
+            match &blocked.reference {
+                Some(reference) => println!("  ✘ {} {} ({}) - blocked: {} ({})", dep.name, requested, dep.manifest, blocked.reason, reference),
+                None => println!("  ✘ {} {} ({}) - blocked: {}", dep.name, requested, dep.manifest, blocked.reason),
+            }
+            continue;
+        }
+        // a lockfile or sbom hash that contradicts the reviewed one is not the artifact that was reviewed,
+        // even when a policy rule would allow the package
+        if let Some(entry) = &result.package_entry
+            && let Some(hash) = &dep.hash
+            && dep.version.as_deref() == Some(entry.version.as_str())
+            && sbom::hash_conflict(hash, &entry.hash)
+        {
+            untrusted_count += 1;
+            println!("  ✘ {} {} ({}) - hash {} does not match the reviewed {}", dep.name, requested, dep.manifest, hash, entry.hash);
+            continue;
+        }
+        
+        for warning in &result.policy.warnings {
+            println!("  ⚠ {} {} ({}) - {}", dep.name, requested, dep.manifest, warning);
+        }
+        if let Some(decision) = &result.policy.decision {
+            let marker = if result.is_trusted { "✓" } else { "✘" };
+            if !result.is_trusted {
+                untrusted_count += 1;
+            }
+            println!("  {} {} {} ({}) - {}", marker, dep.name, requested, dep.manifest, decision);
+            continue;
+        }
+        
+        match &result.package_entry {
+            Some(entry) if result.is_trusted => {
+                match &result.stale {
+                    Some(stale) => println!("  ⚠ {} {} ({}) - trusted, score {:.1}, but review is stale: {}", dep.name, requested, dep.manifest, entry.trust_score, stale),
+                    None => println!("  ✓ {} {} ({}) - trusted, score {:.1}", dep.name, requested, dep.manifest, entry.trust_score),
+                }
+            }
+            Some(entry) if result.stale.is_some() && entry.trust_score >= DEFAULT_TRUST_THRESHOLD => {
+                untrusted_count += 1;
+                println!("  ✘ {} {} ({}) - review expired, last reviewed {}", dep.name, requested, dep.manifest, entry.last_reviewed);
+            }
+            Some(entry) => {
+                untrusted_count += 1;
+                println!("  ⚠ {} {} ({}) - below threshold, score {:.1}", dep.name, requested, dep.manifest, entry.trust_score);
+            }
+            None => {
+                untrusted_count += 1;
+                println!("  ✘ {} {} ({}) - not in trusted registry", dep.name, requested, dep.manifest);
+            }
+        }
+    }
+    
+    if blocked_count > 0 {
+        return Err(format!("{} blocked and {} untrusted of {} dependencies", blocked_count, untrusted_count, dependencies.len()).into());
+    }
+    
+    if untrusted_count > 0 {
+        return Err(format!("{} of {} dependencies are not trusted", untrusted_count, dependencies.len()).into());
+    }
+    
+    println!("✓ all {} dependencies are trusted", dependencies.len());
+    Ok(())
+}
+
+// the license of a dependency: the reviewed record's, else with --fetch what upstream publishes
+// for the declared version, or for the latest when that version is a range upstream does not know
+fn dependency_license(registry: &Registry, dep: &Dependency, fetch: Option<&reqwest::Client>) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let record = match dep.version.as_deref() {
+        Some(v) => match registry.lookup_version(&dep.name, v, &dep.language)? {
+            Some(found) => Some(found),
+            None => registry.lookup_package(&dep.name, &dep.language)?,
+        },
+        None => registry.lookup_package(&dep.name, &dep.language)?,
+    };
+    if let Some(license) = record.and_then(|r| r.record.license.clone()) {
+        return Ok(Some(license));
+    }
+    let Some(client) = fetch.filter(|_| upstream::supports(&dep.language)) else {
+        return Ok(None);
+    };
+    let lookup = |version| upstream_best_effort(SCAN_TIMEOUT, upstream::fetch_license(client, &dep.name, version, &dep.language));
+    Ok(lookup(dep.version.as_deref()).or_else(|| dep.version.as_ref().and_then(|_| lookup(None))))
+}
+
+fn handle_licenses(path: String, lang: Option<Language>, fetch: bool, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = if path == "." {
+        let cwd = std::env::current_dir()?;
+        detect_project(&cwd).root.unwrap_or(cwd)
+    } else {
+        PathBuf::from(&path)
+    };
+    
+    // the lockfile's pinned versions, so each license is the one of the release actually installed
+    let dependencies = collect_resolved_dependencies(&dir, lang.as_ref())?;
+    if dependencies.is_empty() {
+        println!("no dependencies found in {}", dir.display());
+        return Ok(());
+    }
+    
+    let registry = load_registry()?;
+    let licenses = Config::load()?.licenses.unwrap_or_default();
+    let client = if fetch { Some(upstream::client()?) } else { None };
+    
+    let mut entries = Vec::new();
+    for dep in &dependencies {
+        let license = dependency_license(&registry, dep, client.as_ref())?;
+        let check = license.as_deref().filter(|_| licenses.is_configured()).map(|l| licenses.check(l));
+        entries.push((dep, license, check));
+    }
+    let denied = entries.iter().filter(|(_, _, check)| matches!(check, Some(LicenseCheck::Denied(_) | LicenseCheck::NotAllowed(_)))).count();
+    
+    // dependencies per license expression, most common first
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, license, _) in &entries {
+        *counts.entry(license.as_deref().unwrap_or("unknown")).or_default() += 1;
+    }
+    let mut summary: Vec<(&str, usize)> = counts.into_iter().collect();
+    summary.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    
+    match format {
+        OutputFormat::Json => {
+            let report = serde_json::json!({
+                "path": dir.display().to_string(),
+                "dependencies": entries.iter().map(|(dep, license, check)| serde_json::json!({
+                    "name": dep.name,
+                    "version": dep.version,
+                    "language": dep.language.to_string(),
+                    "manifest": dep.manifest,
+                    "license": license,
+                    "status": match (license, check) {
+                        (None, _) => "unknown",
+                        (Some(_), None | Some(LicenseCheck::Allowed)) => "allowed",
+                        (Some(_), Some(LicenseCheck::Denied(_))) => "denied",
+                        (Some(_), Some(LicenseCheck::NotAllowed(_))) => "not-allowed",
+                        (Some(_), Some(LicenseCheck::Unparsed)) => "unparsed",
+                    },
+                })).collect::<Vec<_>>(),
+                "summary": summary.iter().map(|(license, count)| serde_json::json!({ "license": license, "count": count })).collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::Text => {
+            println!("licenses of {} dependencies in {}", dependencies.len(), dir.display());
+            for (dep, license, check) in &entries {
+                let requested = dep.version.as_deref().unwrap_or("*");
+                match (license, check) {
+                    (None, _) => println!("  ⚠ {} {} ({}) - no license known", dep.name, requested, dep.manifest),
+                    (Some(license), None | Some(LicenseCheck::Allowed)) => println!("  ✓ {} {} ({}) - {}", dep.name, requested, dep.manifest, license),
+                    (Some(license), Some(LicenseCheck::Unparsed)) => println!("  ⚠ {} {} ({}) - {}: not a valid spdx expression", dep.name, requested, dep.manifest, license),
+                    (Some(license), Some(check)) => println!("  ✘ {} {} ({}) - {}: {}", dep.name, requested, dep.manifest, license, check),
+                }
+            }
+            println!();
+            for (license, count) in &summary {
+                println!("  {:>4}  {}", count, license);
+            }
+        }
+    }
+    
+    if denied > 0 {
+        return Err(format!("{} of {} dependencies have a license that is not allowed", denied, dependencies.len()).into());
+    }
+    Ok(())
+}
+
+fn handle_sbom(path: String, lang: Option<Language>, format: SbomFormat, output: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = if path == "." {
+        let cwd = std::env::current_dir()?;
+        detect_project(&cwd).root.unwrap_or(cwd)
+    } else {
+        PathBuf::from(&path)
+    };
+    let project = fs::canonicalize(&dir)?
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string());
     
-    if Path::new("requirements.txt").exists() || 
-       Path::new("setup.py").exists() || 
-       Path::new("pyproject.toml").exists() {
-        return Some(Language::Python);
-    }
+    let mut dependencies = collect_resolved_dependencies(&dir, lang.as_ref())?;
+    // a package declared in several manifest sections is one component
+    dependencies.sort_by(|a, b| (a.language.to_string(), &a.name, &a.version).cmp(&(b.language.to_string(), &b.name, &b.version)));
+    dependencies.dedup_by(|a, b| a.language == b.language && a.name == b.name && a.version == b.version);
+    
+    let registry = load_registry()?;
+    let config = Config::load()?;
+    let policy = Policy::load(&config)?;
+    let review_config = config.review.unwrap_or_default();
     
-    if Path::new("go.mod").exists() || Path::new("go.sum").exists() {
-        return Some(Language::Go);
+    let mut components = Vec::new();
+    for dep in dependencies {
+        let result = verify_package(&dep.name, dep.pinned_version(), &dep.language, &registry, DEFAULT_TRUST_THRESHOLD, &review_config, &policy)?;
+        let verdict = if result.blocked.is_some() {
+            Verdict::Blocked
+        } else if result.policy.denied() {
+            Verdict::Denied
+        } else if result.is_trusted {
+            Verdict::Trusted
+        } else {
+            Verdict::Untrusted
+        };
+        let record = result.package_entry;
+        // the reviewed hash and license only describe the version they were recorded for
+        let reviewed = record.as_ref().filter(|r| dep.version.as_deref() == Some(r.version.as_str()));
+        let hash = dep.hash.or_else(|| reviewed.map(|r| r.hash.clone()));
+        components.push(Component {
+            license: reviewed.and_then(|r| r.license.clone()),
+            name: dep.name,
+            version: dep.version,
+            language: dep.language,
+            hash,
+            record,
+            layer: result.layer,
+            verdict,
+        });
     }
     
-    // check for common project structures
-    if Path::new("src").is_dir() {
-        // check if src contains rust files
-        if let Ok(entries) = fs::read_dir("src") {
-            for entry in entries.flatten() {
-                if let Some(ext) = entry.path().extension() {
-                    if ext == "rs" {
-                        return Some(Language::Rust);
-                    }
-                    if ext == "py" {
-                        return Some(Language::Python);
-                    }
-                    if ext == "go" {
-                        return Some(Language::Go);
-                    }
-                }
-            }
+    let created = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let document = match format {
+        SbomFormat::CycloneDx => sbom::cyclonedx(&project, &components, &created),
+        SbomFormat::Spdx => sbom::spdx(&project, &components, &created),
+    };
+    let json = serde_json::to_string_pretty(&document)?;
+    match output {
+        Some(file) => {
+            fs::write(&file, json + "\n")?;
+            println!("✓ wrote {} component(s) to {}", components.len(), file);
         }
+        None => println!("{}", json),
     }
-    
-    None
+    Ok(())
 }
 
-fn handle_install(
-    package: String,
-    version: Option<String>,
-    lang: Option<Language>,
-    yes: bool,
-    extra_args: Vec<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("installing package: {}", package);
-    if let Some(version) = &version {
-        println!("version: {}", version);
-    }
+fn handle_review(stale_only: bool, lang: Option<Language>) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = load_registry()?;
+    let review_config = Config::load()?.review.unwrap_or_default();
+    let today = review::today();
     
-    let detected_lang = lang.or_else(detect_language);
-    let language = match detected_lang {
-        Some(language) => {
-            println!("language: {}", language);
-            language
-        }
-        None => {
-            return Err("could not detect language - please specify with --lang flag".into());
+    // how often each package was installed, from the audit log
+    let mut installs: HashMap<(String, String), usize> = HashMap::new();
+    for event in audit::read_events(&log_dir())? {
+        if event.command == "install" && event.decision == Decision::Allowed
+            && let Some(language) = event.language
+            && let Some(lang) = Language::ALL.iter().find(|l| l.to_string() == language)
+        {
+            // keyed like the registry, so `Flask` and `flask` installs count towards the same record
+            *installs.entry((language, normalize_name(&event.package, lang))).or_default() += 1;
         }
-    };
+    }
     
-    // load registry and check if package is trusted
-    let registry = load_registry()?;
+    let languages: Vec<Language> = match lang {
+        Some(lang) => vec![lang],
+        None => Language::ALL.to_vec(),
+    };
     
-    if let Some(trusted_package) = registry.lookup_package(&package, &language) {
-        println!("✓ package '{}' is trusted", package);
-        println!("  version: {}", trusted_package.version);
-        println!("  trust score: {:.1}", trusted_package.trust_score);
-        println!("  endorsed by: {}", trusted_package.endorsed_by.join(", "));
-        println!("  last reviewed: {}", trusted_package.last_reviewed);
-        
-        // proceed with installation
-        install_package(&package, &version, &language, &extra_args)?;
-    } else {
-        println!("⚠ package '{}' is not in trusted registry", package);
-        println!("consider using 'sip trust {}' to add it to your trusted packages", package);
-        
-        if !yes {
-            // ask user if they want to proceed
-            println!("do you want to proceed with installation anyway? [y/N]");
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-            
-            if input.trim().to_lowercase() != "y" && input.trim().to_lowercase() != "yes" {
-                println!("installation cancelled");
-                return Ok(());
+    let mut rows = Vec::new();
+    for language in &languages {
+        for entry in registry.list_packages(Some(language), None)? {
+            let stale = review::staleness(entry.record, language, &review_config, today);
+            if stale_only && stale.is_none() {
+                continue;
             }
+            let age = review::review_age(entry.record, today);
+            let usage = installs
+                .get(&(language.to_string(), normalize_name(&entry.record.name, language)))
+                .copied()
+                .unwrap_or(0);
+            rows.push((entry, language, age, stale, usage));
         }
-        
-        println!("proceeding with untrusted installation...");
-        install_package(&package, &version, &language, &extra_args)?;
+    }
+    
+    if rows.is_empty() {
+        println!("{}", if stale_only { "no packages are due for re-review" } else { "no trusted packages found" });
+        return Ok(());
+    }
+    
+    // oldest reviews first, unreadable dates before everything else; ties go to the most installed
+    rows.sort_by(|a, b| {
+        b.2.unwrap_or(i64::MAX)
+            .cmp(&a.2.unwrap_or(i64::MAX))
+            .then(b.4.cmp(&a.4))
+    });
+    
+    println!("{}", if stale_only { "packages due for re-review:" } else { "review status:" });
+    for (entry, language, age, stale, usage) in rows {
+        let marker = match (&stale, review_config.on_stale) {
+            (None, _) => "✓",
+            (Some(_), StaleAction::Warn) => "⚠",
+            (Some(_), StaleAction::Block) => "✘",
+        };
+        let age = age.map(|days| format!("{} days ago", days)).unwrap_or_else(|| "invalid date".to_string());
+        println!("  {} {} {} ({}) - reviewed {}, {} (max {}), {} installs [{}]",
+            marker,
+            entry.record.name,
+            entry.record.version,
+            language,
+            entry.record.last_reviewed,
+            age,
+            review::max_age_days(&review_config, language, entry.record.trust_score),
+            usage,
+            entry.layer
+        );
     }
     
     Ok(())
 }
 
-fn handle_verify(
-    package: String, 
-    version: Option<String>, 
-    lang: Option<Language>
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("verifying package: {}", package);
-    if let Some(version) = &version {
-        println!("version: {}", version);
+fn handle_log(filter: LogFilter, limit: usize, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    filter.validate()?;
+    
+    let dir = log_dir();
+    let events: Vec<AuditEvent> = audit::read_events(&dir)?
+        .into_iter()
+        .filter(|event| filter.matches(event))
+        .collect();
+    
+    if events.is_empty() {
+        println!("no matching events in {}", dir.display());
+        return Ok(());
     }
     
-    let detected_lang = lang.or_else(detect_language);
-    let language = match detected_lang {
-        Some(language) => {
-            println!("language: {}", language);
-            language
+    for event in &events[events.len().saturating_sub(limit)..] {
+        if json {
+            println!("{}", serde_json::to_string(event)?);
+            continue;
         }
-        None => {
-            return Err("could not detect language - please specify with --lang flag".into());
+        
+        let mut line = format!("{} {} {}", event.timestamp, event.command, event.package);
+        if let Some(version) = &event.version {
+            line.push_str(&format!(" {}", version));
         }
-    };
-    
-    let registry = load_registry()?;
-    
-    if let Some(trusted_package) = registry.lookup_package(&package, &language) {
-        println!("✓ package '{}' is trusted", package);
-        println!("  version: {}", trusted_package.version);
-        println!("  trust score: {:.1}", trusted_package.trust_score);
-        println!("  endorsed by: {}", trusted_package.endorsed_by.join(", "));
-        println!("  last reviewed: {}", trusted_package.last_reviewed);
-        println!("  source: {}", trusted_package.source);
-    } else {
-        println!("⚠ package '{}' is not in trusted registry", package);
+        if let Some(language) = &event.language {
+            line.push_str(&format!(" ({})", language));
+        }
+        line.push_str(&format!(" - {}", event.decision));
+        if event.overridden {
+            line.push_str(" [overridden]");
+        }
+        if let Some(choice) = &event.review {
+            line.push_str(&format!(" [{}]", choice));
+        }
+        if let Some(code) = event.exit_status {
+            line.push_str(&format!(" exit {}", code));
+        }
+        println!("{} by {} in {}", line, event.user, event.cwd);
     }
     
     Ok(())
 }
 
-fn handle_trust(
-    package: String, 
-    version: Option<String>, 
-    lang: Option<Language>, 
-    fetch: bool, 
-    score: Option<f64>
+fn handle_registry_diff(
+    old: String,
+    new: String,
+    lang: Option<Language>,
+    format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let detected_lang = lang.or_else(detect_language);
-    let language = match detected_lang {
-        Some(language) => language,
-        None => {
-            return Err("could not detect language - please specify with --lang flag".into());
-        }
-    };
-    
-    let mut registry = Registry::load_mutable()?;
+    let diff = registry_diff::diff_snapshots(Path::new(&old), Path::new(&new), lang.as_ref())?;
     
-    let package_entry = if fetch && language == Language::Python {
-        // fetch metadata from pypi
-        println!("fetching metadata for '{}' from pypi...", package);
-        let mut fetched_entry = tokio::runtime::Runtime::new()
-            .unwrap()
-            .block_on(fetch_from_pypi(&package))?;
-        
-        // override trust score if provided
-        if let Some(trust_score) = score {
-            fetched_entry.trust_score = trust_score;
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+            // keep stdout parseable; the red flag count is in the report
+            if diff.red_flags > 0 {
+                std::process::exit(1);
+            }
         }
-        
-        // add user endorsement
-        fetched_entry.endorsed_by.push("user".to_string());
-        
-        fetched_entry
-    } else {
-        // manual entry
-        let version = version.unwrap_or_else(|| "1.0.0".to_string());
-        let trust_score = score.unwrap_or(5.0);
-        
-        PackageRecord {
-            name: package.clone(),
-            version,
-            hash: "sha256:manual".to_string(),
-            trust_score,
-            endorsed_by: vec!["user".to_string()],
-            last_reviewed: Utc::now().format("%Y-%m-%d").to_string(),
-            source: "https://manual.entry".to_string(),
+        OutputFormat::Text => {
+            if diff.changes.is_empty() {
+                println!("no registry changes");
+            }
+            let mut current_language = None;
+            for change in &diff.changes {
+                if current_language != Some(&change.language) {
+                    println!("{}:", change.language);
+                    current_language = Some(&change.language);
+                }
+                println!("  {}", change);
+            }
         }
-    };
-    
-    registry.add_package(package_entry, &language);
-    registry.save_to_disk()?;
+    }
     
-    println!("✓ added '{}' to trusted {} packages", package, match language {
-        Language::Python => "python",
-        Language::Rust => "rust", 
-        Language::Go => "go",
-    });
+    // a changed hash for an unchanged version fails the check so it can gate registry prs
+    if diff.red_flags > 0 {
+        return Err(format!("{} red flag(s): hash changed for an already reviewed version", diff.red_flags).into());
+    }
     
     Ok(())
 }
 
-fn handle_untrust(package: String, lang: Option<Language>) -> Result<(), Box<dyn std::error::Error>> {
-    let detected_lang = lang.or_else(detect_language);
-    let language = match detected_lang {
-        Some(language) => language,
-        None => {
-            return Err("could not detect language - please specify with --lang flag".into());
+fn handle_registry_lint(path: String, lang: Option<Language>, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let lint_config = Config::load()?.lint.unwrap_or_default();
+    let findings = registry_lint::lint_path(Path::new(&path), lang.as_ref(), &lint_config, review::today())?;
+    let errors = findings.iter().filter(|f| f.severity == Severity::Error).count();
+    
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&findings)?);
+            if errors > 0 {
+                std::process::exit(1);
+            }
         }
-    };
+        OutputFormat::Text => {
+            for finding in &findings {
+                println!("{}", finding);
+            }
+        }
+    }
     
-    let mut registry = Registry::load_mutable()?;
+    if errors > 0 {
+        return Err(format!("{} error(s) and {} warning(s) in {}", errors, findings.len() - errors, path).into());
+    }
     
-    if registry.remove_package(&package, &language) {
-        registry.save_to_disk()?;
-        println!("✓ removed '{}' from trusted {} packages", package, match language {
-            Language::Python => "python",
-            Language::Rust => "rust",
-            Language::Go => "go",
-        });
+    if findings.is_empty() {
+        println!("✓ {} is clean", path);
     } else {
-        println!("⚠ package '{}' was not found in trusted {} packages", package, match language {
-            Language::Python => "python", 
-            Language::Rust => "rust",
-            Language::Go => "go",
-        });
+        println!("⚠ {} warning(s) in {}", findings.len(), path);
     }
-    
     Ok(())
 }
 
-fn handle_list(lang: Option<Language>) -> Result<(), Box<dyn std::error::Error>> {
-    let registry = Registry::load_mutable()?;
-    
-    let packages = registry.list_packages(lang.as_ref());
+fn handle_registry_audit(
+    path: String,
+    lang: Option<Language>,
+    fix: bool,
+    behind: usize,
+    sources: bool,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = Path::new(&path);
+    let runtime = tokio::runtime::Runtime::new()?;
+    let client = upstream::client()?;
+    let mut findings = Vec::new();
     
-    if packages.is_empty() {
-        println!("no trusted packages found");
-        return Ok(());
+    for language in Language::ALL.iter().filter(|l| lang.as_ref().is_none_or(|lang| lang == *l)) {
+        let file = data_file(dir, language);
+        if !file.exists() {
+            continue;
+        }
+        let mut records: Vec<PackageRecord> = serde_json::from_str(&fs::read_to_string(&file)?)?;
+        if records.is_empty() {
+            continue;
+        }
+        if !upstream::supports(language) {
+            println!("skipping {} - upstream lookups are not supported yet", language);
+            continue;
+        }
+        
+        println!("auditing {} {} records against upstream...", records.len(), language);
+        let language_findings = runtime.block_on(registry_audit::audit_records(&client, &mut records, language, fix, behind, sources));
+        
+        if language_findings.iter().any(|f| f.fixed) {
+            fs::write(&file, serde_json::to_string_pretty(&records)?)?;
+            println!("updated {}", file.display());
+        }
+        findings.extend(language_findings);
     }
     
-    match lang {
-        Some(l) => println!("trusted {} packages:", match l {
-            Language::Python => "python",
-            Language::Rust => "rust", 
-            Language::Go => "go",
-        }),
-        None => println!("all trusted packages:"),
+    let errors = findings.iter().filter(|f| f.issue.is_error() && !f.fixed).count();
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&findings)?);
+            if errors > 0 {
+                std::process::exit(1);
+            }
+        }
+        OutputFormat::Text => {
+            for finding in &findings {
+                println!("  {}", finding);
+            }
+        }
     }
     
-    for package in packages {
-        println!("  {} {} (score: {:.1}) - {}", 
-            package.name, 
-            package.version, 
-            package.trust_score,
-            package.last_reviewed
-        );
+    if errors > 0 {
+        let hint = if fix { "" } else { " (hash mismatches can be refreshed with --fix)" };
+        return Err(format!("{} record(s) failed the upstream audit{}", errors, hint).into());
     }
     
+    println!("✓ every audited record matches upstream");
     Ok(())
 }
 
 fn handle_bulk_trust(
     file_path: String,
     language: Language,
-    score: Option<f64>
+    score: Option<f64>,
+    layer: String,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let content = fs::read_to_string(&file_path)?;
     let package_names: Vec<&str> = content.lines()
@@ -326,17 +1636,18 @@ fn handle_bulk_trust(
     println!("found {} packages to process", package_names.len());
     
     let mut registry = Registry::load_mutable()?;
+    let runtime = tokio::runtime::Runtime::new()?;
     let mut success_count = 0;
     let mut error_count = 0;
     
     for (i, package_name) in package_names.iter().enumerate() {
         println!("({}/{}) processing: {}", i + 1, package_names.len(), package_name);
         
-        let result = if language == Language::Python {
-            // fetch from pypi
-            match tokio::runtime::Runtime::new()
-                .unwrap()
-                .block_on(fetch_from_pypi(package_name)) {
+        let result = if language != Language::Go {
+            // fetch from the upstream registry
+            match fetch_package_metadata(&runtime, package_name, None, &language) {
+                Ok(entry) if entry.yanked => Err(Yanked { version: entry.version, reason: entry.yank_reason }.to_string().into()),
+                Ok(entry) if let Err(e) = registry.validate_record(&entry) => Err(e),
                 Ok(mut entry) => {
                     if let Some(trust_score) = score {
                         entry.trust_score = trust_score;
@@ -347,22 +1658,24 @@ fn handle_bulk_trust(
                 Err(e) => Err(e)
             }
         } else {
-            // manual entry for rust/go
+            // manual entry for go
             let trust_score = score.unwrap_or(5.0);
             Ok(PackageRecord {
                 name: package_name.to_string(),
                 version: "1.0.0".to_string(),
-                hash: "manual".to_string(),
+                hash: "sha256:manual".to_string(),
                 trust_score,
                 endorsed_by: vec!["bulk-import".to_string()],
                 last_reviewed: Utc::now().format("%Y-%m-%d").to_string(),
-                source: "manual".to_string(),
+                source: "https://manual.entry".to_string(),
+                maintainers: upstream::repository_owner(package_name).into_iter().collect(),
+                ..Default::default()
             })
         };
         
         match result {
             Ok(package_entry) => {
-                registry.add_package(package_entry, &language);
+                registry.add_package(package_entry, &language, &layer)?;
                 success_count += 1;
                 println!("  ✓ added {}", package_name);
             }
@@ -415,7 +1728,7 @@ fn install_package(
                 .output()?;
                 
             if !output.status.success() {
-                return Err("pip install failed".into());
+                return Err(InstallerFailed::new("pip install", output.status).into());
             }
         }
         Language::Rust => {
@@ -442,7 +1755,7 @@ fn install_package(
                 .output()?;
                 
             if !output.status.success() {
-                return Err("cargo add failed".into());
+                return Err(InstallerFailed::new("cargo add", output.status).into());
             }
         }
         Language::Go => {
@@ -469,10 +1782,151 @@ fn install_package(
                 .output()?;
                 
             if !output.status.success() {
-                return Err("go get failed".into());
+                return Err(InstallerFailed::new("go get", output.status).into());
+            }
+        }
+        Language::Node => {
+            let installer = detect_node_installer();
+            let mut args = vec![if installer == "npm" { "install" } else { "add" }];
+            let package_spec: String;
+            
+            // scoped names keep their leading @, so `@types/node@20.1.0` is still valid
+            if let Some(v) = version {
+                package_spec = format!("{}@{}", package, v);
+                args.push(&package_spec);
+            } else {
+                args.push(package);
+            }
+            
+            // add extra args
+            for arg in extra_args {
+                args.push(arg);
+            }
+            
+            println!("running: {} {}", installer, args.join(" "));
+            let output = Command::new(installer)
+                .args(&args)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .output()?;
+                
+            if !output.status.success() {
+                return Err(InstallerFailed::new(&format!("{} {}", installer, args[0]), output.status).into());
+            }
+        }
+        Language::Ruby => {
+            // inside a bundler project the gem belongs in the Gemfile, otherwise install it globally
+            let (installer, mut args) = if project_file_exists("Gemfile") {
+                ("bundle", vec!["add".to_string(), package.to_string()])
+            } else {
+                ("gem", vec!["install".to_string(), package.to_string()])
+            };
+            
+            if let Some(v) = version {
+                args.push("--version".to_string());
+                args.push(v.clone());
+            }
+            
+            // add extra args
+            args.extend(extra_args.iter().cloned());
+            
+            println!("running: {} {}", installer, args.join(" "));
+            let output = Command::new(installer)
+                .args(&args)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .output()?;
+                
+            if !output.status.success() {
+                return Err(InstallerFailed::new(&format!("{} {}", installer, args[0]), output.status).into());
+            }
+        }
+        Language::Php => {
+            let mut args = vec!["require"];
+            let package_spec: String;
+            
+            if let Some(v) = version {
+                package_spec = format!("{}:{}", package, v);
+                args.push(&package_spec);
+            } else {
+                args.push(package);
+            }
+            
+            // add extra args
+            for arg in extra_args {
+                args.push(arg);
+            }
+            
+            println!("running: composer {}", args.join(" "));
+            let output = Command::new("composer")
+                .args(&args)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .output()?;
+                
+            if !output.status.success() {
+                return Err(InstallerFailed::new("composer require", output.status).into());
             }
         }
+        Language::Java => {
+            // maven and gradle resolve dependencies from the build file, there is no install step to wrap
+            return Err(format!(
+                "sip cannot install jvm artifacts directly - declare '{}' in pom.xml or build.gradle and run 'sip vet'",
+                package
+            ).into());
+        }
     }
     
     Ok(())
 }
+
+// a package manager that ran but exited unsuccessfully
+#[derive(Debug)]
+struct InstallerFailed {
+    command: String,
+    code: Option<i32>,
+}
+
+impl InstallerFailed {
+    fn new(command: &str, status: ExitStatus) -> Self {
+        InstallerFailed {
+            command: command.to_string(),
+            code: status.code(),
+        }
+    }
+}
+
+impl fmt::Display for InstallerFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed", self.command)
+    }
+}
+
+impl std::error::Error for InstallerFailed {}
+
+// exit code of the package manager for the audit log, None when it never ran
+fn installer_exit_status(result: &Result<(), Box<dyn std::error::Error>>) -> Option<i32> {
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => e.downcast_ref::<InstallerFailed>().and_then(|failed| failed.code),
+    }
+}
+
+// whether the enclosing project root (not just the current directory) has this file
+fn project_file_exists(file: &str) -> bool {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| find_project_root(&cwd))
+        .is_some_and(|root| root.join(file).exists())
+}
+
+// pick the node package manager that owns the lockfile in the current project
+fn detect_node_installer() -> &'static str {
+    if project_file_exists("pnpm-lock.yaml") {
+        "pnpm"
+    } else if project_file_exists("yarn.lock") {
+        "yarn"
+    } else {
+        "npm"
+    }
+}
@@ -0,0 +1,540 @@
+// software bills of materials: a project's resolved dependencies as cyclonedx 1.5 or spdx 2.3
+// json, annotated with what sip knows about each one (trust score, endorsers, review date and
+// the verdict vet would give), and third-party sboms read back in for `sip vet --sbom`
+
+use crate::cli::Language;
+use crate::sip::manifest::Dependency;
+use crate::sip::normalize::normalize_name;
+use crate::sip::package::PackageRecord;
+use crate::sip::version::is_exact;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+const TOOL: &str = concat!("sip-", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Trusted,
+    Untrusted,
+    Blocked,
+    // refused by a policy rule or the license lists
+    Denied,
+}
+
+impl Verdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Trusted => "trusted",
+            Verdict::Untrusted => "untrusted",
+            Verdict::Blocked => "blocked",
+            Verdict::Denied => "denied",
+        }
+    }
+}
+
+pub struct Component {
+    pub name: String,
+    pub version: Option<String>,
+    pub language: Language,
+    // a lockfile's pinned digest, or the reviewed record's when it is for the same version
+    pub hash: Option<String>,
+    pub license: Option<String>,
+    // the registry record the verdict is based on, possibly for another version
+    pub record: Option<PackageRecord>,
+    pub layer: Option<String>,
+    pub verdict: Verdict,
+}
+
+// package urls as the purl spec types them; go versions keep their `v`
+pub fn purl(name: &str, version: Option<&str>, lang: &Language) -> String {
+    let (kind, path) = match lang {
+        Language::Python => ("pypi", normalize_name(name, lang)),
+        Language::Rust => ("cargo", name.to_string()),
+        Language::Go => ("golang", name.to_string()),
+        Language::Node => ("npm", name.replacen('@', "%40", 1)),
+        Language::Java => ("maven", name.replacen(':', "/", 1)),
+        Language::Ruby => ("gem", name.to_string()),
+        Language::Php => ("composer", name.to_lowercase()),
+    };
+    // a purl names a version, manifest requirements like "^1.2" or ">=2" are left out
+    match version.filter(|v| is_exact(v)) {
+        Some(v) if *lang == Language::Go => format!("pkg:{}/{}@v{}", kind, path, v.trim_start_matches('v')),
+        Some(v) => format!("pkg:{}/{}@{}", kind, path, v.trim_start_matches('v')),
+        None => format!("pkg:{}/{}", kind, path),
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// the sip language, package name and version a purl names; None for ecosystems sip does not
+// cover (deb, docker, github actions, ...)
+pub fn parse_purl(purl: &str) -> Option<(Language, String, Option<String>)> {
+    let rest = purl.strip_prefix("pkg:")?.trim_start_matches('/');
+    // qualifiers and subpath do not change which package it is
+    let rest = rest.split(['?', '#']).next()?;
+    let (kind, rest) = rest.split_once('/')?;
+    let (path, version) = match rest.rsplit_once('@') {
+        Some((path, version)) if !path.is_empty() => (path, Some(percent_decode(version))),
+        _ => (rest, None),
+    };
+    let segments: Vec<String> = path.split('/').filter(|s| !s.is_empty()).map(percent_decode).collect();
+    let language = match kind.to_lowercase().as_str() {
+        "pypi" => Language::Python,
+        "cargo" => Language::Rust,
+        "golang" => Language::Go,
+        "npm" => Language::Node,
+        "maven" => Language::Java,
+        "gem" => Language::Ruby,
+        "composer" => Language::Php,
+        _ => return None,
+    };
+    let name = match language {
+        Language::Java => segments.join(":"),
+        _ => segments.join("/"),
+    };
+    if name.is_empty() {
+        return None;
+    }
+    let version = version.map(|v| if language == Language::Go { v.trim_start_matches('v').to_string() } else { v });
+    Some((language, name, version))
+}
+
+// an sbom's components as dependencies, plus the purls (or names) sip has no ecosystem for
+pub fn read_components(content: &str, file: &str) -> Result<(Vec<Dependency>, Vec<String>), Box<dyn std::error::Error>> {
+    let document: Value = serde_json::from_str(content).map_err(|e| format!("{} is not json: {}", file, e))?;
+    let mut purls: Vec<(String, String, Hashes)> = Vec::new();
+
+    if document.get("bomFormat").and_then(|f| f.as_str()) == Some("CycloneDX") {
+        // components can nest, e.g. the libraries bundled inside a framework
+        let mut pending: Vec<&Value> = document.get("components").and_then(|c| c.as_array()).map(|c| c.iter().rev().collect()).unwrap_or_default();
+        while let Some(component) = pending.pop() {
+            let name = component.get("name").and_then(|n| n.as_str()).unwrap_or("(unnamed)");
+            let purl = component.get("purl").and_then(|p| p.as_str()).unwrap_or_default().to_string();
+            purls.push((name.to_string(), purl, listed_hashes(component.get("hashes"), "alg", "content")));
+            pending.extend(component.get("components").and_then(|c| c.as_array()).into_iter().flatten().rev());
+        }
+    } else if document.get("spdxVersion").is_some() {
+        // the packages the document describes are the product itself, which usually has no purl
+        let described: Vec<&Value> = document
+            .get("documentDescribes")
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+            .chain(
+                document
+                    .get("relationships")
+                    .and_then(|r| r.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter(|r| r.get("relationshipType").and_then(|t| t.as_str()) == Some("DESCRIBES"))
+                    .filter_map(|r| r.get("relatedSpdxElement")),
+            )
+            .collect();
+        for package in document.get("packages").and_then(|p| p.as_array()).into_iter().flatten() {
+            let name = package.get("name").and_then(|n| n.as_str()).unwrap_or("(unnamed)");
+            let purl = package
+                .get("externalRefs")
+                .and_then(|r| r.as_array())
+                .into_iter()
+                .flatten()
+                .find(|r| r.get("referenceType").and_then(|t| t.as_str()) == Some("purl"))
+                .and_then(|r| r.get("referenceLocator")?.as_str());
+            if purl.is_some() || !package.get("SPDXID").is_some_and(|id| described.contains(&id)) {
+                let hashes = listed_hashes(package.get("checksums"), "algorithm", "checksumValue");
+                purls.push((name.to_string(), purl.unwrap_or_default().to_string(), hashes));
+            }
+        }
+    } else {
+        return Err(format!("{} is neither a cyclonedx nor an spdx json document", file).into());
+    }
+
+    let mut dependencies = Vec::new();
+    let mut unsupported = Vec::new();
+    for (name, purl, hashes) in purls {
+        match parse_purl(&purl) {
+            Some((language, name, version)) => {
+                let hash = preferred_hash(&hashes, &language);
+                dependencies.push(Dependency { name, version, language, manifest: file.to_string(), hash })
+            }
+            None if purl.is_empty() => unsupported.push(name),
+            None => unsupported.push(purl),
+        }
+    }
+    Ok((dependencies, unsupported))
+}
+
+// (algorithm, value) pairs as an sbom lists them
+type Hashes = Vec<(String, String)>;
+
+// the hashes of a cyclonedx `hashes` or spdx `checksums` list
+fn listed_hashes(list: Option<&Value>, algorithm: &str, value: &str) -> Hashes {
+    list.and_then(|l| l.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some((entry.get(algorithm)?.as_str()?.to_string(), entry.get(value)?.as_str()?.to_string())))
+        .collect()
+}
+
+// one of a component's hashes in registry record form (`sha256:<hex>`), preferring the
+// algorithm the ecosystem's registry publishes so it can be compared with the reviewed record
+fn preferred_hash(hashes: &[(String, String)], lang: &Language) -> Option<String> {
+    let order: &[&str] = match lang {
+        Language::Node => &["sha512", "sha256", "sha384", "sha1"],
+        _ => &["sha256", "sha512", "sha384", "sha1"],
+    };
+    let hashes: Vec<(String, String)> = hashes
+        .iter()
+        .map(|(algorithm, value)| (algorithm.to_lowercase().replace('-', ""), value.to_lowercase()))
+        .filter(|(_, value)| !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit()))
+        .collect();
+    order
+        .iter()
+        .find_map(|algorithm| hashes.iter().find(|(a, _)| a == algorithm))
+        .map(|(algorithm, value)| format!("{}:{}", algorithm, value))
+}
+
+// whether two hashes of the same algorithm disagree; different algorithms cannot be compared
+pub fn hash_conflict(found: &str, recorded: &str) -> bool {
+    match (digest(found), digest(recorded)) {
+        (Some((a, found)), Some((b, recorded))) => a == b && found != recorded,
+        _ => false,
+    }
+}
+
+// (algorithm, hex digest) from a registry hash: `sha256:<hex>` or an sri `sha512-<base64>`.
+// go's h1: hashes cover the module tree rather than a file, so neither format can carry them
+pub fn digest(hash: &str) -> Option<(&'static str, String)> {
+    let (algorithm, value, encoded) = if let Some((algorithm, hex)) = hash.split_once(':') {
+        (algorithm, hex.to_lowercase(), false)
+    } else {
+        let (algorithm, b64) = hash.split_once('-')?;
+        (algorithm, b64.to_string(), true)
+    };
+    let algorithm = match algorithm {
+        "sha1" => "SHA-1",
+        "sha256" => "SHA-256",
+        "sha384" => "SHA-384",
+        "sha512" => "SHA-512",
+        _ => return None,
+    };
+    let hex = if encoded {
+        BASE64.decode(value).ok()?.iter().map(|b| format!("{:02x}", b)).collect()
+    } else {
+        value
+    };
+    (!hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit())).then_some((algorithm, hex))
+}
+
+impl Component {
+    pub fn purl(&self) -> String {
+        purl(&self.name, self.version.as_deref(), &self.language)
+    }
+
+    // sip's view of the component, as name/value pairs under the `sip:` namespace
+    pub fn properties(&self) -> Vec<(&'static str, String)> {
+        let mut properties = vec![("sip:decision", self.verdict.as_str().to_string())];
+        if let Some(record) = &self.record {
+            properties.push(("sip:trust_score", format!("{:.1}", record.trust_score)));
+            if !record.endorsed_by.is_empty() {
+                properties.push(("sip:endorsed_by", record.endorsed_by.join(", ")));
+            }
+            properties.push(("sip:last_reviewed", record.last_reviewed.clone()));
+            properties.push(("sip:reviewed_version", record.version.clone()));
+        }
+        if let Some(layer) = &self.layer {
+            properties.push(("sip:layer", layer.clone()));
+        }
+        properties
+    }
+}
+
+// a random-looking but reproducible uuid (version 4 layout) for the document serial number
+fn document_uuid(project: &str, components: &[Component], created: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(project.as_bytes());
+    hasher.update(created.as_bytes());
+    for component in components {
+        hasher.update(component.purl().as_bytes());
+    }
+    let mut bytes: [u8; 16] = hasher.finalize()[..16].try_into().unwrap_or_default();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+// bom-refs must be unique within a document, but two requirements on different ranges of one
+// package share a versionless purl; later ones get a package-id qualifier, as syft does
+fn bom_refs(components: &[Component]) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    components
+        .iter()
+        .map(|component| {
+            let purl = component.purl();
+            let count = seen.entry(purl.clone()).or_default();
+            *count += 1;
+            match *count {
+                1 => purl,
+                n => format!("{}{}package-id={}", purl, if purl.contains('?') { '&' } else { '?' }, n),
+            }
+        })
+        .collect()
+}
+
+pub fn cyclonedx(project: &str, components: &[Component], created: &str) -> Value {
+    let entries: Vec<Value> = components
+        .iter()
+        .zip(bom_refs(components))
+        .map(|(component, bom_ref)| {
+            let purl = component.purl();
+            let mut entry = json!({
+                "type": "library",
+                "bom-ref": bom_ref,
+                "name": component.name,
+                "purl": purl,
+                "properties": component.properties().into_iter().map(|(name, value)| json!({ "name": name, "value": value })).collect::<Vec<_>>(),
+            });
+            if let Some(version) = &component.version {
+                entry["version"] = json!(version);
+            }
+            if let Some((alg, content)) = component.hash.as_deref().and_then(digest) {
+                entry["hashes"] = json!([{ "alg": alg, "content": content }]);
+            }
+            if let Some(license) = &component.license {
+                entry["licenses"] = json!([{ "expression": license }]);
+            }
+            entry
+        })
+        .collect();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "serialNumber": format!("urn:uuid:{}", document_uuid(project, components, created)),
+        "version": 1,
+        "metadata": {
+            "timestamp": created,
+            "tools": { "components": [{ "type": "application", "name": "sip", "version": env!("CARGO_PKG_VERSION") }] },
+            "component": { "type": "application", "bom-ref": project, "name": project },
+        },
+        "dependencies": [{
+            "ref": project,
+            "dependsOn": entries.iter().filter_map(|c| c["bom-ref"].as_str()).collect::<Vec<_>>(),
+        }],
+        "components": entries,
+    })
+}
+
+// spdx ids allow letters, digits, `.` and `-`
+fn spdx_safe(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' }).collect()
+}
+
+pub fn spdx(project: &str, components: &[Component], created: &str) -> Value {
+    let root = "SPDXRef-Project";
+    let mut packages = vec![json!({
+        "SPDXID": root,
+        "name": project,
+        "downloadLocation": "NOASSERTION",
+        "filesAnalyzed": false,
+        "licenseConcluded": "NOASSERTION",
+        "licenseDeclared": "NOASSERTION",
+        "copyrightText": "NOASSERTION",
+    })];
+    let mut relationships = vec![json!({ "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": root })];
+
+    for (index, component) in components.iter().enumerate() {
+        let id = format!("SPDXRef-Package-{}-{}", index + 1, spdx_safe(&component.name));
+        let mut package = json!({
+            "SPDXID": id,
+            "name": component.name,
+            "downloadLocation": "NOASSERTION",
+            "filesAnalyzed": false,
+            // the declared license is the package's own claim, nobody concluded anything
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": component.license.as_deref().unwrap_or("NOASSERTION"),
+            "copyrightText": "NOASSERTION",
+            "externalRefs": [{ "referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": component.purl() }],
+            "annotations": component.properties().into_iter().map(|(name, value)| json!({
+                "annotationDate": created,
+                "annotationType": "OTHER",
+                "annotator": format!("Tool: {}", TOOL),
+                "comment": format!("{}={}", name, value),
+            })).collect::<Vec<_>>(),
+        });
+        if let Some(version) = &component.version {
+            package["versionInfo"] = json!(version);
+        }
+        if let Some((alg, content)) = component.hash.as_deref().and_then(digest) {
+            package["checksums"] = json!([{ "algorithm": alg.replace('-', ""), "checksumValue": content }]);
+        }
+        packages.push(package);
+        relationships.push(json!({ "spdxElementId": root, "relationshipType": "DEPENDS_ON", "relatedSpdxElement": id }));
+    }
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": project,
+        "documentNamespace": format!("https://spdx.org/spdxdocs/{}-{}", spdx_safe(project), document_uuid(project, components, created)),
+        "creationInfo": { "created": created, "creators": [format!("Tool: {}", TOOL)] },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn components() -> Vec<Component> {
+        vec![
+            Component {
+                name: "left-pad".to_string(),
+                version: Some("1.3.0".to_string()),
+                language: Language::Node,
+                hash: Some("sha512-3q2+7w==".to_string()),
+                license: Some("WTFPL".to_string()),
+                record: Some(PackageRecord {
+                    name: "left-pad".to_string(),
+                    version: "1.3.0".to_string(),
+                    trust_score: 8.5,
+                    endorsed_by: vec!["alice".to_string()],
+                    last_reviewed: "2026-05-01".to_string(),
+                    ..Default::default()
+                }),
+                layer: Some("community".to_string()),
+                verdict: Verdict::Trusted,
+            },
+            Component {
+                name: "@types/node".to_string(),
+                version: Some("^20".to_string()),
+                language: Language::Node,
+                hash: None,
+                license: None,
+                record: None,
+                layer: None,
+                verdict: Verdict::Untrusted,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_purls_and_digests() {
+        assert_eq!(purl("Django_Rest", Some("3.14.0"), &Language::Python), "pkg:pypi/django-rest@3.14.0");
+        assert_eq!(purl("@types/node", Some("20.1.0"), &Language::Node), "pkg:npm/%40types/node@20.1.0");
+        assert_eq!(purl("github.com/spf13/cobra", Some("1.8.0"), &Language::Go), "pkg:golang/github.com/spf13/cobra@v1.8.0");
+        assert_eq!(purl("org.slf4j:slf4j-api", Some("2.0.9"), &Language::Java), "pkg:maven/org.slf4j/slf4j-api@2.0.9");
+        assert_eq!(purl("serde", Some("^1.0"), &Language::Rust), "pkg:cargo/serde");
+
+        assert_eq!(digest("sha256:ABCD"), Some(("SHA-256", "abcd".to_string())));
+        assert_eq!(digest("sha512-3q2+7w=="), Some(("SHA-512", "deadbeef".to_string())));
+        assert_eq!(digest("sha256:manual"), None);
+        assert_eq!(digest("h1:FEBL="), None);
+
+        assert!(hash_conflict("sha512:deadbeff", "sha512-3q2+7w=="));
+        assert!(!hash_conflict("sha512:DEADBEEF", "sha512-3q2+7w=="));
+        assert!(!hash_conflict("sha256:abcd", "sha512-3q2+7w=="));
+        assert!(!hash_conflict("sha256:abcd", "h1:FEBL="));
+    }
+
+    #[test]
+    fn test_parse_purls() {
+        assert_eq!(parse_purl("pkg:npm/%40types/node@20.1.0"), Some((Language::Node, "@types/node".to_string(), Some("20.1.0".to_string()))));
+        assert_eq!(parse_purl("pkg:golang/github.com/spf13/cobra@v1.8.0?type=module"), Some((Language::Go, "github.com/spf13/cobra".to_string(), Some("1.8.0".to_string()))));
+        assert_eq!(parse_purl("pkg:maven/org.slf4j/slf4j-api@2.0.9"), Some((Language::Java, "org.slf4j:slf4j-api".to_string(), Some("2.0.9".to_string()))));
+        assert_eq!(parse_purl("pkg:cargo/serde"), Some((Language::Rust, "serde".to_string(), None)));
+        assert_eq!(parse_purl("pkg:deb/debian/curl@7.50.3-1"), None);
+        assert_eq!(parse_purl("not a purl"), None);
+
+        // every purl sip writes reads back as the same package
+        for component in components() {
+            let (language, name, _) = parse_purl(&component.purl()).unwrap();
+            assert_eq!((language, name), (component.language, component.name));
+        }
+    }
+
+    #[test]
+    fn test_read_components_round_trip() {
+        let created = "2026-10-18T00:00:00Z";
+        for document in [cyclonedx("app", &components(), created), spdx("app", &components(), created)] {
+            let (dependencies, unsupported) = read_components(&document.to_string(), "bom.json").unwrap();
+            let mut names: Vec<String> = dependencies.iter().map(|d| format!("{} {:?} {:?}", d.name, d.version, d.hash)).collect();
+            names.sort();
+            assert_eq!(names, vec!["@types/node None None", "left-pad Some(\"1.3.0\") Some(\"sha512:deadbeef\")"]);
+            assert!(unsupported.is_empty());
+        }
+
+        let bom = r#"{"bomFormat": "CycloneDX", "components": [
+            {"name": "rails", "purl": "pkg:gem/rails@7.1.0", "hashes": [{"alg": "SHA-1", "content": "aa"}, {"alg": "SHA-256", "content": "BB"}],
+             "components": [{"name": "rack", "purl": "pkg:gem/rack@3.0.8"}]},
+            {"name": "curl", "purl": "pkg:deb/debian/curl@7.50.3-1"},
+            {"name": "vendored"}
+        ]}"#;
+        let (dependencies, unsupported) = read_components(bom, "bom.json").unwrap();
+        assert_eq!(dependencies.iter().map(|d| d.name.as_str()).collect::<Vec<_>>(), vec!["rails", "rack"]);
+        assert_eq!(dependencies[0].hash.as_deref(), Some("sha256:bb"));
+        assert_eq!(dependencies[1].hash, None);
+        assert_eq!(unsupported, vec!["pkg:deb/debian/curl@7.50.3-1", "vendored"]);
+        assert!(read_components("{}", "bom.json").is_err());
+    }
+
+    #[test]
+    fn test_cyclonedx_document() {
+        let bom = cyclonedx("app", &components(), "2026-10-18T00:00:00Z");
+        assert_eq!(bom["specVersion"], "1.5");
+        let left_pad = &bom["components"][0];
+        assert_eq!(left_pad["purl"], "pkg:npm/left-pad@1.3.0");
+        assert_eq!(left_pad["hashes"][0]["content"], "deadbeef");
+        assert_eq!(left_pad["licenses"][0]["expression"], "WTFPL");
+        let properties = left_pad["properties"].as_array().unwrap();
+        assert!(properties.contains(&json!({ "name": "sip:trust_score", "value": "8.5" })));
+        assert!(properties.contains(&json!({ "name": "sip:decision", "value": "trusted" })));
+        assert!(bom["components"][1].get("hashes").is_none());
+        assert_eq!(bom["dependencies"][0]["dependsOn"].as_array().unwrap().len(), 2);
+
+        // a second range of the same package keeps its own ref
+        let mut ranged = components();
+        let mut older = components().remove(1);
+        older.version = Some("^18".to_string());
+        ranged.push(older);
+        let bom = cyclonedx("app", &ranged, "2026-10-18T00:00:00Z");
+        assert_eq!(bom["components"][1]["bom-ref"], "pkg:npm/%40types/node");
+        assert_eq!(bom["components"][2]["bom-ref"], "pkg:npm/%40types/node?package-id=2");
+        assert_eq!(bom["dependencies"][0]["dependsOn"][2], "pkg:npm/%40types/node?package-id=2");
+    }
+
+    #[test]
+    fn test_spdx_document() {
+        let doc = spdx("app", &components(), "2026-10-18T00:00:00Z");
+        assert_eq!(doc["spdxVersion"], "SPDX-2.3");
+        let packages = doc["packages"].as_array().unwrap();
+        assert_eq!(packages.len(), 3);
+        assert_eq!(packages[1]["licenseDeclared"], "WTFPL");
+        assert_eq!(packages[1]["checksums"][0]["algorithm"], "SHA512");
+        assert_eq!(packages[2]["SPDXID"], "SPDXRef-Package-2--types-node");
+        assert_eq!(packages[2]["licenseDeclared"], "NOASSERTION");
+        assert_eq!(doc["relationships"].as_array().unwrap().len(), 3);
+    }
+}
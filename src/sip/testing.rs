@@ -0,0 +1,17 @@
+// fixtures shared by the unit tests
+
+use crate::sip::package::PackageRecord;
+
+// a record that passes the registry schema; tests override the fields they look at
+pub fn record(name: &str, version: &str) -> PackageRecord {
+    PackageRecord {
+        name: name.to_string(),
+        version: version.to_string(),
+        hash: "sha256:00".to_string(),
+        trust_score: 9.0,
+        endorsed_by: vec!["core-team".to_string()],
+        last_reviewed: "2024-01-01".to_string(),
+        source: "https://example.com".to_string(),
+        ..Default::default()
+    }
+}
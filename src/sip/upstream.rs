@@ -0,0 +1,536 @@
+// per-version lookups against upstream registries, used to re-check records that are
+// already in the registry rather than to create new ones (see fetch.rs for that)
+
+use crate::cli::Language;
+use crate::sip::fetch::{download_artifact, unpack_artifact};
+use crate::sip::license;
+use crate::sip::maven;
+use crate::sip::normalize::normalize_name;
+use crate::sip::version::compare_versions;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// what the upstream registry says about one exact version
+#[derive(Debug, Clone, Default)]
+pub struct UpstreamRelease {
+    // every hash upstream publishes for the version (one per artifact), preferred first
+    pub hashes: Vec<String>,
+    // yanked on crates.io and pypi, retracted in go, deprecated on npm
+    pub yanked: bool,
+    pub yank_reason: Option<String>,
+}
+
+// crates.io rejects requests without a user agent
+pub fn client() -> Result<reqwest::Client, reqwest::Error> {
+    reqwest::Client::builder()
+        .user_agent(concat!("sip/", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+// None when upstream has no such package or version
+pub async fn get_json<T: serde::de::DeserializeOwned>(client: &reqwest::Client, url: &str) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    let resp = client.get(url).send().await?;
+    if matches!(resp.status().as_u16(), 404 | 410) {
+        return Ok(None);
+    }
+    Ok(Some(resp.error_for_status()?.json().await?))
+}
+
+async fn get_text(client: &reqwest::Client, url: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let resp = client.get(url).send().await?;
+    if matches!(resp.status().as_u16(), 404 | 410) {
+        return Ok(None);
+    }
+    Ok(Some(resp.error_for_status()?.text().await?))
+}
+
+// ecosystems whose upstream can be queried per version
+pub fn supports(lang: &Language) -> bool {
+    matches!(lang, Language::Python | Language::Rust | Language::Go | Language::Node)
+}
+
+pub async fn fetch_release(client: &reqwest::Client, name: &str, version: &str, lang: &Language) -> Result<Option<UpstreamRelease>, Box<dyn std::error::Error>> {
+    match lang {
+        Language::Python => pypi_release(client, name, version).await,
+        Language::Rust => crates_release(client, name, version).await,
+        Language::Go => go_release(client, name, version).await,
+        Language::Node => npm_release(client, name, version).await,
+        _ => Err(format!("per-version lookups are not supported for {} yet", lang).into()),
+    }
+}
+
+// every published version, in no particular order
+pub async fn fetch_versions(client: &reqwest::Client, name: &str, lang: &Language) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    match lang {
+        Language::Python => {
+            let resp: Option<PyPiProject> = get_json(client, &format!("https://pypi.org/pypi/{}/json", name)).await?;
+            Ok(resp.map(|p| p.releases.into_keys().collect()).unwrap_or_default())
+        }
+        Language::Rust => {
+            let resp: Option<CrateVersions> = get_json(client, &format!("https://crates.io/api/v1/crates/{}/versions", name)).await?;
+            Ok(resp
+                .map(|c| c.versions.into_iter().filter(|v| !v.yanked).map(|v| v.num).collect())
+                .unwrap_or_default())
+        }
+        Language::Go => {
+            let list = get_text(client, &format!("https://proxy.golang.org/{}/@v/list", normalize_name(name, lang))).await?;
+            Ok(list
+                .unwrap_or_default()
+                .lines()
+                .map(|v| v.trim_start_matches('v').to_string())
+                .filter(|v| !v.is_empty())
+                .collect())
+        }
+        Language::Node => {
+            let resp: Option<NpmPackument> = get_json(client, &format!("https://registry.npmjs.org/{}", name.replace('/', "%2f"))).await?;
+            Ok(resp.map(|p| p.versions.into_keys().collect()).unwrap_or_default())
+        }
+        _ => Err(format!("version listing is not supported for {} yet", lang).into()),
+    }
+}
+
+// the spdx license of a version (the latest when none is given); None when upstream does not
+// know the package or records no license. go modules have no license metadata, so the LICENSE
+// files of the module zip are read instead
+pub async fn fetch_license(client: &reqwest::Client, name: &str, version: Option<&str>, lang: &Language) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    match lang {
+        Language::Python => {
+            let url = match version {
+                Some(v) => format!("https://pypi.org/pypi/{}/{}/json", name, v),
+                None => format!("https://pypi.org/pypi/{}/json", name),
+            };
+            let resp: Option<PyPiLicense> = get_json(client, &url).await?;
+            Ok(resp.and_then(|r| license::pypi_license(r.info.license_expression.as_deref(), &r.info.classifiers, r.info.license.as_deref())))
+        }
+        Language::Rust => {
+            let resp: Option<CrateVersions> = get_json(client, &format!("https://crates.io/api/v1/crates/{}/versions", name)).await?;
+            // newest first, so the latest is the first version that is not yanked
+            let versions = resp.map(|c| c.versions).unwrap_or_default();
+            let found = match version {
+                Some(v) => versions.into_iter().find(|c| c.num == v),
+                None => versions.into_iter().find(|c| !c.yanked),
+            };
+            Ok(found.and_then(|c| c.license).and_then(|l| license::spdx_expression(&l)))
+        }
+        Language::Node => {
+            let url = format!("https://registry.npmjs.org/{}/{}", name.replace('/', "%2f"), version.unwrap_or("latest"));
+            let resp: Option<serde_json::Value> = get_json(client, &url).await?;
+            Ok(resp.and_then(|manifest| license::npm_license(manifest.get("license"), manifest.get("licenses"))))
+        }
+        Language::Go => {
+            let artifact = download_artifact(client, name, version, lang).await?;
+            Ok(license::license_from_files(&unpack_artifact(&artifact.bytes)?))
+        }
+        _ => Err(format!("license lookups are not supported for {} yet", lang).into()),
+    }
+}
+
+#[derive(Deserialize)]
+struct PyPiLicenseInfo {
+    license_expression: Option<String>,
+    license: Option<String>,
+    #[serde(default)]
+    classifiers: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PyPiLicense {
+    info: PyPiLicenseInfo,
+}
+
+// owners added and removed since a record was reviewed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OwnershipChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl OwnershipChange {
+    // records reviewed before owners were captured have nothing to compare against
+    pub fn between(recorded: &[String], current: &[String]) -> Option<OwnershipChange> {
+        if recorded.is_empty() || current.is_empty() {
+            return None;
+        }
+        let added: Vec<String> = current.iter().filter(|m| !recorded.contains(m)).cloned().collect();
+        let removed: Vec<String> = recorded.iter().filter(|m| !current.contains(m)).cloned().collect();
+        (!added.is_empty() || !removed.is_empty()).then_some(OwnershipChange { added, removed })
+    }
+}
+
+impl std::fmt::Display for OwnershipChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts: Vec<String> = self.added.iter().map(|m| format!("+{}", m)).collect();
+        parts.extend(self.removed.iter().map(|m| format!("-{}", m)));
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+// the current owner set, sorted; None when upstream does not know the package
+pub async fn fetch_maintainers(client: &reqwest::Client, name: &str, lang: &Language) -> Result<Option<Vec<String>>, Box<dyn std::error::Error>> {
+    let maintainers = match lang {
+        Language::Python => pypi_maintainers(client, name).await?,
+        Language::Rust => {
+            let resp: Option<CrateOwners> = get_json(client, &format!("https://crates.io/api/v1/crates/{}/owners", name)).await?;
+            resp.map(|r| r.users.into_iter().map(|u| u.login).collect())
+        }
+        Language::Go => go_owner(client, name).await?.map(|owner| vec![owner]),
+        Language::Node => {
+            let url = format!("https://registry.npmjs.org/{}/latest", name.replace('/', "%2f"));
+            let resp: Option<NpmMaintainers> = get_json(client, &url).await?;
+            resp.map(|r| r.maintainers.into_iter().map(|m| m.name).collect())
+        }
+        _ => return Err(format!("owner lookups are not supported for {} yet", lang).into()),
+    };
+    Ok(maintainers.map(|mut m: Vec<String>| {
+        m.sort();
+        m.dedup();
+        m
+    }).filter(|m| !m.is_empty()))
+}
+
+// pypi only exposes roles over xml-rpc
+async fn pypi_maintainers(client: &reqwest::Client, name: &str) -> Result<Option<Vec<String>>, Box<dyn std::error::Error>> {
+    let body = package_roles_request(name);
+    let resp = client.post("https://pypi.org/pypi").header("Content-Type", "text/xml").body(body).send().await?;
+    Ok(Some(parse_package_roles(&resp.error_for_status()?.text().await?)))
+}
+
+// the name comes from the command line, so it is escaped rather than spliced into the xml
+fn package_roles_request(name: &str) -> String {
+    let escaped = name
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;");
+    format!(
+        "<?xml version=\"1.0\"?><methodCall><methodName>package_roles</methodName><params><param><value><string>{}</string></value></param></params></methodCall>",
+        escaped
+    )
+}
+
+// the response is a list of [role, username] pairs
+fn parse_package_roles(xml: &str) -> Vec<String> {
+    maven::xml_blocks(xml, "string")
+        .chunks(2)
+        .filter_map(|pair| pair.get(1))
+        .map(|user| user.trim().to_string())
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct CrateOwner {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct CrateOwners {
+    users: Vec<CrateOwner>,
+}
+
+#[derive(Deserialize)]
+struct NpmMaintainer {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct NpmMaintainers {
+    #[serde(default)]
+    maintainers: Vec<NpmMaintainer>,
+}
+
+// go modules have no registry accounts, whoever controls the repository owns the module;
+// vanity paths are resolved through their go-import meta tag
+async fn go_owner(client: &reqwest::Client, module: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    if let Some(owner) = repository_owner(module) {
+        return Ok(Some(owner));
+    }
+    Ok(go_repository(client, module).await?.and_then(|repo| repository_owner(&repo)))
+}
+
+// the repository a vanity module path redirects to; a module on a forge is its own repository,
+// so there is nothing to resolve
+pub async fn go_repository(client: &reqwest::Client, module: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    if repository_owner(module).is_some() {
+        return Ok(None);
+    }
+    let Some(html) = get_text(client, &format!("https://{}?go-get=1", module)).await? else {
+        return Ok(None);
+    };
+    Ok(parse_go_import(&html, module))
+}
+
+// host/owner for repositories on the well-known forges
+pub fn repository_owner(url: &str) -> Option<String> {
+    let path = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let mut parts = path.trim_end_matches(".git").split('/');
+    let host = parts.next()?;
+    let owner = parts.next().filter(|owner| !owner.is_empty())?;
+    matches!(host, "github.com" | "gitlab.com" | "bitbucket.org" | "codeberg.org").then(|| format!("{}/{}", host, owner))
+}
+
+// repository url from `<meta name="go-import" content="prefix vcs url">`
+fn parse_go_import(html: &str, module: &str) -> Option<String> {
+    html.split("<meta").skip(1).find_map(|tag| {
+        if !tag.contains("name=\"go-import\"") {
+            return None;
+        }
+        let content = tag.split_once("content=\"")?.1.split('"').next()?;
+        let mut fields = content.split_whitespace();
+        let (prefix, _vcs, url) = (fields.next()?, fields.next()?, fields.next()?);
+        module.starts_with(prefix).then(|| url.to_string())
+    })
+}
+
+#[derive(Deserialize)]
+struct PyPiFile {
+    packagetype: String,
+    digests: HashMap<String, String>,
+    #[serde(default)]
+    yanked: bool,
+    yanked_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PyPiVersion {
+    urls: Vec<PyPiFile>,
+}
+
+#[derive(Deserialize)]
+struct PyPiProject {
+    releases: HashMap<String, serde_json::Value>,
+}
+
+async fn pypi_release(client: &reqwest::Client, name: &str, version: &str) -> Result<Option<UpstreamRelease>, Box<dyn std::error::Error>> {
+    let Some(resp) = get_json::<PyPiVersion>(client, &format!("https://pypi.org/pypi/{}/{}/json", name, version)).await? else {
+        return Ok(None);
+    };
+
+    // the sdist is what fetch_from_pypi records, so it goes first
+    let mut files = resp.urls;
+    files.sort_by_key(|f| f.packagetype != "sdist");
+    Ok(Some(UpstreamRelease {
+        yanked: !files.is_empty() && files.iter().all(|f| f.yanked),
+        yank_reason: files.iter().find_map(|f| f.yanked_reason.clone()).filter(|reason| !reason.is_empty()),
+        hashes: files
+            .iter()
+            .filter_map(|f| f.digests.get("sha256"))
+            .map(|sha| format!("sha256:{}", sha))
+            .collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+struct CrateVersion {
+    num: String,
+    checksum: String,
+    yanked: bool,
+    yank_message: Option<String>,
+    license: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CrateVersionResponse {
+    version: CrateVersion,
+}
+
+#[derive(Deserialize)]
+struct CrateVersions {
+    versions: Vec<CrateVersion>,
+}
+
+async fn crates_release(client: &reqwest::Client, name: &str, version: &str) -> Result<Option<UpstreamRelease>, Box<dyn std::error::Error>> {
+    let resp: Option<CrateVersionResponse> = get_json(client, &format!("https://crates.io/api/v1/crates/{}/{}", name, version)).await?;
+    Ok(resp.map(|r| UpstreamRelease {
+        hashes: vec![format!("sha256:{}", r.version.checksum)],
+        yanked: r.version.yanked,
+        yank_reason: r.version.yank_message,
+    }))
+}
+
+// the checksum database holds the go.sum line for every public module version
+async fn go_release(client: &reqwest::Client, name: &str, version: &str) -> Result<Option<UpstreamRelease>, Box<dyn std::error::Error>> {
+    let version = format!("v{}", version.trim_start_matches('v'));
+    let url = format!("https://sum.golang.org/lookup/{}@{}", normalize_name(name, &Language::Go), version);
+    let Some(body) = get_text(client, &url).await? else {
+        return Ok(None);
+    };
+
+    let hashes = body
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(_), Some(v), Some(hash)) if v == version => Some(hash.to_string()),
+                _ => None,
+            }
+        })
+        .collect();
+
+    // a missing or unreadable go.mod only means the retraction check is skipped
+    let retraction = go_retraction(client, name, &version).await.ok().flatten();
+    Ok(Some(UpstreamRelease {
+        hashes,
+        yanked: retraction.is_some(),
+        yank_reason: retraction.and_then(|r| r.reason),
+    }))
+}
+
+#[derive(Deserialize)]
+struct GoProxyInfo {
+    #[serde(rename = "Version")]
+    version: String,
+}
+
+// a `retract` directive: one version or an inclusive [low, high] range
+#[derive(Debug, Clone, PartialEq)]
+struct Retraction {
+    low: String,
+    high: String,
+    reason: Option<String>,
+}
+
+impl Retraction {
+    fn covers(&self, version: &str) -> bool {
+        let version = version.trim_start_matches('v');
+        compare_versions(version, &self.low).is_ge() && compare_versions(version, &self.high).is_le()
+    }
+}
+
+// retractions are read from the go.mod of the module's latest version
+async fn go_retraction(client: &reqwest::Client, name: &str, version: &str) -> Result<Option<Retraction>, Box<dyn std::error::Error>> {
+    let base = format!("https://proxy.golang.org/{}", normalize_name(name, &Language::Go));
+    let Some(latest) = get_json::<GoProxyInfo>(client, &format!("{}/@latest", base)).await? else {
+        return Ok(None);
+    };
+    let Some(go_mod) = get_text(client, &format!("{}/@v/{}.mod", base, latest.version)).await? else {
+        return Ok(None);
+    };
+    Ok(parse_retractions(&go_mod).into_iter().find(|r| r.covers(version)))
+}
+
+// the rationale is the comment on the directive's line, or the comment block right above it
+fn parse_retractions(go_mod: &str) -> Vec<Retraction> {
+    let mut retractions = Vec::new();
+    let mut comments: Vec<String> = Vec::new();
+    // rationale written above a `retract (` block applies to entries without their own
+    let mut block: Option<Vec<String>> = None;
+
+    for line in go_mod.lines().map(str::trim) {
+        if let Some(comment) = line.strip_prefix("//") {
+            comments.push(comment.trim().to_string());
+            continue;
+        }
+
+        let spec = match &block {
+            Some(_) if line == ")" => {
+                block = None;
+                None
+            }
+            Some(_) => Some(line),
+            None => match line.strip_prefix("retract").map(str::trim) {
+                Some("(") => {
+                    block = Some(std::mem::take(&mut comments));
+                    continue;
+                }
+                spec => spec,
+            },
+        };
+
+        if let Some(spec) = spec.filter(|spec| !spec.is_empty()) {
+            let (spec, comment) = match spec.split_once("//") {
+                Some((spec, comment)) => (spec.trim(), Some(comment.trim().to_string())),
+                None => (spec, None),
+            };
+            let rationale = comment
+                .or_else(|| (!comments.is_empty()).then(|| comments.join(" ")))
+                .or_else(|| block.as_ref().filter(|c| !c.is_empty()).map(|c| c.join(" ")));
+            let (low, high) = match spec.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                Some(range) => match range.split_once(',') {
+                    Some((low, high)) => (low.trim(), high.trim()),
+                    None => continue,
+                },
+                None => (spec, spec),
+            };
+            retractions.push(Retraction {
+                low: low.trim_start_matches('v').to_string(),
+                high: high.trim_start_matches('v').to_string(),
+                reason: rationale.filter(|r| !r.is_empty()),
+            });
+        }
+        comments.clear();
+    }
+    retractions
+}
+
+#[derive(Deserialize)]
+struct NpmDist {
+    integrity: Option<String>,
+    shasum: String,
+}
+
+#[derive(Deserialize)]
+struct NpmVersion {
+    dist: NpmDist,
+    deprecated: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NpmPackument {
+    versions: HashMap<String, serde_json::Value>,
+}
+
+async fn npm_release(client: &reqwest::Client, name: &str, version: &str) -> Result<Option<UpstreamRelease>, Box<dyn std::error::Error>> {
+    let url = format!("https://registry.npmjs.org/{}/{}", name.replace('/', "%2f"), version);
+    let resp: Option<NpmVersion> = get_json(client, &url).await?;
+    Ok(resp.map(|r| UpstreamRelease {
+        hashes: r.dist.integrity.into_iter().chain([format!("sha1:{}", r.dist.shasum)]).collect(),
+        // npm cannot unpublish after 72 hours, deprecation is how authors pull a release
+        yanked: r.deprecated.is_some(),
+        yank_reason: r.deprecated,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retractions() {
+        let go_mod = "module example.com/m\n\ngo 1.21\n\n// published too early\nretract v1.0.0\n\nretract [v1.1.0, v1.1.3] // leaks credentials\n\n// broken builds\nretract (\n    v1.2.0\n    v1.2.1 // bad tag\n)\n";
+        let retractions = parse_retractions(go_mod);
+        let reason = |v: &str| retractions.iter().find(|r| r.covers(v)).map(|r| r.reason.clone());
+
+        assert_eq!(retractions.len(), 4);
+        assert_eq!(reason("1.0.0"), Some(Some("published too early".to_string())));
+        assert_eq!(reason("v1.1.2"), Some(Some("leaks credentials".to_string())));
+        assert_eq!(reason("1.2.0"), Some(Some("broken builds".to_string())));
+        assert_eq!(reason("1.2.1"), Some(Some("bad tag".to_string())));
+        assert_eq!(reason("1.1.4"), None);
+    }
+
+    #[test]
+    fn test_owner_lookups() {
+        let roles = "<methodResponse><params><param><value><array><data>\
+            <value><array><data><value><string>Owner</string></value><value><string>alice</string></value></data></array></value>\
+            <value><array><data><value><string>Maintainer</string></value><value><string>bob</string></value></data></array></value>\
+            </data></array></value></param></params></methodResponse>";
+        assert_eq!(parse_package_roles(roles), vec!["alice", "bob"]);
+        assert!(package_roles_request("x</string><string>y").contains("<string>x&lt;/string&gt;&lt;string&gt;y</string>"));
+
+        let html = r#"<html><head><meta name="go-import" content="golang.org/x/net git https://go.googlesource.com/net"><meta name="go-import" content="example.org/lib git https://github.com/example/lib.git"></head></html>"#;
+        assert_eq!(parse_go_import(html, "example.org/lib/v2").as_deref(), Some("https://github.com/example/lib.git"));
+        assert_eq!(repository_owner("https://github.com/example/lib.git").as_deref(), Some("github.com/example"));
+        assert_eq!(repository_owner("github.com/gin-gonic/gin").as_deref(), Some("github.com/gin-gonic"));
+        assert_eq!(repository_owner("https://go.googlesource.com/net"), None);
+    }
+
+    #[test]
+    fn test_ownership_change() {
+        let recorded = vec!["alice".to_string(), "bob".to_string()];
+        assert_eq!(OwnershipChange::between(&recorded, &recorded), None);
+        assert_eq!(OwnershipChange::between(&[], &recorded), None);
+        let change = OwnershipChange::between(&recorded, &["alice".to_string(), "mallory".to_string()]).unwrap();
+        assert_eq!(change.to_string(), "+mallory -bob");
+    }
+}
@@ -1,38 +1,142 @@
 use crate::cli::Language;
 use crate::sip::registry::Registry;
-use crate::sip::package::PackageRecord;
+use crate::sip::config::{ReviewConfig, StaleAction};
+use crate::sip::package::{BlockedRecord, PackageRecord};
+use crate::sip::policy::{Policy, PolicyVerdict, Subject};
+use crate::sip::review::{self, Staleness};
+use crate::sip::upstream::OwnershipChange;
+
+// minimum trust score for a package to be considered trusted
+pub const DEFAULT_TRUST_THRESHOLD: f64 = 8.0;
+
+// a release its upstream registry has withdrawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct Yanked {
+    pub version: String,
+    pub reason: Option<String>,
+}
+
+impl Yanked {
+    pub fn from_record(record: &PackageRecord) -> Option<Yanked> {
+        record.yanked.then(|| Yanked {
+            version: record.version.clone(),
+            reason: record.yank_reason.clone(),
+        })
+    }
+}
+
+impl std::fmt::Display for Yanked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "version {} was yanked upstream", self.version)?;
+        if let Some(reason) = &self.reason {
+            write!(f, ": {}", reason)?;
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug)]
 pub struct VerificationResult {
     pub package_name: String,
     pub package_entry: Option<PackageRecord>,
+    // registry layer the effective record came from
+    pub layer: Option<String>,
+    // set when the package (or the requested version) is on a blocklist
+    pub blocked: Option<BlockedRecord>,
+    // set when the entry is past its review age
+    pub stale: Option<Staleness>,
+    pub stale_action: StaleAction,
+    // set when the requested (or recorded) version was withdrawn upstream
+    pub yanked: Option<Yanked>,
+    // set when upstream owners differ from the ones recorded at review time
+    pub ownership: Option<OwnershipChange>,
+    // organization policy rules that matched, and the one that decided
+    pub policy: PolicyVerdict,
     pub trust_score: Option<f64>,
     pub is_trusted: bool,
     pub trust_threshold: f64,
 }
 
 impl VerificationResult {
+    fn display_policy(&self) {
+        for matched in self.policy.matches() {
+            println!("{} {}", matched.marker(), matched);
+        }
+    }
+    
     pub fn display(&self) {
         println!("package: {}", self.package_name);
         
+        if let Some(blocked) = &self.blocked {
+            println!("✘ blocked: {}", blocked.reason);
+            if !blocked.versions.is_empty() {
+                println!("affected versions: {}", blocked.versions.join(", "));
+            }
+            if let Some(reference) = &blocked.reference {
+                println!("reference: {}", reference);
+            }
+            return;
+        }
+        
         match &self.package_entry {
             Some(entry) => {
                 println!("version: {}", entry.version);
-                println!("trust score: {:.1}/10.0", entry.trust_score);
+                if let Some(score) = self.trust_score {
+                    println!("trust score: {:.1}/10.0", score);
+                }
                 println!("last reviewed: {}", entry.last_reviewed);
                 println!("endorsed by: {}", entry.endorsed_by.join(", "));
+                if !entry.maintainers.is_empty() {
+                    println!("maintainers: {}", entry.maintainers.join(", "));
+                }
+                if let Some(scan) = &entry.install_scan {
+                    println!("install-time scan: {} finding(s) on {}", scan.findings.len(), scan.scanned_on);
+                }
                 
                 println!("source: {}", entry.source);
+                if let Some(layer) = &self.layer {
+                    println!("registry layer: {}", layer);
+                }
+                
+                if let Some(yanked) = &self.yanked {
+                    println!("⚠ {}", yanked);
+                }
+                
+                if let Some(change) = &self.ownership {
+                    println!("⚠ ownership changed since last review ({}): {}", entry.last_reviewed, change);
+                }
                 
-                if self.is_trusted {
+                if let Some(stale) = &self.stale {
+                    let marker = if self.stale_action == StaleAction::Block { "✘" } else { "⚠" };
+                    println!("{} review is stale: {}", marker, stale);
+                }
+                
+                self.display_policy();
+                if self.policy.denied() {
+                    println!("✘ not trusted (denied by policy)");
+                } else if self.policy.allowed() {
+                    println!("✓ trusted (allowed by policy)");
+                } else if self.is_trusted && self.trust_threshold > 0.0 {
                     println!("✓ trusted (meets threshold of {:.1})", self.trust_threshold);
+                } else if self.is_trusted {
+                    println!("✓ trusted");
+                } else if self.stale.is_some() && self.trust_score.is_some_and(|score| score >= self.trust_threshold) {
+                    println!("✘ not trusted until re-reviewed (review policy blocks stale entries)");
                 } else {
                     println!("⚠ below trust threshold (requires {:.1})", self.trust_threshold);
                 }
             }
             None => {
-                println!("✘ not found in trusted registry");
-                println!("this package has not been reviewed or endorsed");
+                if let Some(yanked) = &self.yanked {
+                    println!("⚠ {}", yanked);
+                }
+                self.display_policy();
+                if self.policy.allowed() {
+                    println!("✓ not in trusted registry, but allowed by policy without review");
+                } else {
+                    println!("✘ not found in trusted registry");
+                    println!("this package has not been reviewed or endorsed");
+                }
             }
         }
     }
@@ -40,24 +144,55 @@ impl VerificationResult {
 
 pub fn verify_package(
     package: &str, 
-    _version: Option<&str>, 
+    version: Option<&str>, 
     lang: &Language,
     registry: &Registry,
     trust_threshold: f64,
+    review_config: &ReviewConfig,
+    policy: &Policy,
 ) -> Result<VerificationResult, Box<dyn std::error::Error>> {
-    let package_entry = registry.lookup_package(package, lang);
-    
-    let (trust_score, is_trusted) = match &package_entry {
-        Some(entry) => {
-            let score = entry.trust_score;
-            (Some(score), score >= trust_threshold)
-        }
-        None => (None, false),
+    // prefer the record for the requested version when it was reviewed
+    let package_entry = match version {
+        Some(v) => match registry.lookup_version(package, v, lang)? {
+            Some(entry) => Some(entry),
+            None => registry.lookup_package(package, lang)?,
+        },
+        None => registry.lookup_package(package, lang)?,
     };
     
+    let blocked = registry.lookup_block(package, version, lang)?;
+    let stale = package_entry.and_then(|entry| review::staleness(entry.record, lang, review_config, review::today()));
+    let stale_action = review_config.on_stale;
+    // the record's flag only speaks for its own version
+    let yanked = package_entry
+        .filter(|entry| version.is_none_or(|v| v == entry.record.version))
+        .and_then(|entry| Yanked::from_record(entry.record));
+    let verdict = policy.evaluate(&Subject {
+        name: package,
+        language: lang,
+        record: package_entry.map(|entry| entry.record),
+        scan: None,
+        license: None,
+    });
+    let trust_score = package_entry.map(|entry| entry.record.trust_score);
+    // blocks beat policy, a deciding policy rule beats scores and review age
+    let is_trusted = blocked.is_none()
+        && !verdict.denied()
+        && (verdict.allowed()
+            || (!(stale.is_some() && stale_action == StaleAction::Block)
+                && trust_score.is_some_and(|score| score >= trust_threshold)));
+    
     Ok(VerificationResult {
         package_name: package.to_string(),
-        package_entry: package_entry.cloned(),
+        package_entry: package_entry.map(|entry| entry.record.clone()),
+        layer: package_entry.map(|entry| entry.layer.to_string()),
+        blocked: blocked.map(|entry| entry.record.clone()),
+        stale,
+        stale_action,
+        yanked,
+        // comparing owners needs the network, callers that can reach upstream fill it in
+        ownership: None,
+        policy: verdict,
         trust_score,
         is_trusted,
         trust_threshold,
@@ -0,0 +1,94 @@
+// loose version ordering that works across ecosystems (semver, pep 440, maven qualifiers)
+
+use std::cmp::Ordering;
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Number(u64),
+    Text(&'a str),
+}
+
+fn tokenize(version: &str) -> Vec<Token<'_>> {
+    let version = version.trim().trim_start_matches('v');
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let bytes = version.as_bytes();
+
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_alphanumeric() {
+            start += 1;
+            continue;
+        }
+        let numeric = bytes[start].is_ascii_digit();
+        let mut end = start;
+        while end < bytes.len() && bytes[end].is_ascii_alphanumeric() && bytes[end].is_ascii_digit() == numeric {
+            end += 1;
+        }
+        let part = &version[start..end];
+        tokens.push(if numeric {
+            Token::Number(part.parse().unwrap_or(u64::MAX))
+        } else {
+            Token::Text(part)
+        });
+        start = end;
+    }
+
+    tokens
+}
+
+// numeric components compare numerically, and a trailing qualifier (`-rc1`, `b2`, `-SNAPSHOT`)
+// sorts before the plain release it qualifies
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (tokenize(a), tokenize(b));
+
+    for i in 0..a.len().max(b.len()) {
+        let ordering = match (a.get(i), b.get(i)) {
+            (Some(Token::Number(x)), Some(Token::Number(y))) => x.cmp(y),
+            (Some(Token::Text(x)), Some(Token::Text(y))) => x.to_lowercase().cmp(&y.to_lowercase()),
+            (Some(Token::Number(_)), Some(Token::Text(_))) => Ordering::Greater,
+            (Some(Token::Text(_)), Some(Token::Number(_))) => Ordering::Less,
+            (Some(Token::Number(_)), None) => Ordering::Greater,
+            (Some(Token::Text(_)), None) => Ordering::Less,
+            (None, Some(Token::Number(_))) => Ordering::Less,
+            (None, Some(Token::Text(_))) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
+// one pinned version, not a requirement like "^1.2", "~> 2.0", ">=3" or "[1.0,2.0)"
+pub fn is_exact(version: &str) -> bool {
+    version.starts_with(|c: char| c.is_ascii_digit() || c == 'v')
+        && version.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.3"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0.0-rc1", "2.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("v1.9.1", "1.9.1"), Ordering::Equal);
+        assert_eq!(compare_versions("32.1.2-jre", "32.1.3-jre"), Ordering::Less);
+        assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_is_exact() {
+        assert!(is_exact("3.3.6"));
+        assert!(is_exact("v1.9.1"));
+        assert!(is_exact("32.1.2-jre"));
+        assert!(!is_exact("^3.3.0"));
+        assert!(!is_exact("~> 7.1"));
+        assert!(!is_exact(">=2.0"));
+        assert!(!is_exact("[1.0,2.0)"));
+        assert!(!is_exact("*"));
+    }
+}